@@ -1,3 +1,6 @@
+import inspect
+import typing
+
 from .memflow import *
 
 
@@ -6,19 +9,73 @@ class CDataTypeMeta(type):
         return ARRAY(self, length)
 
 
+# Metadata for annotated fields, i.e. `name: Annotated[c_char * 16, Offset(0x20)]`.
+class Offset:
+    def __init__(self, offset):
+        self.offset = offset
+
+
+# Metadata for annotated bitfields, i.e. `flag: Annotated[c_uint32, Bits(1)]`.
+class Bits:
+    def __init__(self, width):
+        self.width = width
+
+
+def _fields_from_annotations(cls):
+    """Fills in `_fields_` and `_offsets_` from the class annotations (PEP 526) in declaration order.
+
+    Fields of a base class come first, the annotations of a subclass extend them."""
+    if hasattr(inspect, "get_annotations"):
+        annotations = inspect.get_annotations(cls)
+    else:
+        annotations = cls.__dict__.get("__annotations__", {})
+    if not annotations or "_fields_" in cls.__dict__:
+        return
+    try:
+        hints = typing.get_type_hints(cls, include_extras=True)
+    except TypeError:
+        # Python < 3.9 has no `Annotated`.
+        hints = typing.get_type_hints(cls)
+    # Inherited from the closest base that has them, if any.
+    fields = list(getattr(cls, "_fields_", []))
+    offsets = list(getattr(cls, "_offsets_", []))
+    for name in annotations:
+        hint = hints[name]
+        # `typing.get_origin` is only available from Python 3.8.
+        if getattr(hint, "__origin__", None) is typing.ClassVar:
+            continue
+        metadata = getattr(hint, "__metadata__", ())
+        field_type = hint.__origin__ if metadata else hint
+        offset = next((m.offset for m in metadata if isinstance(m, Offset)), None)
+        bits = next((m.width for m in metadata if isinstance(m, Bits)), None)
+        if offset is not None:
+            offsets.append((offset, name, field_type))
+        elif bits is not None:
+            fields.append((name, field_type, bits))
+        else:
+            fields.append((name, field_type))
+    cls._fields_ = fields
+    if offsets:
+        cls._offsets_ = offsets
+
+
 # TODO: Move to rust
 class Structure(object, metaclass=CDataTypeMeta):
+    def __init_subclass__(cls, **kwargs):
+        super().__init_subclass__(**kwargs)
+        _fields_from_annotations(cls)
+
     def __new__(cls, *args, **kwargs):
-        # self._endianness_ = "what to put here?"
         # unpack *args to kwargs if not already present
         for idx, val in enumerate(args):
             field_name = cls._fields_[idx][0]
             if not kwargs.get(field_name):
                 kwargs[field_name] = val
-        # Set field and offset attributes on the class
+        # Set field and offset attributes on the instance, every read yields its own values.
+        inst = super(Structure, cls).__new__(cls)
         for field_name, field_val in kwargs.items():
-            setattr(cls, field_name, field_val)
-        return super(Structure, cls).__new__(cls)
+            setattr(inst, field_name, field_val)
+        return inst
 
     def __eq__(self, other):
         if self.__class__ == other.__class__:
@@ -55,6 +112,70 @@ class Structure(object, metaclass=CDataTypeMeta):
         return "{}({})".format(self.__class__.__name__, ", ".join(fields_strs))
 
 
+class BigEndianStructure(Structure):
+    _endianness_ = "big"
+
+
+class LittleEndianStructure(Structure):
+    _endianness_ = "little"
+
+
+class Union(object, metaclass=CDataTypeMeta):
+    def __init_subclass__(cls, **kwargs):
+        super().__init_subclass__(**kwargs)
+        _fields_from_annotations(cls)
+
+    def __init__(self, *args, **kwargs):
+        # unpack *args to kwargs if not already present
+        for idx, val in enumerate(args):
+            field_name = self._fields_[idx][0]
+            if field_name not in kwargs:
+                kwargs[field_name] = val
+        for field_name, field_val in kwargs.items():
+            setattr(self, field_name, field_val)
+        # Every member is set when read from memory, leave it up to the largest member on write.
+        if len(kwargs) > 1:
+            self._set_ = None
+
+    def __setattr__(self, name, value):
+        # Track the last set member, it is the one that gets written.
+        if any(field[0] == name for field in self._fields_):
+            super().__setattr__("_set_", name)
+        super().__setattr__(name, value)
+
+    def __eq__(self, other):
+        if self.__class__ == other.__class__:
+            return all(
+                getattr(self, field[0], None) == getattr(other, field[0], None)
+                for field in self._fields_
+            )
+        else:
+            raise TypeError("Comparing object is not of the same union type.")
+
+    def _set_fields_(self):
+        return [field[0] for field in self._fields_ if hasattr(self, field[0])]
+
+    def __str__(self):
+        return " ".join(
+            f"{name}={getattr(self, name)}"
+            for name in self._set_fields_()
+            # Skip "private" fields (i.e. _pad_0x0)
+            if not name.startswith("_")
+        )
+
+    def __repr__(self):
+        fields_strs = [f"{name}={getattr(self, name)}" for name in self._set_fields_()]
+        return "{}({})".format(self.__class__.__name__, ", ".join(fields_strs))
+
+
+class BigEndianUnion(Union):
+    _endianness_ = "big"
+
+
+class LittleEndianUnion(Union):
+    _endianness_ = "little"
+
+
 # TODO: Move to rust
 class Array:
     def __init__(self, *args):
@@ -64,7 +185,7 @@ class Array:
         return self._vals_[key]
 
     def __len__(self):
-        return self._length_
+        return len(self._vals_)
 
     def __str__(self):
         return repr(self)
@@ -75,6 +196,32 @@ class Array:
         )
 
 
+# Text of a `c_char` array up to the first NUL, `raw` holds the full buffer.
+class CharArray(bytes):
+    def __new__(cls, raw=b""):
+        if isinstance(raw, str):
+            raw = raw.encode()
+        inst = super().__new__(cls, raw.split(b"\0", 1)[0])
+        inst.raw = raw
+        return inst
+
+    @property
+    def value(self):
+        return bytes(self)
+
+
+# Text of a `c_wchar` array up to the first NUL, `raw` holds the full buffer.
+class WideCharArray(str):
+    def __new__(cls, raw=""):
+        inst = super().__new__(cls, raw.split("\0", 1)[0])
+        inst.raw = raw
+        return inst
+
+    @property
+    def value(self):
+        return str(self)
+
+
 # Cache for memflow array types.
 mf_arr_types = dict()
 
@@ -86,13 +233,38 @@ def ARRAY(target_type, len):
     if target_type not in mf_arr_types[len]:
         arr_type = type(
             f"{target_type.__name__}_MF_Array_{len}",
-            (Array,),
+            (_array_base(target_type),),
             dict(_type_=target_type, _length_=len),
         )
         mf_arr_types[len][target_type] = arr_type
     return mf_arr_types[len][target_type]
 
 
+# Cache for memflow dynamic array types.
+mf_varr_types = dict()
+
+
+def VARRAY(target_type, length_field):
+    """Array ending a structure with as many items as the value of the field named `length_field`."""
+    global mf_varr_types
+    key = (target_type, length_field)
+    if key not in mf_varr_types:
+        mf_varr_types[key] = type(
+            f"{target_type.__name__}_MF_VArray_{length_field}",
+            (_array_base(target_type),),
+            dict(_type_=target_type, _length_field_=length_field),
+        )
+    return mf_varr_types[key]
+
+
+def _array_base(target_type):
+    if issubclass(target_type, c_char):
+        return CharArray
+    elif issubclass(target_type, c_wchar):
+        return WideCharArray
+    return Array
+
+
 class CDataType(object, metaclass=CDataTypeMeta):
     # Preserve ctypes shorthand type
     _type_ = ""
@@ -135,6 +307,26 @@ class c_longdouble(CDataType):
     _type_ = "g"
 
 
+# x87 extended precision without padding, override `_size_` for padded storage (i.e. 16 bytes in `FXSAVE`).
+class c_float80(c_longdouble):
+    _size_ = 10
+
+
+# Decodes into a `fractions.Fraction` without losing precision.
+class c_float80_exact(c_float80):
+    _exact_ = True
+
+
+# IEEE 754 quadruple precision, the `long double` of 64-bit Arm Unix targets.
+class c_float128(c_longdouble):
+    _size_ = 16
+    _format_ = "binary128"
+
+
+class c_float128_exact(c_float128):
+    _exact_ = True
+
+
 class c_longlong(CDataType):
     _type_ = "q"
 
@@ -163,86 +355,180 @@ class c_wchar(CDataType):
     _type_ = "u"
 
 
-# Fill in specifically-sized types
+class c_char_p(CDataType):
+    _type_ = "z"
+
+    def __init__(self, addr=0):
+        self.addr = addr
+
+    def is_null(self):
+        return self.addr == 0
+
+    def read(self, mem, max_bytes=None, encoding=None, errors=None):
+        """Follows the pointer and reads the string from `mem` (i.e. a `Process`)."""
+        if self.is_null():
+            return None
+        return mem.read_char_string(self.addr, max_bytes, encoding, errors)
+
+    def __eq__(self, other):
+        if isinstance(other, c_char_p):
+            return self.addr == other.addr
+        return self.addr == other
+
+    def __hash__(self):
+        return hash(self.addr)
+
+    def __str__(self):
+        return f"{self.__class__.__name__} @ {hex(self.addr)}"
+
+    def __repr__(self):
+        return f"{self.__class__.__name__}({hex(self.addr)})"
+
+
+class c_wchar_p(c_char_p):
+    _type_ = "Z"
+
+    def read(self, mem, max_bytes=None, encoding=None, errors=None):
+        """Follows the pointer and reads the wide string from `mem` (i.e. a `Process`)."""
+        if self.is_null():
+            return None
+        return mem.read_wchar_string(self.addr, max_bytes, encoding, errors)
+
+
+# Cache for eager string pointer types.
+mf_str_p_types = dict()
+
+
+def STRING_POINTER(ptr_type, max_bytes):
+    """String pointer type that is followed while reading, decoding to a `str` of at most `max_bytes` bytes."""
+    global mf_str_p_types
+    key = (ptr_type, max_bytes)
+    if key not in mf_str_p_types:
+        mf_str_p_types[key] = type(
+            f"{ptr_type.__name__}_{max_bytes}",
+            (ptr_type,),
+            dict(_max_bytes_=max_bytes),
+        )
+    return mf_str_p_types[key]
+
+
+def CHAR_P(max_bytes):
+    return STRING_POINTER(c_char_p, max_bytes)
+
+
+def WCHAR_P(max_bytes):
+    return STRING_POINTER(c_wchar_p, max_bytes)
+
+
+# Fill in specifically-sized types, `long` is left out as its width depends on the target data model.
 c_int8 = c_byte
 c_uint8 = c_ubyte
-for kind in [c_short, c_int, c_long, c_longlong]:
-    if sizeof(kind) == 2:
-        c_int16 = kind
-    elif sizeof(kind) == 4:
-        c_int32 = kind
-    elif sizeof(kind) == 8:
-        c_int64 = kind
-for kind in [c_ushort, c_uint, c_ulong, c_ulonglong]:
-    if sizeof(kind) == 2:
-        c_uint16 = kind
-    elif sizeof(kind) == 4:
-        c_uint32 = kind
-    elif sizeof(kind) == 8:
-        c_uint64 = kind
-del kind
+c_int16 = c_short
+c_uint16 = c_ushort
+c_int32 = c_int
+c_uint32 = c_uint
+c_int64 = c_longlong
+c_uint64 = c_ulonglong
 # Corresponds to memflow `umem` type, guaranteed to hold a whole address of introspection target.
 umem = c_uint64
 
 
-class Pointer(Structure):
-    _fields_ = [("addr", umem)]
+# Fill in explicit byte order types, these take precedence over the structure byte order.
+class c_int16_be(c_int16):
+    _endianness_ = "big"
 
-    def __init__(self, addr):
-        if addr < 0:
-            raise ValueError("addr should be a positive number")
-        self.addr = addr
 
-    def is_null(self):
-        return self.addr != 0
+class c_uint16_be(c_uint16):
+    _endianness_ = "big"
 
-    def __add__(self, other):
-        if other < 0:
-            raise ValueError("other should be a positive number")
-        return Pointer(self.addr + other)
 
-    def __sub__(self, other):
-        if other < 0:
-            raise ValueError("other should be a positive number")
-        return Pointer(self.addr - other)
+class c_int32_be(c_int32):
+    _endianness_ = "big"
 
-    def __bool__(self):
-        return self.is_null()
 
-    def __eq__(self, other):
-        if isinstance(other, Pointer):
-            return self.addr == other.addr
-        return self.addr == other
+class c_uint32_be(c_uint32):
+    _endianness_ = "big"
 
-    def __ne__(self, other):
-        if isinstance(other, Pointer):
-            return self.addr != other.addr
-        return self.addr != other
 
-    def __str__(self):
-        return f"{self._type_.__name__} @ {hex(self.addr)}"
+class c_int64_be(c_int64):
+    _endianness_ = "big"
 
-    def __repr__(self):
-        return f"{self._type_.__name__}({hex(self.addr)})"
+
+class c_uint64_be(c_uint64):
+    _endianness_ = "big"
+
+
+class c_float_be(c_float):
+    _endianness_ = "big"
+
+
+class c_double_be(c_double):
+    _endianness_ = "big"
+
+
+class c_int16_le(c_int16):
+    _endianness_ = "little"
+
+
+class c_uint16_le(c_uint16):
+    _endianness_ = "little"
+
+
+class c_int32_le(c_int32):
+    _endianness_ = "little"
+
+
+class c_uint32_le(c_uint32):
+    _endianness_ = "little"
+
+
+class c_int64_le(c_int64):
+    _endianness_ = "little"
+
+
+class c_uint64_le(c_uint64):
+    _endianness_ = "little"
+
+
+class c_float_le(c_float):
+    _endianness_ = "little"
+
+
+class c_double_le(c_double):
+    _endianness_ = "little"
+
+
+# Cache for enum field types.
+mf_enum_types = dict()
+
+
+def ENUM(enum_type, target_type=c_int):
+    """Integer of `target_type` decoded into members of `enum_type` (i.e. `enum.IntEnum`), unknown values stay `int`."""
+    global mf_enum_types
+    key = (enum_type, target_type)
+    if key not in mf_enum_types:
+        mf_enum_types[key] = type(
+            f"{target_type.__name__}_{enum_type.__name__}",
+            (target_type,),
+            dict(_enum_=enum_type),
+        )
+    return mf_enum_types[key]
 
 
 # Cache for memflow pointer types.
 mf_lp_types = dict()
 
 
-def POINTER(target_type, byteness):
+def POINTER(target_type, byteness=None):
     global mf_lp_types
     if byteness not in mf_lp_types:
         mf_lp_types[byteness] = dict()
     if target_type not in mf_lp_types[byteness]:
-        ptr_type = type(
-            f"MF_LP_{target_type.__name__}",
-            (Pointer,),
-            dict(
-                _type_=target_type,
-                _byteness_=byteness,
-            ),
-        )
+        ptr_attrs = dict(_type_=target_type)
+        # Without a byteness the pointer width of the target data model is used.
+        if byteness is not None:
+            ptr_attrs["_byteness_"] = byteness
+        ptr_type = type(f"MF_LP_{target_type.__name__}", (Pointer,), ptr_attrs)
         mf_lp_types[byteness][target_type] = ptr_type
     return mf_lp_types[byteness][target_type]
 
@@ -253,3 +539,50 @@ def POINTER64(target_type):
 
 def POINTER32(target_type):
     return POINTER(target_type, 4)
+
+
+class FunctionPointer(Pointer):
+    """Address of a function, read like a pointer but never dereferenced or called."""
+
+    _restype_ = None
+    _argtypes_ = ()
+
+    def symbol(self, modules=None):
+        """Returns the address as `module+offset`, `modules` defaults to the modules of the memory it was read from."""
+        if modules is None:
+            module_info_list = getattr(self.source, "module_info_list", None)
+            if module_info_list is None:
+                return None
+            modules = module_info_list()
+        for module in modules:
+            if module.base <= self.addr < module.base + module.size:
+                return f"{module.name}+{hex(self.addr - module.base)}"
+        return None
+
+    def __str__(self):
+        try:
+            symbol = self.symbol()
+        except Exception:
+            symbol = None
+        return symbol or hex(self.addr)
+
+    def __repr__(self):
+        return f"{type(self).__name__}({hex(self.addr)})"
+
+
+# Cache for memflow function pointer types.
+mf_fn_types = dict()
+
+
+def CFUNCTYPE(restype, *argtypes, byteness=None):
+    """Function pointer type carrying its signature, like `ctypes.CFUNCTYPE` without calling support."""
+    global mf_fn_types
+    key = (restype, argtypes, byteness)
+    if key not in mf_fn_types:
+        fn_attrs = dict(_restype_=restype, _argtypes_=argtypes)
+        # Without a byteness the pointer width of the target data model is used.
+        if byteness is not None:
+            fn_attrs["_byteness_"] = byteness
+        restype_name = "None" if restype is None else restype.__name__
+        mf_fn_types[key] = type(f"MF_FN_{restype_name}", (FunctionPointer,), fn_attrs)
+    return mf_fn_types[key]
@@ -1,8 +1,24 @@
 import memflow.dummy
 
-from typing import Optional, Type, Any, List
+from typing import Optional, Type, Any, Dict, List, Union
 
-def sizeof(type: Type[_CT]) -> int: ...
+def sizeof(type: Union[Type[_CT], str], data_model: Optional[str] = None) -> int: ...
+def parse_c(source: str, data_model: Optional[str] = None) -> Dict[str, Type[_CT]]: ...
+def offsetof(
+    type: Union[Type[_CT], str], path: str, data_model: Optional[str] = None
+) -> int: ...
+def alignof(type: Union[Type[_CT], str], data_model: Optional[str] = None) -> int: ...
+def layout(
+    type: Union[Type[_CT], str], data_model: Optional[str] = None
+) -> List[Dict[str, Any]]: ...
+def to_c_header(
+    type: Union[Type[_CT], str], data_model: Optional[str] = None
+) -> str: ...
+def to_layout_json(
+    type: Union[Type[_CT], str],
+    data_model: Optional[str] = None,
+    indent: Optional[int] = None,
+) -> str: ...
 
 class Inventory:
     def __init__(self, path: Optional[str]) -> self: ...
@@ -33,11 +49,22 @@ class Connector:
     def phys_read(self, addr: int, type: Type[_CT]) -> Any: ...
     def phys_read_ptr(self, ptr: Any) -> Any: ...
     def phys_write(self, addr: int, type: Type[_CT], value: Any): ...
+    def hexdump(
+        self,
+        addr: int,
+        len: Optional[int] = None,
+        ty: Union[Type[_CT], str, None] = None,
+        data_model: Optional[str] = None,
+    ) -> str: ...
 
 class Os:
     @property
     def arch(self) -> str: ...
     @property
+    def data_model(self) -> str: ...
+    @data_model.setter
+    def data_model(self, data_model: Optional[str]): ...
+    @property
     def base(self) -> int: ...
     @property
     def size(self) -> int: ...
@@ -48,23 +75,126 @@ class Os:
     def process_from_addr(self, addr: int) -> Process: ...
     def module_info_list(self) -> List[ModuleInfo]: ...
     def module_from_name(self, name: str) -> ModuleInfo: ...
-    def read(self, addr: int, type: Type[_CT]) -> Any: ...
+    def read(
+        self, addr: int, type: Union[Type[_CT], str], follow: Optional[int] = None
+    ) -> Any: ...
     def read_ptr(self, ptr: Any) -> Any: ...
+    def read_char_string(
+        self,
+        addr: int,
+        max_bytes: Optional[int] = None,
+        encoding: Optional[str] = None,
+        errors: Optional[str] = None,
+    ) -> str: ...
+    def read_wchar_string(
+        self,
+        addr: int,
+        max_bytes: Optional[int] = None,
+        encoding: Optional[str] = None,
+        errors: Optional[str] = None,
+    ) -> str: ...
+    def read_unicode_string(self, addr: int, errors: Optional[str] = None) -> str: ...
+    def dump_type(
+        self, addr: int, ty: Union[Type[_CT], str], depth: int = 1
+    ) -> TypeDump: ...
+    def view(self, addr: int, type: Type[_CT]) -> View: ...
     def write(self, addr: int, type: Type[_CT], value: Any): ...
+    def write_char_string(
+        self,
+        addr: int,
+        value: str,
+        encoding: Optional[str] = None,
+        errors: Optional[str] = None,
+        terminate: bool = True,
+        max_bytes: Optional[int] = None,
+    ): ...
+    def write_wchar_string(
+        self,
+        addr: int,
+        value: str,
+        encoding: Optional[str] = None,
+        terminate: bool = True,
+        max_bytes: Optional[int] = None,
+    ): ...
     def phys_read(self, addr: int, type: Type[_CT]) -> Any: ...
     def phys_read_ptr(self, ptr: Any) -> Any: ...
     def phys_write(self, addr: int, type: Type[_CT], value: Any): ...
+    def hexdump(
+        self,
+        addr: int,
+        len: Optional[int] = None,
+        ty: Union[Type[_CT], str, None] = None,
+        data_model: Optional[str] = None,
+    ) -> str: ...
 
 class Process:
-    def read(self, addr: int, type: Type[_CT]) -> Any: ...
+    @property
+    def data_model(self) -> str: ...
+    @data_model.setter
+    def data_model(self, data_model: Optional[str]): ...
+    def read(
+        self, addr: int, type: Union[Type[_CT], str], follow: Optional[int] = None
+    ) -> Any: ...
+    def hexdump(
+        self,
+        addr: int,
+        len: Optional[int] = None,
+        ty: Union[Type[_CT], str, None] = None,
+        data_model: Optional[str] = None,
+    ) -> str: ...
+    def dump_type(
+        self, addr: int, ty: Union[Type[_CT], str], depth: int = 1
+    ) -> TypeDump: ...
     def read_ptr(self, ptr: Any) -> Any: ...
-    def read_char_string(self, addr: int, max_bytes: Optional[int]) -> str: ...
-    def read_wchar_string(self, addr: int, max_bytes: Optional[int]) -> bytes: ...
+    def view(self, addr: int, type: Type[_CT]) -> View: ...
+    def read_char_string(
+        self,
+        addr: int,
+        max_bytes: Optional[int] = None,
+        encoding: Optional[str] = None,
+        errors: Optional[str] = None,
+    ) -> str: ...
+    def read_wchar_string(
+        self,
+        addr: int,
+        max_bytes: Optional[int] = None,
+        encoding: Optional[str] = None,
+        errors: Optional[str] = None,
+    ) -> str: ...
+    def read_unicode_string(self, addr: int, errors: Optional[str] = None) -> str: ...
     def write(self, addr: int, type: Type[_CT], value: Any): ...
+    def write_char_string(
+        self,
+        addr: int,
+        value: str,
+        encoding: Optional[str] = None,
+        errors: Optional[str] = None,
+        terminate: bool = True,
+        max_bytes: Optional[int] = None,
+    ): ...
+    def write_wchar_string(
+        self,
+        addr: int,
+        value: str,
+        encoding: Optional[str] = None,
+        terminate: bool = True,
+        max_bytes: Optional[int] = None,
+    ): ...
     def module_info_list(self) -> List[ModuleInfo]: ...
     def module_by_name(self, name: str) -> ModuleInfo: ...
     def info(self) -> ProcessInfo: ...
 
+class View:
+    @property
+    def addr(self) -> int: ...
+    def read(self) -> Any: ...
+    def write(self, value: Any): ...
+    def __getattr__(self, name: str) -> Any: ...
+    def __setattr__(self, name: str, value: Any): ...
+    def __getitem__(self, key: int | str) -> Any: ...
+    def __setitem__(self, key: int | str, value: Any): ...
+    def __len__(self) -> int: ...
+
 class ProcessInfo:
     def __init__(
         self,
@@ -136,4 +266,58 @@ class ArchitectureIdent:
 
 class Pointer:
     def __init__(self, addr: int) -> self: ...
+    @property
+    def addr(self) -> int: ...
+    @property
+    def source(self) -> Optional[Any]: ...
     def is_null(self) -> bool: ...
+    def deref(self) -> Any: ...
+    def offset(self, n: int) -> Pointer: ...
+    def __getitem__(self, idx: int) -> Any: ...
+    def __add__(self, n: int) -> Pointer: ...
+    def __sub__(self, n: int) -> Pointer: ...
+
+class Pdb:
+    def __init__(self, path: str) -> self: ...
+    @property
+    def symbols(self) -> Dict[str, int]: ...
+    def type_names(self) -> List[str]: ...
+    def __getitem__(self, name: str) -> Type[_CT]: ...
+    def __contains__(self, name: str) -> bool: ...
+    def __len__(self) -> int: ...
+
+class Dwarf:
+    def __init__(self, path: str) -> self: ...
+    @property
+    def symbols(self) -> Dict[str, int]: ...
+    def type_names(self) -> List[str]: ...
+    def __getitem__(self, name: str) -> Type[_CT]: ...
+    def __getattr__(self, name: str) -> Type[_CT]: ...
+    def __contains__(self, name: str) -> bool: ...
+    def __len__(self) -> int: ...
+
+class TypeLibrary:
+    def __init__(
+        self,
+        types: Optional[Dict[str, Type[_CT]]] = None,
+        version: Optional[str] = None,
+    ) -> self: ...
+    @staticmethod
+    def default() -> TypeLibrary: ...
+    version: Optional[str]
+    def register(self, type: Type[_CT], name: Optional[str] = None) -> Type[_CT]: ...
+    def merge(
+        self, other: Union[TypeLibrary, Dict[str, Type[_CT]]], replace: bool = False
+    ): ...
+    def names(self) -> List[str]: ...
+    def to_json(self, indent: Optional[int] = None) -> str: ...
+    @staticmethod
+    def from_json(json: str) -> TypeLibrary: ...
+    def __getitem__(self, expr: str) -> Type[_CT]: ...
+    def __setitem__(self, name: str, type: Type[_CT]): ...
+    def __delitem__(self, name: str): ...
+    def __contains__(self, name: str) -> bool: ...
+    def __len__(self) -> int: ...
+
+class TypeDump:
+    def _repr_html_(self) -> str: ...
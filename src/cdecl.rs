@@ -0,0 +1,1387 @@
+use std::collections::HashMap;
+
+use pyo3::{
+    prelude::*,
+    types::{PyDict, PyList, PyModule, PyTuple},
+};
+
+use crate::{
+    internal::{BitfieldRun, DataModel},
+    MemflowPyError,
+};
+
+/// Words that make up the builtin C types, the fixed width ones of `stdint.h` and `stddef.h` included.
+const BUILTIN_WORDS: &[&str] = &[
+    "void",
+    "char",
+    "short",
+    "int",
+    "long",
+    "signed",
+    "unsigned",
+    "float",
+    "double",
+    "_Bool",
+    "bool",
+    "wchar_t",
+    "int8_t",
+    "uint8_t",
+    "int16_t",
+    "uint16_t",
+    "int32_t",
+    "uint32_t",
+    "int64_t",
+    "uint64_t",
+    "size_t",
+    "ssize_t",
+    "ptrdiff_t",
+    "intptr_t",
+    "uintptr_t",
+];
+
+/// Words without influence on the layout.
+const QUALIFIERS: &[&str] = &[
+    "const",
+    "volatile",
+    "restrict",
+    "__restrict",
+    "extern",
+    "static",
+    "register",
+    "inline",
+    "__inline",
+    "__unaligned",
+    "__ptr32",
+    "__ptr64",
+];
+
+const CALLING_CONVENTIONS: &[&str] = &[
+    "__cdecl",
+    "__stdcall",
+    "__fastcall",
+    "__thiscall",
+    "__vectorcall",
+];
+
+/// Puncts ordered so the longest match wins.
+const PUNCTS: &[&str] = &[
+    "...", "<<", ">>", "{", "}", "(", ")", "[", "]", ";", ",", "*", ":", "=", "+", "-", "/", "%",
+    "|", "&", "^", "~",
+];
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Ident(String),
+    Number(i64),
+    Punct(&'static str),
+    /// `#pragma pack` directive, other preprocessor directives are skipped.
+    Pack(PackChange),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum PackChange {
+    Push(Option<usize>),
+    Pop,
+    Set(Option<usize>),
+}
+
+/// Splits the source into tokens along with the line they start on, expanding object-like macros.
+fn tokenize(
+    source: &str,
+    macros: &mut HashMap<String, Vec<Token>>,
+) -> crate::Result<Vec<(Token, usize)>> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    let mut line = 1;
+    let mut line_start = true;
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+        if c == '\n' {
+            line += 1;
+            line_start = true;
+            i += 1;
+        } else if c.is_whitespace() {
+            i += 1;
+        } else if c == '/' && next == Some('/') {
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+        } else if c == '/' && next == Some('*') {
+            i += 2;
+            while i < chars.len() && !(chars[i] == '*' && chars.get(i + 1) == Some(&'/')) {
+                if chars[i] == '\n' {
+                    line += 1;
+                }
+                i += 1;
+            }
+            i += 2;
+        } else if c == '#' && line_start {
+            // Directives end with the line unless it is continued by a backslash.
+            let start_line = line;
+            let mut directive = String::new();
+            while i < chars.len() && chars[i] != '\n' {
+                if chars[i] == '\\' && chars.get(i + 1) == Some(&'\n') {
+                    line += 1;
+                    i += 2;
+                    continue;
+                }
+                directive.push(chars[i]);
+                i += 1;
+            }
+            if let Some((name, body)) = parse_define(&directive) {
+                let body = tokenize(body, macros)
+                    .map_err(|e| MemflowPyError::InvalidCDecl(start_line, e.to_string()))?;
+                macros.insert(
+                    name.to_owned(),
+                    body.into_iter().map(|(token, _)| token).collect(),
+                );
+            } else if let Some(change) = parse_pragma_pack(&directive)
+                .map_err(|msg| MemflowPyError::InvalidCDecl(start_line, msg))?
+            {
+                tokens.push((Token::Pack(change), start_line));
+            }
+        } else if c.is_ascii_alphabetic() || c == '_' {
+            line_start = false;
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            let ident: String = chars[start..i].iter().collect();
+            match macros.get(&ident) {
+                Some(expansion) => {
+                    tokens.extend(expansion.iter().map(|token| (token.clone(), line)))
+                }
+                None => tokens.push((Token::Ident(ident), line)),
+            }
+        } else if c.is_ascii_digit() {
+            line_start = false;
+            let start = i;
+            while i < chars.len() && chars[i].is_ascii_alphanumeric() {
+                i += 1;
+            }
+            let literal: String = chars[start..i].iter().collect();
+            let value = parse_number(&literal).ok_or_else(|| {
+                MemflowPyError::InvalidCDecl(line, format!("invalid number `{}`", literal))
+            })?;
+            tokens.push((Token::Number(value), line));
+        } else {
+            line_start = false;
+            let rest: String = chars[i..chars.len().min(i + 3)].iter().collect();
+            let punct = PUNCTS
+                .iter()
+                .find(|punct| rest.starts_with(**punct))
+                .ok_or_else(|| {
+                    MemflowPyError::InvalidCDecl(line, format!("unexpected character `{}`", c))
+                })?;
+            tokens.push((Token::Punct(punct), line));
+            i += punct.len();
+        }
+    }
+    Ok(tokens)
+}
+
+/// Parses integer literals, the `u` and `l` suffixes are ignored.
+fn parse_number(literal: &str) -> Option<i64> {
+    let digits = literal.trim_end_matches(['u', 'U', 'l', 'L']);
+    let value = if let Some(hex) = digits
+        .strip_prefix("0x")
+        .or_else(|| digits.strip_prefix("0X"))
+    {
+        u64::from_str_radix(hex, 16).ok()?
+    } else if digits.len() > 1 && digits.starts_with('0') {
+        u64::from_str_radix(&digits[1..], 8).ok()?
+    } else {
+        digits.parse().ok()?
+    };
+    Some(value as i64)
+}
+
+/// Splits `#define NAME body` into the name and body, function-like macros are not expanded and yield `None`.
+fn parse_define(directive: &str) -> Option<(&str, &str)> {
+    let rest = directive[1..].trim_start().strip_prefix("define")?;
+    if !rest.starts_with(char::is_whitespace) {
+        return None;
+    }
+    let rest = rest.trim_start();
+    let name_end = rest
+        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+        .unwrap_or(rest.len());
+    let (name, body) = rest.split_at(name_end);
+    match name.is_empty() || body.starts_with('(') {
+        true => None,
+        false => Some((name, body)),
+    }
+}
+
+/// Parses `#pragma pack(...)`, any other directive yields `None`.
+fn parse_pragma_pack(directive: &str) -> Result<Option<PackChange>, String> {
+    let words: Vec<&str> = directive[1..].split_whitespace().collect();
+    if words.first() != Some(&"pragma") || words.get(1).is_none_or(|w| !w.starts_with("pack")) {
+        return Ok(None);
+    }
+    let args = words[1..].concat();
+    let args = args
+        .strip_prefix("pack(")
+        .and_then(|args| args.strip_suffix(')'))
+        .ok_or_else(|| format!("malformed `{}`", directive))?;
+    let args: Vec<&str> = args.split(',').filter(|arg| !arg.is_empty()).collect();
+    let pack = |arg: Option<&&str>| -> Result<Option<usize>, String> {
+        arg.map(|arg| {
+            arg.parse()
+                .map_err(|_| format!("invalid packing `{}` in `{}`", arg, directive))
+        })
+        .transpose()
+    };
+    match args.first() {
+        Some(&"push") => Ok(Some(PackChange::Push(pack(
+            args.last()
+                .filter(|arg| arg.chars().all(|c| c.is_ascii_digit())),
+        )?))),
+        Some(&"pop") => Ok(Some(PackChange::Pop)),
+        arg => Ok(Some(PackChange::Set(pack(arg)?))),
+    }
+}
+
+/// Type of a declaration while parsing.
+#[derive(Clone)]
+enum CType {
+    Void,
+    Leaf(Leaf),
+    /// Structure or union by its key, it can be incomplete while its members are parsed.
+    Record(String),
+    /// Function type, only usable behind a pointer.
+    Function(Box<CType>, Vec<CType>),
+}
+
+/// Complete type along with its python type and C layout.
+#[derive(Clone)]
+struct Leaf {
+    obj: PyObject,
+    size: usize,
+    align: usize,
+}
+
+struct Record {
+    obj: PyObject,
+    /// Size and alignment, `None` until the members are known.
+    layout: Option<(usize, usize)>,
+    /// Named members with their offsets, members of anonymous structures are included.
+    members: Vec<(String, usize, PyObject)>,
+    /// Records without a tag are named after the typedef or member declaring them.
+    named: bool,
+}
+
+/// Part of a declarator, applied to the base type from the inside out.
+enum Modifier {
+    Pointer,
+    Array(Option<usize>),
+    Function(Vec<CType>),
+}
+
+/// Member of a structure or union before the layout is decided.
+struct MemberDecl {
+    name: Option<String>,
+    ty: CType,
+    bits: Option<u32>,
+}
+
+struct Parser<'py> {
+    py: Python<'py>,
+    memflow: &'py PyModule,
+    data_model: DataModel,
+    tokens: Vec<(Token, usize)>,
+    pos: usize,
+    pack: Option<usize>,
+    pack_stack: Vec<Option<usize>>,
+    records: HashMap<String, Record>,
+    enums: HashMap<String, CType>,
+    typedefs: HashMap<String, CType>,
+    constants: HashMap<String, i64>,
+    /// Pointer types without a `POINTER` helper, created once per parse.
+    pointer_types: HashMap<&'static str, PyObject>,
+    anonymous: usize,
+    types: &'py PyDict,
+}
+
+/// Parses C declarations into python types, keyed by their tag or typedef name.
+///
+/// The layout follows the C rules for the data model, padding is spelled out with `_pad_` members as structures
+/// are laid out without gaps. Pointers and `long` are pinned to the widths of the data model.
+pub fn parse<'py>(
+    py: Python<'py>,
+    source: &str,
+    data_model: DataModel,
+) -> crate::Result<&'py PyDict> {
+    let mut parser = Parser {
+        py,
+        memflow: py.import("memflow")?,
+        data_model,
+        tokens: tokenize(source, &mut HashMap::new())?,
+        pos: 0,
+        pack: None,
+        pack_stack: Vec::new(),
+        records: HashMap::new(),
+        enums: HashMap::new(),
+        typedefs: HashMap::new(),
+        constants: HashMap::new(),
+        pointer_types: HashMap::new(),
+        anonymous: 0,
+        types: PyDict::new(py),
+    };
+    while let Some(token) = parser.peek().cloned() {
+        match token {
+            Token::Pack(change) => {
+                parser.pos += 1;
+                parser.change_pack(change);
+            }
+            Token::Punct(";") => parser.pos += 1,
+            _ => parser.declaration()?,
+        }
+    }
+    Ok(parser.types)
+}
+
+impl<'py> Parser<'py> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(token, _)| token)
+    }
+
+    fn peek_at(&self, n: usize) -> Option<&Token> {
+        self.tokens.get(self.pos + n).map(|(token, _)| token)
+    }
+
+    fn error(&self, msg: impl Into<String>) -> MemflowPyError {
+        let line = self
+            .tokens
+            .get(self.pos)
+            .or(self.tokens.last())
+            .map(|(_, line)| *line)
+            .unwrap_or(1);
+        MemflowPyError::InvalidCDecl(line, msg.into())
+    }
+
+    fn describe(&self) -> String {
+        match self.peek() {
+            Some(Token::Ident(ident)) => format!("`{}`", ident),
+            Some(Token::Number(n)) => format!("`{}`", n),
+            Some(Token::Punct(punct)) => format!("`{}`", punct),
+            Some(Token::Pack(_)) => "`#pragma pack`".to_owned(),
+            None => "end of input".to_owned(),
+        }
+    }
+
+    fn is_punct(&self, punct: &str) -> bool {
+        matches!(self.peek(), Some(Token::Punct(p)) if *p == punct)
+    }
+
+    fn eat(&mut self, punct: &str) -> bool {
+        let found = self.is_punct(punct);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn expect(&mut self, punct: &str) -> crate::Result<()> {
+        match self.eat(punct) {
+            true => Ok(()),
+            false => Err(self.error(format!("expected `{}`, found {}", punct, self.describe()))),
+        }
+    }
+
+    fn eat_ident(&mut self, ident: &str) -> bool {
+        let found = matches!(self.peek(), Some(Token::Ident(i)) if i == ident);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn change_pack(&mut self, change: PackChange) {
+        match change {
+            PackChange::Push(pack) => {
+                self.pack_stack.push(self.pack);
+                self.pack = pack.or(self.pack);
+            }
+            PackChange::Pop => self.pack = self.pack_stack.pop().flatten(),
+            PackChange::Set(pack) => self.pack = pack,
+        }
+    }
+
+    /// Skips `__attribute__((...))` and `__declspec(...)`, returns whether one of them asked for packing.
+    fn skip_attributes(&mut self) -> crate::Result<bool> {
+        let mut packed = false;
+        while let Some(Token::Ident(ident)) = self.peek() {
+            if ident != "__attribute__" && ident != "__declspec" {
+                break;
+            }
+            self.pos += 1;
+            self.expect("(")?;
+            let mut depth = 1;
+            while depth > 0 {
+                match self.peek() {
+                    Some(Token::Punct("(")) => depth += 1,
+                    Some(Token::Punct(")")) => depth -= 1,
+                    Some(Token::Ident(ident)) if ident == "packed" || ident == "__packed__" => {
+                        packed = true
+                    }
+                    None => return Err(self.error("unterminated attribute")),
+                    _ => {}
+                }
+                self.pos += 1;
+            }
+        }
+        Ok(packed)
+    }
+
+    fn declaration(&mut self) -> crate::Result<()> {
+        let typedef = self.eat_ident("typedef");
+        let base = self
+            .specifiers()?
+            .ok_or_else(|| self.error(format!("expected a type, found {}", self.describe())))?;
+        if self.eat(";") {
+            return Ok(());
+        }
+        loop {
+            let (name, modifiers) = self.declarator()?;
+            self.skip_attributes()?;
+            let ty = self.apply(base.clone(), modifiers)?;
+            if typedef {
+                let name = name.ok_or_else(|| self.error("typedef without a name"))?;
+                self.name_record(&ty, &name)?;
+                if let Ok(obj) = self.obj(&ty) {
+                    self.types.set_item(&name, obj)?;
+                }
+                self.typedefs.insert(name, ty);
+            }
+            // Prototypes and variables do not declare types, their initializers and bodies are skipped.
+            if self.eat("=") {
+                self.skip_until_end()?;
+            }
+            if self.is_punct("{") {
+                return self.skip_block();
+            }
+            if !self.eat(",") {
+                break;
+            }
+        }
+        self.expect(";")
+    }
+
+    fn skip_until_end(&mut self) -> crate::Result<()> {
+        let mut depth = 0;
+        loop {
+            match self.peek() {
+                Some(Token::Punct("(" | "{" | "[")) => depth += 1,
+                Some(Token::Punct(")" | "}" | "]")) => depth -= 1,
+                Some(Token::Punct("," | ";")) if depth == 0 => return Ok(()),
+                None => return Err(self.error("unterminated initializer")),
+                _ => {}
+            }
+            self.pos += 1;
+        }
+    }
+
+    fn skip_block(&mut self) -> crate::Result<()> {
+        let mut depth = 0;
+        loop {
+            match self.peek() {
+                Some(Token::Punct("{")) => depth += 1,
+                Some(Token::Punct("}")) => depth -= 1,
+                None => return Err(self.error("unterminated function body")),
+                _ => {}
+            }
+            self.pos += 1;
+            if depth == 0 {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Parses the type specifiers of a declaration, `None` if there are none.
+    fn specifiers(&mut self) -> crate::Result<Option<CType>> {
+        let mut base = None;
+        let mut words = Vec::new();
+        loop {
+            self.skip_attributes()?;
+            let Some(Token::Ident(ident)) = self.peek().cloned() else {
+                break;
+            };
+            if QUALIFIERS.contains(&ident.as_str()) {
+                self.pos += 1;
+            } else if base.is_none() && words.is_empty() && (ident == "struct" || ident == "union")
+            {
+                self.pos += 1;
+                base = Some(self.record_specifier(&ident)?);
+            } else if base.is_none() && words.is_empty() && ident == "enum" {
+                self.pos += 1;
+                base = Some(self.enum_specifier()?);
+            } else if base.is_none() && BUILTIN_WORDS.contains(&ident.as_str()) {
+                self.pos += 1;
+                words.push(ident);
+            } else if base.is_none() && words.is_empty() && self.typedefs.contains_key(&ident) {
+                self.pos += 1;
+                base = self.typedefs.get(&ident).cloned();
+            } else {
+                break;
+            }
+        }
+        match words.is_empty() {
+            true => Ok(base),
+            false => self.builtin(&words).map(Some),
+        }
+    }
+
+    fn prim(&self, name: &str, size: usize) -> crate::Result<CType> {
+        Ok(CType::Leaf(Leaf {
+            obj: self.memflow.getattr(name)?.into(),
+            size,
+            align: self.data_model.scalar_align(size),
+        }))
+    }
+
+    fn int(&self, size: usize, signed: bool) -> crate::Result<CType> {
+        let name = match signed {
+            true => format!("c_int{}", size * 8),
+            false => format!("c_uint{}", size * 8),
+        };
+        self.prim(&name, size)
+    }
+
+    fn builtin(&self, words: &[String]) -> crate::Result<CType> {
+        let has = |word: &str| words.iter().any(|w| w == word);
+        let longs = words.iter().filter(|w| *w == "long").count();
+        let signed = !has("unsigned");
+        let pointer_size = self.data_model.pointer_size();
+        if let [word] = words {
+            let fixed = match word.as_str() {
+                "int8_t" | "uint8_t" => Some(1),
+                "int16_t" | "uint16_t" => Some(2),
+                "int32_t" | "uint32_t" => Some(4),
+                "int64_t" | "uint64_t" => Some(8),
+                "size_t" | "ssize_t" | "ptrdiff_t" | "intptr_t" | "uintptr_t" => Some(pointer_size),
+                _ => None,
+            };
+            if let Some(size) = fixed {
+                let signed = !word.starts_with('u') && word != "size_t";
+                return self.int(size, signed);
+            }
+        }
+        if has("void") {
+            Ok(CType::Void)
+        } else if has("_Bool") || has("bool") {
+            self.prim("c_bool", 1)
+        } else if has("wchar_t") {
+            self.prim("c_wchar", self.data_model.wchar_size())
+        } else if has("float") {
+            self.prim("c_float", 4)
+        } else if has("double") && longs > 0 {
+            let size = self.data_model.long_double_size();
+            Ok(CType::Leaf(Leaf {
+                obj: self.memflow.getattr("c_longdouble")?.into(),
+                size,
+                align: self.data_model.scalar_align(size),
+            }))
+        } else if has("double") {
+            self.prim("c_double", 8)
+        } else if has("char") && !has("signed") && !has("unsigned") {
+            self.prim("c_char", 1)
+        } else if has("char") {
+            self.int(1, signed)
+        } else if has("short") {
+            self.int(2, signed)
+        } else if longs >= 2 {
+            self.int(8, signed)
+        } else if longs == 1 {
+            self.int(self.data_model.long_size(), signed)
+        } else if has("int") || has("signed") || has("unsigned") {
+            self.int(4, signed)
+        } else {
+            Err(self.error(format!("unknown type `{}`", words.join(" "))))
+        }
+    }
+
+    fn new_class(&self, name: &str, base: &str, attrs: &PyDict) -> crate::Result<PyObject> {
+        new_class(self.memflow, name, base, attrs)
+    }
+
+    fn record_specifier(&mut self, kind: &str) -> crate::Result<CType> {
+        let mut packed = self.skip_attributes()?;
+        let tag = match self.peek() {
+            Some(Token::Ident(ident)) => {
+                let tag = ident.clone();
+                self.pos += 1;
+                Some(tag)
+            }
+            _ => None,
+        };
+        let key = match &tag {
+            Some(tag) => format!("{} {}", kind, tag),
+            None => {
+                self.anonymous += 1;
+                format!("{} #{}", kind, self.anonymous)
+            }
+        };
+        if !self.records.contains_key(&key) {
+            let base = match kind {
+                "struct" => "Structure",
+                _ => "Union",
+            };
+            let obj = self.new_class(
+                tag.as_deref().unwrap_or("anonymous"),
+                base,
+                PyDict::new(self.py),
+            )?;
+            if let Some(tag) = &tag {
+                self.types.set_item(tag, &obj)?;
+            }
+            self.records.insert(
+                key.clone(),
+                Record {
+                    obj,
+                    layout: None,
+                    members: Vec::new(),
+                    named: tag.is_some(),
+                },
+            );
+        }
+        if !self.is_punct("{") {
+            return Ok(CType::Record(key));
+        }
+        if self.records[&key].layout.is_some() {
+            return Err(self.error(format!("redefinition of `{}`", key)));
+        }
+
+        let pack = self.pack;
+        self.expect("{")?;
+        let mut members = Vec::new();
+        while !self.eat("}") {
+            let base = self.specifiers()?.ok_or_else(|| {
+                self.error(format!("expected a member type, found {}", self.describe()))
+            })?;
+            // Anonymous structures and unions (C11) without a declarator.
+            if self.eat(";") {
+                let outer = self.records[&key].obj.getattr(self.py, "__name__")?;
+                self.name_record(&base, &format!("{}__anon_{}", outer, members.len()))?;
+                members.push(MemberDecl {
+                    name: None,
+                    ty: base,
+                    bits: None,
+                });
+                continue;
+            }
+            loop {
+                let (name, modifiers) = match self.is_punct(":") {
+                    true => (None, Vec::new()),
+                    false => self.declarator()?,
+                };
+                let ty = self.apply(base.clone(), modifiers)?;
+                let bits = match self.eat(":") {
+                    true => Some(self.const_expr()? as u32),
+                    false => None,
+                };
+                self.skip_attributes()?;
+                if let Some(name) = &name {
+                    let outer = self.records[&key].obj.getattr(self.py, "__name__")?;
+                    self.name_record(&ty, &format!("{}_{}", outer, name))?;
+                }
+                members.push(MemberDecl { name, ty, bits });
+                if !self.eat(",") {
+                    break;
+                }
+            }
+            self.expect(";")?;
+        }
+        packed |= self.skip_attributes()?;
+        let pack = if packed { Some(1) } else { pack };
+        match kind {
+            "struct" => self.layout_struct(&key, members, pack)?,
+            _ => self.layout_union(&key, members, pack)?,
+        }
+        Ok(CType::Record(key))
+    }
+
+    /// Names an anonymous record after the typedef or member declaring it.
+    fn name_record(&mut self, ty: &CType, name: &str) -> crate::Result<()> {
+        let CType::Record(key) = ty else {
+            return Ok(());
+        };
+        let record = self.records.get_mut(key).unwrap();
+        if !record.named {
+            record.named = true;
+            record.obj.setattr(self.py, "__name__", name)?;
+            record.obj.setattr(self.py, "__qualname__", name)?;
+        }
+        Ok(())
+    }
+
+    fn padding(&self, offset: usize, size: usize) -> crate::Result<PyObject> {
+        let uint8 = self.memflow.getattr("c_uint8")?;
+        let pad = self.memflow.getattr("ARRAY")?.call1((uint8, size))?;
+        Ok(PyTuple::new(
+            self.py,
+            [format!("_pad_0x{:x}", offset).into_py(self.py), pad.into()],
+        )
+        .into())
+    }
+
+    /// Pads a structure from `offset` to `start` and ends the bitfield run.
+    ///
+    /// `_fields_` only ends a run at a member in between, `open` runs get one even when it is empty.
+    fn end_run(
+        &self,
+        fields: &mut Vec<PyObject>,
+        run: &mut BitfieldRun,
+        offset: usize,
+        start: usize,
+        open: bool,
+    ) -> crate::Result<()> {
+        if start > offset || open {
+            fields.push(self.padding(offset, start - offset)?);
+        }
+        run.close();
+        Ok(())
+    }
+
+    fn layout_struct(
+        &mut self,
+        key: &str,
+        members: Vec<MemberDecl>,
+        pack: Option<usize>,
+    ) -> crate::Result<()> {
+        if members.is_empty() {
+            return Err(self.error(format!("`{}` has no members", key)));
+        }
+        let py = self.py;
+        let mut fields = Vec::new();
+        let mut offsets: Vec<PyObject> = Vec::new();
+        let mut flat_members = Vec::new();
+        let mut offset = 0;
+        let mut align = 1;
+        // Bitfields share storage units under the same rules as `_fields_`, new runs start at an aligned offset.
+        let mut run = BitfieldRun::default();
+        for (idx, member) in members.into_iter().enumerate() {
+            let obj = self.obj(&member.ty)?;
+            let (size, member_align) = self.layout(&member.ty)?;
+            let member_align = pack.map_or(member_align, |pack| member_align.min(pack));
+            align = align.max(member_align);
+            match member.bits {
+                // Zero width bitfields close the storage unit.
+                Some(0) => {
+                    let start = align_up(offset, member_align);
+                    let open = run.is_open();
+                    self.end_run(&mut fields, &mut run, offset, start, open)?;
+                    offset = start;
+                }
+                Some(bits) => {
+                    let continues = !run.starts_new(size, self.data_model);
+                    let mut next = run;
+                    let placed = match continues {
+                        true => Some(next.place(offset, size, bits, self.data_model)),
+                        false => None,
+                    };
+                    let (unit_offset, bit_offset) = match placed {
+                        Some((unit_offset, bit_offset)) if unit_offset % member_align == 0 => {
+                            run = next;
+                            (unit_offset, bit_offset)
+                        }
+                        // A unit C would not align starts a new run.
+                        _ => {
+                            let start = align_up(offset, member_align);
+                            self.end_run(&mut fields, &mut run, offset, start, continues)?;
+                            offset = start;
+                            run.place(start, size, bits, self.data_model)
+                        }
+                    };
+                    offset = offset.max(unit_offset + size);
+                    let name = member
+                        .name
+                        .unwrap_or_else(|| format!("_pad_0x{:x}_{}", unit_offset, bit_offset));
+                    fields.push(PyTuple::new(py, [name.into_py(py), obj, bits.into_py(py)]).into());
+                }
+                None => {
+                    run.close();
+                    let start = align_up(offset, member_align);
+                    if start > offset {
+                        fields.push(self.padding(offset, start - offset)?);
+                    }
+                    let name = match member.name {
+                        Some(name) => name,
+                        None => {
+                            // Members of anonymous records are reachable through the enclosing structure.
+                            if let CType::Record(inner) = &member.ty {
+                                for (name, inner_offset, obj) in &self.records[inner].members {
+                                    offsets.push(
+                                        PyTuple::new(
+                                            py,
+                                            [
+                                                (start + inner_offset).into_py(py),
+                                                name.into_py(py),
+                                                obj.clone_ref(py),
+                                            ],
+                                        )
+                                        .into_py(py),
+                                    );
+                                    flat_members.push((
+                                        name.clone(),
+                                        start + inner_offset,
+                                        obj.clone_ref(py),
+                                    ));
+                                }
+                            }
+                            format!("_anon_{}", idx)
+                        }
+                    };
+                    if !name.starts_with('_') {
+                        flat_members.push((name.clone(), start, obj.clone_ref(py)));
+                    }
+                    fields.push(PyTuple::new(py, [name.into_py(py), obj]).into());
+                    offset = start + size;
+                }
+            }
+        }
+        let size = align_up(offset, align);
+        if size > offset {
+            fields.push(self.padding(offset, size - offset)?);
+        }
+
+        let record = self.records.get_mut(key).unwrap();
+        record
+            .obj
+            .setattr(py, "_fields_", PyList::new(py, fields))?;
+        if !offsets.is_empty() {
+            record
+                .obj
+                .setattr(py, "_offsets_", PyList::new(py, offsets))?;
+        }
+        record.layout = Some((size, align));
+        record.members = flat_members;
+        Ok(())
+    }
+
+    fn layout_union(
+        &mut self,
+        key: &str,
+        members: Vec<MemberDecl>,
+        pack: Option<usize>,
+    ) -> crate::Result<()> {
+        if members.is_empty() {
+            return Err(self.error(format!("`{}` has no members", key)));
+        }
+        let py = self.py;
+        let mut fields: Vec<PyObject> = Vec::new();
+        let mut flat_members = Vec::new();
+        let mut max_size = 0;
+        let mut align = 1;
+        for (idx, member) in members.into_iter().enumerate() {
+            let obj = self.obj(&member.ty)?;
+            let (size, member_align) = self.layout(&member.ty)?;
+            max_size = max_size.max(size);
+            align = align.max(pack.map_or(member_align, |pack| member_align.min(pack)));
+            let name = member.name.unwrap_or_else(|| format!("_anon_{}", idx));
+            if !name.starts_with('_') {
+                flat_members.push((name.clone(), 0, obj.clone_ref(py)));
+            }
+            fields.push(match member.bits {
+                Some(bits) => PyTuple::new(py, [name.into_py(py), obj, bits.into_py(py)]).into(),
+                None => PyTuple::new(py, [name.into_py(py), obj]).into(),
+            });
+        }
+        // The union is padded to its alignment by a member spanning all of it.
+        let size = align_up(max_size, align);
+        if size > max_size {
+            fields.push(self.padding(0, size)?);
+        }
+
+        let record = self.records.get_mut(key).unwrap();
+        record
+            .obj
+            .setattr(py, "_fields_", PyList::new(py, fields))?;
+        record.layout = Some((size, align));
+        record.members = flat_members;
+        Ok(())
+    }
+
+    fn enum_specifier(&mut self) -> crate::Result<CType> {
+        self.skip_attributes()?;
+        let tag = match self.peek() {
+            Some(Token::Ident(ident)) => {
+                let tag = ident.clone();
+                self.pos += 1;
+                Some(tag)
+            }
+            _ => None,
+        };
+        let key = tag.as_ref().map(|tag| format!("enum {}", tag));
+        if !self.eat("{") {
+            // Enums declared elsewhere are treated as plain integers.
+            return match key.and_then(|key| self.enums.get(&key).cloned()) {
+                Some(ty) => Ok(ty),
+                None => self.int(4, true),
+            };
+        }
+
+        let mut enumerators = Vec::new();
+        let mut value = 0;
+        while !self.eat("}") {
+            let name = match self.peek() {
+                Some(Token::Ident(ident)) => ident.clone(),
+                _ => {
+                    return Err(
+                        self.error(format!("expected an enumerator, found {}", self.describe()))
+                    )
+                }
+            };
+            self.pos += 1;
+            if self.eat("=") {
+                value = self.const_expr()?;
+            }
+            self.constants.insert(name.clone(), value);
+            enumerators.push((name, value));
+            value += 1;
+            if !self.eat(",") {
+                self.expect("}")?;
+                break;
+            }
+        }
+
+        let (Some(tag), Some(key)) = (tag, key) else {
+            return self.int(4, true);
+        };
+        let int_enum = self
+            .py
+            .import("enum")?
+            .getattr("IntEnum")?
+            .call1((tag.as_str(), enumerators))?;
+        let obj: PyObject = self
+            .memflow
+            .getattr("ENUM")?
+            .call1((int_enum, self.memflow.getattr("c_int32")?))?
+            .into();
+        self.types.set_item(&tag, &obj)?;
+        let ty = CType::Leaf(Leaf {
+            obj,
+            size: 4,
+            align: 4,
+        });
+        self.enums.insert(key, ty.clone());
+        Ok(ty)
+    }
+
+    /// Parses a declarator into the declared name and the modifiers applied to the base type.
+    fn declarator(&mut self) -> crate::Result<(Option<String>, Vec<Modifier>)> {
+        let mut pointers = 0;
+        loop {
+            self.skip_attributes()?;
+            match self.peek() {
+                Some(Token::Punct("*")) => pointers += 1,
+                Some(Token::Ident(ident))
+                    if QUALIFIERS.contains(&ident.as_str())
+                        || CALLING_CONVENTIONS.contains(&ident.as_str()) => {}
+                _ => break,
+            }
+            self.pos += 1;
+        }
+
+        let nested = self.is_punct("(")
+            && match self.peek_at(1) {
+                Some(Token::Punct("*")) => true,
+                Some(Token::Ident(ident)) => CALLING_CONVENTIONS.contains(&ident.as_str()),
+                _ => false,
+            };
+        let (name, inner) = if nested {
+            self.pos += 1;
+            let declarator = self.declarator()?;
+            self.expect(")")?;
+            declarator
+        } else if let Some(Token::Ident(ident)) = self.peek() {
+            let name = ident.clone();
+            self.pos += 1;
+            (Some(name), Vec::new())
+        } else {
+            (None, Vec::new())
+        };
+
+        let mut suffixes = Vec::new();
+        loop {
+            if self.eat("[") {
+                let len = match self.is_punct("]") {
+                    true => None,
+                    false => Some(self.const_expr()? as usize),
+                };
+                self.expect("]")?;
+                suffixes.push(Modifier::Array(len));
+            } else if self.eat("(") {
+                suffixes.push(Modifier::Function(self.params()?));
+            } else {
+                break;
+            }
+        }
+
+        // Suffixes bind tighter than pointers, the innermost declarator applies last.
+        let mut modifiers: Vec<Modifier> = (0..pointers).map(|_| Modifier::Pointer).collect();
+        modifiers.extend(suffixes.into_iter().rev());
+        modifiers.extend(inner);
+        Ok((name, modifiers))
+    }
+
+    /// Parses the parameters of a function type after the opening parenthesis.
+    fn params(&mut self) -> crate::Result<Vec<CType>> {
+        let mut params = Vec::new();
+        if self.eat(")") {
+            return Ok(params);
+        }
+        if matches!(self.peek(), Some(Token::Ident(ident)) if ident == "void")
+            && matches!(self.peek_at(1), Some(Token::Punct(")")))
+        {
+            self.pos += 2;
+            return Ok(params);
+        }
+        loop {
+            if !self.eat("...") {
+                let base = self.specifiers()?.ok_or_else(|| {
+                    self.error(format!(
+                        "expected a parameter type, found {}",
+                        self.describe()
+                    ))
+                })?;
+                let (_, mut modifiers) = self.declarator()?;
+                // Array and function parameters decay to pointers.
+                if let Some(last) = modifiers.last_mut() {
+                    if matches!(last, Modifier::Array(_)) {
+                        *last = Modifier::Pointer;
+                    } else if matches!(last, Modifier::Function(_)) {
+                        modifiers.push(Modifier::Pointer);
+                    }
+                }
+                params.push(self.apply(base, modifiers)?);
+            }
+            if !self.eat(",") {
+                break;
+            }
+        }
+        self.expect(")")?;
+        Ok(params)
+    }
+
+    fn apply(&mut self, base: CType, modifiers: Vec<Modifier>) -> crate::Result<CType> {
+        let mut ty = base;
+        for modifier in modifiers {
+            ty = match modifier {
+                Modifier::Pointer => self.pointer_to(&ty)?,
+                Modifier::Array(len) => {
+                    let obj = self.obj(&ty)?;
+                    let (size, align) = self.layout(&ty)?;
+                    // Flexible array members do not take up any space.
+                    let len = len.unwrap_or(0);
+                    CType::Leaf(Leaf {
+                        obj: self.memflow.getattr("ARRAY")?.call1((obj, len))?.into(),
+                        size: size * len,
+                        align,
+                    })
+                }
+                Modifier::Function(params) => CType::Function(Box::new(ty), params),
+            };
+        }
+        Ok(ty)
+    }
+
+    fn pointer_to(&mut self, target: &CType) -> crate::Result<CType> {
+        let size = self.data_model.pointer_size();
+        let obj = match target {
+            CType::Void => self.pointer_type("c_void_p", "Pointer")?,
+            CType::Function(ret, params) => {
+                let restype = match **ret {
+                    CType::Void => self.py.None(),
+                    _ => self.obj(ret)?,
+                };
+                let mut args = vec![restype];
+                for param in params {
+                    args.push(self.obj(param)?);
+                }
+                let kwargs = PyDict::new(self.py);
+                kwargs.set_item("byteness", size)?;
+                self.memflow
+                    .getattr("CFUNCTYPE")?
+                    .call(PyTuple::new(self.py, args), Some(kwargs))?
+                    .into()
+            }
+            // Character pointers are strings, followed on demand like ctypes does.
+            CType::Leaf(leaf) if leaf.obj.is(self.memflow.getattr("c_char")?) => {
+                self.pointer_type("c_char_p", "c_char_p")?
+            }
+            CType::Leaf(leaf) if leaf.obj.is(self.memflow.getattr("c_wchar")?) => {
+                self.pointer_type("c_wchar_p", "c_wchar_p")?
+            }
+            _ => self
+                .memflow
+                .getattr("POINTER")?
+                .call1((self.obj(target)?, size))?
+                .into(),
+        };
+        Ok(CType::Leaf(Leaf {
+            obj,
+            size,
+            align: self.data_model.scalar_align(size),
+        }))
+    }
+
+    /// Subclass of `base` pinned to the pointer width of the data model.
+    fn pointer_type(&mut self, name: &'static str, base: &str) -> crate::Result<PyObject> {
+        if let Some(obj) = self.pointer_types.get(name) {
+            return Ok(obj.clone_ref(self.py));
+        }
+        let attrs = PyDict::new(self.py);
+        attrs.set_item("_byteness_", self.data_model.pointer_size())?;
+        let obj = self.new_class(name, base, attrs)?;
+        self.pointer_types.insert(name, obj.clone_ref(self.py));
+        Ok(obj)
+    }
+
+    fn obj(&self, ty: &CType) -> crate::Result<PyObject> {
+        match ty {
+            CType::Leaf(leaf) => Ok(leaf.obj.clone_ref(self.py)),
+            CType::Record(key) => Ok(self.records[key].obj.clone_ref(self.py)),
+            CType::Void => Err(self.error("`void` can only be pointed to")),
+            CType::Function(_, _) => Err(self.error("functions can only be pointed to")),
+        }
+    }
+
+    /// Returns the size and alignment of a complete type.
+    fn layout(&self, ty: &CType) -> crate::Result<(usize, usize)> {
+        match ty {
+            CType::Leaf(leaf) => Ok((leaf.size, leaf.align)),
+            CType::Record(key) => self.records[key]
+                .layout
+                .ok_or_else(|| self.error(format!("`{}` is incomplete", key))),
+            _ => Err(self.error("type without a size")),
+        }
+    }
+
+    /// Evaluates integer constant expressions, enumerators declared before can be referred to.
+    fn const_expr(&mut self) -> crate::Result<i64> {
+        self.binary_expr(0)
+    }
+
+    fn binary_expr(&mut self, min_prec: u8) -> crate::Result<i64> {
+        let mut lhs = self.unary_expr()?;
+        loop {
+            let (op, prec) = match self.peek() {
+                Some(Token::Punct(op @ "|")) => (*op, 1),
+                Some(Token::Punct(op @ "^")) => (*op, 2),
+                Some(Token::Punct(op @ "&")) => (*op, 3),
+                Some(Token::Punct(op @ ("<<" | ">>"))) => (*op, 4),
+                Some(Token::Punct(op @ ("+" | "-"))) => (*op, 5),
+                Some(Token::Punct(op @ ("*" | "/" | "%"))) => (*op, 6),
+                _ => return Ok(lhs),
+            };
+            if prec < min_prec {
+                return Ok(lhs);
+            }
+            self.pos += 1;
+            let rhs = self.binary_expr(prec + 1)?;
+            lhs = match op {
+                "|" => lhs | rhs,
+                "^" => lhs ^ rhs,
+                "&" => lhs & rhs,
+                "<<" => lhs.wrapping_shl(rhs as u32),
+                ">>" => lhs.wrapping_shr(rhs as u32),
+                "+" => lhs.wrapping_add(rhs),
+                "-" => lhs.wrapping_sub(rhs),
+                "*" => lhs.wrapping_mul(rhs),
+                _ if rhs == 0 => return Err(self.error("division by zero")),
+                "/" => lhs / rhs,
+                _ => lhs % rhs,
+            };
+        }
+    }
+
+    fn unary_expr(&mut self) -> crate::Result<i64> {
+        let token = self.peek().cloned();
+        self.pos += 1;
+        match token {
+            Some(Token::Number(n)) => Ok(n),
+            Some(Token::Ident(ident)) => self
+                .constants
+                .get(&ident)
+                .copied()
+                .ok_or_else(|| self.error(format!("unknown constant `{}`", ident))),
+            Some(Token::Punct("-")) => Ok(self.unary_expr()?.wrapping_neg()),
+            Some(Token::Punct("+")) => self.unary_expr(),
+            Some(Token::Punct("~")) => Ok(!self.unary_expr()?),
+            Some(Token::Punct("(")) => {
+                let value = self.const_expr()?;
+                self.expect(")")?;
+                Ok(value)
+            }
+            _ => {
+                self.pos -= 1;
+                Err(self.error(format!("expected a constant, found {}", self.describe())))
+            }
+        }
+    }
+}
+
+/// Creates a subclass of the memflow type `base`, used for the types generated from declarations.
+pub fn new_class(
+    memflow: &PyModule,
+    name: &str,
+    base: &str,
+    attrs: &PyDict,
+) -> crate::Result<PyObject> {
+    let py = memflow.py();
+    attrs.set_item("__module__", "memflow")?;
+    let class =
+        py.import("builtins")?
+            .getattr("type")?
+            .call1((name, (memflow.getattr(base)?,), attrs))?;
+    Ok(class.into())
+}
+
+fn align_up(offset: usize, align: usize) -> usize {
+    offset.next_multiple_of(align)
+}
+
+/// Member placed at an explicit offset, as described by debug information.
+pub struct Member {
+    pub name: String,
+    pub offset: usize,
+    pub ty: PyObject,
+    pub size: usize,
+    /// Bit width and bit offset within the storage unit of bitfields.
+    pub bits: Option<(u32, u32)>,
+}
+
+/// Lays out structure members with explicit padding, overlapping members (anonymous unions) go to `_offsets_`.
+pub fn struct_members(
+    memflow: &PyModule,
+    members: Vec<Member>,
+    size: usize,
+) -> crate::Result<(Vec<PyObject>, Vec<PyObject>)> {
+    let py = memflow.py();
+    let mut out = Vec::new();
+    let mut offsets = Vec::new();
+    let mut end = 0;
+    // Open bitfield storage unit as (offset, size, used bits).
+    let mut unit: Option<(usize, usize, u32)> = None;
+    let pad = |out: &mut Vec<PyObject>, offset: usize, len: usize| -> crate::Result<()> {
+        if len > 0 {
+            let array = memflow
+                .getattr("ARRAY")?
+                .call1((memflow.getattr("c_uint8")?, len))?;
+            out.push(
+                PyTuple::new(
+                    py,
+                    [format!("_pad_0x{:x}", offset).into_py(py), array.into()],
+                )
+                .into(),
+            );
+        }
+        Ok(())
+    };
+    let pad_bits = |out: &mut Vec<PyObject>, ty: &PyObject, offset: usize, from: u32, to: u32| {
+        if to > from {
+            out.push(
+                PyTuple::new(
+                    py,
+                    [
+                        format!("_pad_0x{:x}_{}", offset, from).into_py(py),
+                        ty.clone_ref(py),
+                        (to - from).into_py(py),
+                    ],
+                )
+                .into(),
+            );
+        }
+    };
+
+    for Member {
+        name,
+        offset,
+        ty,
+        size: member_size,
+        bits,
+    } in members
+    {
+        match bits {
+            Some((bits, position)) => match unit {
+                Some((unit_offset, unit_size, used))
+                    if unit_offset == offset && unit_size == member_size && position >= used =>
+                {
+                    pad_bits(&mut out, &ty, offset, used, position);
+                    out.push(PyTuple::new(py, [name.into_py(py), ty, bits.into_py(py)]).into());
+                    unit = Some((unit_offset, unit_size, position + bits));
+                }
+                _ if offset >= end => {
+                    // The open unit is filled up so the new one is not merged into it.
+                    if let (Some((unit_offset, unit_size, used)), true) = (unit, offset == end) {
+                        let unit_ty = memflow
+                            .getattr(format!("c_uint{}", unit_size * 8).as_str())?
+                            .into();
+                        pad_bits(&mut out, &unit_ty, unit_offset, used, unit_size as u32 * 8);
+                    }
+                    pad(&mut out, end, offset - end)?;
+                    pad_bits(&mut out, &ty, offset, 0, position);
+                    out.push(PyTuple::new(py, [name.into_py(py), ty, bits.into_py(py)]).into());
+                    unit = Some((offset, member_size, position + bits));
+                    end = offset + member_size;
+                }
+                _ => offsets.push(
+                    PyTuple::new(
+                        py,
+                        [
+                            offset.into_py(py),
+                            name.into_py(py),
+                            ty,
+                            bits.into_py(py),
+                            position.into_py(py),
+                        ],
+                    )
+                    .into(),
+                ),
+            },
+            None if offset >= end => {
+                unit = None;
+                pad(&mut out, end, offset - end)?;
+                out.push(PyTuple::new(py, [name.into_py(py), ty]).into());
+                end = offset + member_size;
+            }
+            None => {
+                offsets.push(PyTuple::new(py, [offset.into_py(py), name.into_py(py), ty]).into())
+            }
+        }
+    }
+    if size > end {
+        pad(&mut out, end, size - end)?;
+    }
+    Ok((out, offsets))
+}
+
+/// Lays out union members, padded to `size` by a member spanning all of it.
+pub fn union_members(
+    memflow: &PyModule,
+    members: Vec<Member>,
+    size: usize,
+) -> crate::Result<Vec<PyObject>> {
+    let py = memflow.py();
+    let mut out: Vec<PyObject> = Vec::new();
+    let mut max_size = 0;
+    for member in members {
+        max_size = max_size.max(member.size);
+        out.push(match member.bits {
+            Some((bits, _)) => {
+                PyTuple::new(py, [member.name.into_py(py), member.ty, bits.into_py(py)]).into()
+            }
+            None => PyTuple::new(py, [member.name.into_py(py), member.ty]).into(),
+        });
+    }
+    if size > max_size {
+        let array = memflow
+            .getattr("ARRAY")?
+            .call1((memflow.getattr("c_uint8")?, size))?;
+        out.push(PyTuple::new(py, ["_pad_0x0".to_object(py), array.into()]).into());
+    }
+    Ok(out)
+}
@@ -4,7 +4,11 @@ use memflow::{
 };
 use pyo3::{exceptions::PyException, prelude::*};
 
-use crate::internal::InternalDT;
+use crate::{
+    hexdump,
+    internal::{BoundMemory, DataModel, InternalDT},
+    pointer::PointerSource,
+};
 
 #[derive(Clone)]
 #[pyclass(name = "Connector")]
@@ -38,27 +42,49 @@ impl PyConnector {
         self.0.metadata().ideal_batch_size
     }
 
-    fn phys_read(&mut self, addr: umem, ty: PyObject) -> PyResult<PyObject> {
+    fn phys_read(slf: &PyCell<Self>, addr: umem, ty: PyObject) -> PyResult<PyObject> {
         let dt: InternalDT = ty.try_into()?;
-        let mut raw: Vec<u8> = vec![0; dt.size()];
+        let source = PointerSource::new(slf, "phys_read", DataModel::Native)?;
 
-        self.0
-            .phys_read_into(addr.into(), raw.as_mut_slice())
-            .map_err(|e| PyException::new_err(format!("failed to read bytes {}", e)))?;
+        let mut this = slf.try_borrow_mut()?;
+        let mut view = this.0.phys_view();
+        let mut mem = BoundMemory::new(&mut view, source);
+        let raw = dt.read_bytes(&mut mem, addr.into())?;
 
-        Ok(dt.py_from_bytes(raw)?)
+        Ok(dt.py_from_bytes(raw, Some(&mut mem))?)
     }
 
-    fn phys_read_ptr(&mut self, ptr_inst: PyObject) -> PyResult<PyObject> {
+    fn phys_read_ptr(slf: &PyCell<Self>, ptr_inst: PyObject) -> PyResult<PyObject> {
         let addr: umem = Python::with_gil(|py| ptr_inst.getattr(py, "addr")?.extract(py))?;
         let dt: InternalDT = Python::with_gil(|py| ptr_inst.getattr(py, "_type_")?.try_into())?;
-        let mut raw: Vec<u8> = vec![0; dt.size()];
+        let source = PointerSource::new(slf, "phys_read", DataModel::Native)?;
 
-        self.0
-            .phys_read_into(addr.into(), raw.as_mut_slice())
-            .map_err(|e| PyException::new_err(format!("failed to read bytes {}", e)))?;
+        let mut this = slf.try_borrow_mut()?;
+        let mut view = this.0.phys_view();
+        let mut mem = BoundMemory::new(&mut view, source);
+        let raw = dt.read_bytes(&mut mem, addr.into())?;
+
+        Ok(dt.py_from_bytes(raw, Some(&mut mem))?)
+    }
 
-        Ok(dt.py_from_bytes(raw)?)
+    /// Formats `len` bytes at the physical address `addr` as a hexdump, bytes that can not be read show as `??`.
+    /// The fields of the structure `ty` label the bytes they cover, `len` defaults to its size.
+    /// Physical memory does not tell the target, `data_model` lays out `ty` for one other than the running system.
+    #[pyo3(signature = (addr, len=None, ty=None, data_model=None))]
+    fn hexdump(
+        &mut self,
+        addr: umem,
+        len: Option<usize>,
+        ty: Option<PyObject>,
+        data_model: Option<&str>,
+    ) -> PyResult<String> {
+        let data_model = data_model.map(DataModel::try_from).transpose()?;
+        let dt = ty
+            .map(|ty| InternalDT::from_py(ty, data_model.unwrap_or_default().into()))
+            .transpose()?;
+        let len = hexdump::length(len, dt.as_ref())?;
+        let mut view = self.0.phys_view();
+        Ok(hexdump::hexdump(&mut view, addr, len, dt.as_ref()))
     }
 
     fn phys_write(&mut self, addr: umem, ty: PyObject, value: PyObject) -> PyResult<()> {
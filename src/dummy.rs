@@ -10,7 +10,13 @@ use pyo3::{exceptions::PyException, prelude::*};
 use cglue::arc::CArc;
 use cglue::*;
 
-use crate::{internal::InternalDT, os::PyOs, process::PyProcess, MemflowPyError};
+use crate::{
+    internal::{BoundMemory, DataModel, InternalDT},
+    os::PyOs,
+    pointer::PointerSource,
+    process::PyProcess,
+    MemflowPyError,
+};
 
 #[pymodule]
 pub fn register_dummy_module(_py: Python<'_>, parent_module: &PyModule) -> PyResult<()> {
@@ -86,27 +92,29 @@ impl PyDummyMemory {
         DummyMemory::new(size).into()
     }
 
-    fn read(&mut self, addr: umem, ty: PyObject) -> PyResult<PyObject> {
+    fn read(slf: &PyCell<Self>, addr: umem, ty: PyObject) -> PyResult<PyObject> {
         let dt: InternalDT = ty.try_into()?;
-        let mut raw: Vec<u8> = vec![0; dt.size()];
+        let source = PointerSource::new(slf, "read", DataModel::Native)?;
 
-        self.0
-            .phys_read_into(addr.into(), raw.as_mut_slice())
-            .map_err(|e| PyException::new_err(format!("failed to read bytes {}", e)))?;
+        let mut this = slf.try_borrow_mut()?;
+        let mut view = this.0.phys_view();
+        let mut mem = BoundMemory::new(&mut view, source);
+        let raw = dt.read_bytes(&mut mem, addr.into())?;
 
-        Ok(dt.py_from_bytes(raw)?)
+        Ok(dt.py_from_bytes(raw, Some(&mut mem))?)
     }
 
-    fn read_ptr(&mut self, ptr_inst: PyObject) -> PyResult<PyObject> {
+    fn read_ptr(slf: &PyCell<Self>, ptr_inst: PyObject) -> PyResult<PyObject> {
         let addr: umem = Python::with_gil(|py| ptr_inst.getattr(py, "addr")?.extract(py))?;
         let dt: InternalDT = Python::with_gil(|py| ptr_inst.getattr(py, "_type_")?.try_into())?;
-        let mut raw: Vec<u8> = vec![0; dt.size()];
+        let source = PointerSource::new(slf, "read", DataModel::Native)?;
 
-        self.0
-            .phys_read_into(addr.into(), raw.as_mut_slice())
-            .map_err(|e| PyException::new_err(format!("failed to read bytes {}", e)))?;
+        let mut this = slf.try_borrow_mut()?;
+        let mut view = this.0.phys_view();
+        let mut mem = BoundMemory::new(&mut view, source);
+        let raw = dt.read_bytes(&mut mem, addr.into())?;
 
-        Ok(dt.py_from_bytes(raw)?)
+        Ok(dt.py_from_bytes(raw, Some(&mut mem))?)
     }
 
     fn write(&mut self, addr: umem, ty: PyObject, value: PyObject) -> PyResult<()> {
@@ -0,0 +1,286 @@
+use memflow::prelude::v1::*;
+use pyo3::prelude::*;
+
+use crate::{
+    internal::{addr_from_bytes, DTOptions, InternalDT, MemorySource},
+    layout::{self, sorted_fields},
+    MemflowPyError,
+};
+
+/// Items of an array that are expanded or previewed at most.
+const MAX_ITEMS: usize = 16;
+
+/// Member of a dumped record, offsets are relative to the record holding it.
+struct DumpLine {
+    level: usize,
+    offset: usize,
+    name: String,
+    ty: String,
+    value: String,
+}
+
+/// Tree of a record read from memory like the WinDbg `dt` command, printed as text or a table in notebooks.
+#[pyclass(name = "TypeDump")]
+pub struct PyTypeDump {
+    title: String,
+    lines: Vec<DumpLine>,
+}
+
+#[pymethods]
+impl PyTypeDump {
+    fn __str__(&self) -> String {
+        let width = self
+            .lines
+            .iter()
+            .map(|line| line.level * 3 + line.name.len())
+            .max()
+            .unwrap_or(0);
+        let mut out = self.title.clone();
+        for line in &self.lines {
+            let indent = "   ".repeat(line.level);
+            let name = format!("{}{}", indent, line.name);
+            out += &format!("\n   +0x{:03x} {:<width$} : {}", line.offset, name, line.ty);
+            // Pointers read as `type @ 0x...`, other values follow an equal sign.
+            match line.value.starts_with('@') {
+                true => out += &format!(" {}", line.value),
+                false if !line.value.is_empty() => out += &format!(" = {}", line.value),
+                false => {}
+            }
+        }
+        out
+    }
+
+    fn __repr__(&self) -> String {
+        self.__str__()
+    }
+
+    fn _repr_html_(&self) -> String {
+        let mut out = format!(
+            "<table><caption style=\"text-align:left\"><code>{}</code></caption>\
+             <tr><th>Offset</th><th>Field</th><th>Type</th><th>Value</th></tr>",
+            escape(&self.title)
+        );
+        for line in &self.lines {
+            out += &format!(
+                "<tr><td><code>+0x{:03x}</code></td>\
+                 <td style=\"text-align:left;padding-left:{}em\"><code>{}</code></td>\
+                 <td style=\"text-align:left\"><code>{}</code></td>\
+                 <td style=\"text-align:left\"><code>{}</code></td></tr>",
+                line.offset,
+                line.level as f32 * 1.5 + 0.5,
+                escape(&line.name),
+                escape(&line.ty),
+                escape(&line.value)
+            );
+        }
+        out + "</table>"
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Walks records read from memory, following pointers to records while levels are left.
+struct Dumper<'a, M: ?Sized> {
+    py: Python<'a>,
+    mem: &'a mut M,
+    opts: DTOptions,
+    lines: Vec<DumpLine>,
+}
+
+impl<M: MemorySource + ?Sized> Dumper<'_, M> {
+    /// Formats the value of a field that is not expanded.
+    fn value(&mut self, dt: &InternalDT, bytes: &[u8]) -> crate::Result<String> {
+        let py = self.py;
+        Ok(match dt {
+            InternalDT::Structure(..) | InternalDT::Union(..) | InternalDT::DynArray(..) => {
+                String::new()
+            }
+            // Records are listed below the array once expanded.
+            InternalDT::Array(_, elem, _)
+                if matches!(**elem, InternalDT::Structure(..) | InternalDT::Union(..)) =>
+            {
+                String::new()
+            }
+            InternalDT::Pointer(..) | InternalDT::CharPtr(..) | InternalDT::WideCharPtr(..) => {
+                format!("@ {:#x}", addr_from_bytes(bytes))
+            }
+            InternalDT::Array(_, elem, len) if elem.char_width().is_none() => {
+                let mut items = bytes
+                    .chunks(elem.size())
+                    .take(MAX_ITEMS)
+                    .map(|chunk| self.value(elem, chunk))
+                    .collect::<crate::Result<Vec<_>>>()?;
+                if *len as usize > MAX_ITEMS {
+                    items.push("...".to_owned());
+                }
+                format!("[{}]", items.join(", "))
+            }
+            dt => {
+                let value = dt.py_from_bytes(bytes.to_vec(), None)?;
+                let value = value.as_ref(py);
+                // Enum members show their name next to the value.
+                match (value.getattr("name"), value.getattr("value")) {
+                    (Ok(name), Ok(int)) if matches!(dt, InternalDT::Enum(..)) => {
+                        format!("{} ({})", name, int.repr()?)
+                    }
+                    _ => value.repr()?.to_string(),
+                }
+            }
+        })
+    }
+
+    fn type_name(&self, dt: &InternalDT) -> crate::Result<String> {
+        let name = layout::type_name(self.py, dt, self.opts)?;
+        Ok(match dt {
+            InternalDT::Bitfield(_, _, bit_width) => {
+                let bit_offset = layout::bit_range(0, dt).0;
+                format!(
+                    "{}, bits {}-{}",
+                    name,
+                    bit_offset,
+                    bit_offset + *bit_width as usize - 1
+                )
+            }
+            _ => name,
+        })
+    }
+
+    /// Lists the fields of the record `dt` at `level`, expanding nested records while `depth` is above one.
+    fn record(
+        &mut self,
+        dt: &InternalDT,
+        bytes: &[u8],
+        level: usize,
+        depth: u32,
+    ) -> crate::Result<()> {
+        for (name, offset, field_dt) in sorted_fields(dt) {
+            // A dynamic array is sized by a field before it, like reading the record does.
+            let sized_dt;
+            let field_dt = match (field_dt, dt) {
+                (InternalDT::DynArray(_, _, length_field), InternalDT::Structure(_, dts)) => {
+                    let (length_offset, length_dt) = &dts[length_field];
+                    match bytes.get(*length_offset..length_offset + length_dt.size()) {
+                        Some(length_bytes) => {
+                            let length = length_dt
+                                .py_from_bytes(length_bytes.to_vec(), None)?
+                                .extract(self.py)?;
+                            sized_dt = field_dt.with_length(length);
+                            &sized_dt
+                        }
+                        None => field_dt,
+                    }
+                }
+                _ => field_dt,
+            };
+            let field_bytes = bytes.get(offset..offset + field_dt.size()).unwrap_or(&[]);
+            let ty = self.type_name(field_dt)?;
+            let value = self.value(field_dt, field_bytes)?;
+            self.lines.push(DumpLine {
+                level,
+                offset,
+                name: name.clone(),
+                ty,
+                value,
+            });
+            if depth > 1 {
+                self.expand(field_dt, field_bytes, level + 1, depth - 1)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Lists what a field holds, records by value, records behind pointers and items of arrays of records.
+    fn expand(
+        &mut self,
+        dt: &InternalDT,
+        bytes: &[u8],
+        level: usize,
+        depth: u32,
+    ) -> crate::Result<()> {
+        match dt {
+            InternalDT::Structure(..) | InternalDT::Union(..) => {
+                self.record(dt, bytes, level, depth)
+            }
+            InternalDT::Array(_, elem, _)
+                if matches!(**elem, InternalDT::Structure(..) | InternalDT::Union(..)) =>
+            {
+                let ty = self.type_name(elem)?;
+                for (idx, chunk) in bytes.chunks(elem.size()).take(MAX_ITEMS).enumerate() {
+                    self.lines.push(DumpLine {
+                        level,
+                        offset: idx * elem.size(),
+                        name: format!("[{}]", idx),
+                        ty: ty.clone(),
+                        value: String::new(),
+                    });
+                    self.record(elem, chunk, level + 1, depth)?;
+                }
+                Ok(())
+            }
+            InternalDT::Pointer(class, _) => {
+                let addr = addr_from_bytes(bytes);
+                let Ok(target) = class.getattr(self.py, "_type_") else {
+                    return Ok(());
+                };
+                let target = InternalDT::from_py(target, self.opts)?;
+                if addr == 0 || !matches!(target, InternalDT::Structure(..) | InternalDT::Union(..))
+                {
+                    return Ok(());
+                }
+                match target.read_bytes(self.mem, addr.into()) {
+                    Ok(target_bytes) => self.record(&target, &target_bytes, level, depth),
+                    // Unreadable targets are noted instead of failing the whole dump.
+                    Err(_) => {
+                        self.lines.push(DumpLine {
+                            level,
+                            offset: 0,
+                            name: "??".to_owned(),
+                            ty: self.type_name(&target)?,
+                            value: "(memory not readable)".to_owned(),
+                        });
+                        Ok(())
+                    }
+                }
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Reads the record `dt` at `addr` and dumps its fields, pointers to records are followed up to `depth` levels.
+pub fn dump(
+    py: Python<'_>,
+    dt: &InternalDT,
+    addr: Address,
+    mem: &mut (impl MemorySource + ?Sized),
+    opts: DTOptions,
+    depth: u32,
+) -> crate::Result<PyTypeDump> {
+    if !matches!(dt, InternalDT::Structure(..) | InternalDT::Union(..)) {
+        return Err(MemflowPyError::InvalidType(layout::type_name(
+            py, dt, opts,
+        )?));
+    }
+    let bytes = dt.read_bytes(mem, addr)?;
+    let title = format!(
+        "{} @ {:#x}",
+        layout::type_name(py, dt, opts)?,
+        addr.to_umem()
+    );
+    let mut dumper = Dumper {
+        py,
+        mem,
+        opts,
+        lines: Vec::new(),
+    };
+    dumper.record(dt, &bytes, 0, depth)?;
+    Ok(PyTypeDump {
+        title,
+        lines: dumper.lines,
+    })
+}
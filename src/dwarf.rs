@@ -0,0 +1,1296 @@
+use std::collections::HashMap;
+
+use goblin::elf::{
+    header::ET_REL,
+    program_header::PT_LOAD,
+    section_header::{SHF_COMPRESSED, SHN_ABS, SHN_UNDEF},
+    sym::{STT_FUNC, STT_NOTYPE, STT_OBJECT},
+    Elf,
+};
+use indexmap::IndexMap;
+use pyo3::{
+    exceptions::{PyAttributeError, PyKeyError},
+    prelude::*,
+    types::{PyDict, PyList, PyModule, PyTuple},
+};
+
+use crate::{
+    cdecl::{self, Member},
+    MemflowPyError,
+};
+
+const DW_TAG_ARRAY_TYPE: u16 = 0x01;
+const DW_TAG_CLASS_TYPE: u16 = 0x02;
+const DW_TAG_ENUMERATION_TYPE: u16 = 0x04;
+const DW_TAG_FORMAL_PARAMETER: u16 = 0x05;
+const DW_TAG_MEMBER: u16 = 0x0d;
+const DW_TAG_POINTER_TYPE: u16 = 0x0f;
+const DW_TAG_REFERENCE_TYPE: u16 = 0x10;
+const DW_TAG_STRUCTURE_TYPE: u16 = 0x13;
+const DW_TAG_SUBROUTINE_TYPE: u16 = 0x15;
+const DW_TAG_TYPEDEF: u16 = 0x16;
+const DW_TAG_UNION_TYPE: u16 = 0x17;
+const DW_TAG_INHERITANCE: u16 = 0x1c;
+const DW_TAG_PTR_TO_MEMBER_TYPE: u16 = 0x1f;
+const DW_TAG_SUBRANGE_TYPE: u16 = 0x21;
+const DW_TAG_BASE_TYPE: u16 = 0x24;
+const DW_TAG_CONST_TYPE: u16 = 0x26;
+const DW_TAG_ENUMERATOR: u16 = 0x28;
+const DW_TAG_PACKED_TYPE: u16 = 0x2d;
+const DW_TAG_VOLATILE_TYPE: u16 = 0x35;
+const DW_TAG_RESTRICT_TYPE: u16 = 0x37;
+const DW_TAG_UNSPECIFIED_TYPE: u16 = 0x3b;
+const DW_TAG_RVALUE_REFERENCE_TYPE: u16 = 0x42;
+const DW_TAG_ATOMIC_TYPE: u16 = 0x47;
+const DW_TAG_IMMUTABLE_TYPE: u16 = 0x4b;
+
+const DW_AT_SIBLING: u16 = 0x01;
+const DW_AT_NAME: u16 = 0x03;
+const DW_AT_BYTE_SIZE: u16 = 0x0b;
+const DW_AT_BIT_OFFSET: u16 = 0x0c;
+const DW_AT_BIT_SIZE: u16 = 0x0d;
+const DW_AT_CONST_VALUE: u16 = 0x1c;
+const DW_AT_LOWER_BOUND: u16 = 0x22;
+const DW_AT_UPPER_BOUND: u16 = 0x2f;
+const DW_AT_COUNT: u16 = 0x37;
+const DW_AT_DATA_MEMBER_LOCATION: u16 = 0x38;
+const DW_AT_DECLARATION: u16 = 0x3c;
+const DW_AT_ENCODING: u16 = 0x3e;
+const DW_AT_EXTERNAL: u16 = 0x3f;
+const DW_AT_TYPE: u16 = 0x49;
+const DW_AT_DATA_BIT_OFFSET: u16 = 0x6b;
+const DW_AT_STR_OFFSETS_BASE: u16 = 0x72;
+
+const DW_ATE_BOOLEAN: u64 = 0x02;
+const DW_ATE_COMPLEX_FLOAT: u64 = 0x03;
+const DW_ATE_FLOAT: u64 = 0x04;
+const DW_ATE_SIGNED: u64 = 0x05;
+const DW_ATE_SIGNED_CHAR: u64 = 0x06;
+
+const DW_UT_TYPE: u8 = 0x02;
+const DW_UT_SKELETON: u8 = 0x04;
+const DW_UT_SPLIT_COMPILE: u8 = 0x05;
+const DW_UT_SPLIT_TYPE: u8 = 0x06;
+
+const DW_OP_CONSTU: u8 = 0x10;
+const DW_OP_PLUS_UCONST: u8 = 0x23;
+
+fn invalid(msg: impl Into<String>) -> MemflowPyError {
+    MemflowPyError::InvalidDwarf(msg.into())
+}
+
+/// Cursor over a debug section in the byte order of the ELF file.
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+    big_endian: bool,
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8], pos: usize, big_endian: bool) -> Self {
+        Self {
+            data,
+            pos,
+            big_endian,
+        }
+    }
+
+    fn bytes(&mut self, len: usize) -> crate::Result<&'a [u8]> {
+        let bytes = self
+            .data
+            .get(self.pos..self.pos + len)
+            .ok_or_else(|| invalid("unexpected end of section"))?;
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> crate::Result<u8> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    /// Reads an unsigned integer of `size` bytes.
+    fn uint(&mut self, size: usize) -> crate::Result<u64> {
+        let bytes = self.bytes(size)?;
+        let fold = |value, byte: &u8| (value << 8) | *byte as u64;
+        Ok(match self.big_endian {
+            true => bytes.iter().fold(0, fold),
+            false => bytes.iter().rev().fold(0, fold),
+        })
+    }
+
+    fn uleb(&mut self) -> crate::Result<u64> {
+        let mut value = 0u64;
+        let mut shift = 0;
+        loop {
+            let byte = self.u8()?;
+            if shift < 64 {
+                value |= ((byte & 0x7f) as u64) << shift;
+            }
+            shift += 7;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+    }
+
+    fn sleb(&mut self) -> crate::Result<i64> {
+        let mut value = 0i64;
+        let mut shift = 0;
+        loop {
+            let byte = self.u8()?;
+            if shift < 64 {
+                value |= ((byte & 0x7f) as i64) << shift;
+            }
+            shift += 7;
+            if byte & 0x80 == 0 {
+                if shift < 64 && byte & 0x40 != 0 {
+                    value |= -1 << shift;
+                }
+                return Ok(value);
+            }
+        }
+    }
+
+    fn cstr(&mut self) -> crate::Result<String> {
+        let len = self
+            .data
+            .get(self.pos..)
+            .and_then(|rest| rest.iter().position(|b| *b == 0))
+            .ok_or_else(|| invalid("unterminated string"))?;
+        let name = String::from_utf8_lossy(&self.data[self.pos..self.pos + len]).into_owned();
+        self.pos += len + 1;
+        Ok(name)
+    }
+}
+
+/// Debug sections copied out of the ELF file, `.debug_types` is appended to `.debug_info` so a
+/// single offset identifies every DIE.
+#[derive(Default)]
+struct Sections {
+    info: Vec<u8>,
+    info_len: usize,
+    abbrev: Vec<u8>,
+    str: Vec<u8>,
+    line_str: Vec<u8>,
+    str_offsets: Vec<u8>,
+}
+
+struct Unit {
+    start: usize,
+    end: usize,
+    /// Offset of the first DIE.
+    dies: usize,
+    version: u16,
+    addr_size: u8,
+    offset_size: u8,
+    abbrevs: usize,
+    str_offsets_base: usize,
+}
+
+struct AttrSpec {
+    at: u16,
+    form: u16,
+    implicit_const: i64,
+}
+
+struct Abbrev {
+    tag: u16,
+    children: bool,
+    attrs: Vec<AttrSpec>,
+}
+
+enum Value {
+    Uint(u64),
+    Int(i64),
+    /// Offset of the referenced DIE.
+    Ref(usize),
+    /// Signature of the referenced type unit.
+    Sig(u64),
+    Str(String),
+    /// Range of a block or expression within `.debug_info`.
+    Block(usize, usize),
+    Flag(bool),
+}
+
+struct Die {
+    offset: usize,
+    tag: u16,
+    children: bool,
+    attrs: Vec<(u16, Value)>,
+    /// Offset following the attributes, the first child if there are children.
+    end: usize,
+}
+
+impl Die {
+    fn attr(&self, at: u16) -> Option<&Value> {
+        self.attrs.iter().find(|(a, _)| *a == at).map(|(_, v)| v)
+    }
+
+    fn name(&self) -> Option<&str> {
+        match self.attr(DW_AT_NAME) {
+            Some(Value::Str(name)) => Some(name),
+            _ => None,
+        }
+    }
+
+    fn flag(&self, at: u16) -> bool {
+        matches!(self.attr(at), Some(Value::Flag(true)))
+    }
+
+    /// Returns a non-negative constant attribute.
+    fn uint(&self, at: u16) -> Option<u64> {
+        match self.attr(at)? {
+            Value::Uint(value) => Some(*value),
+            Value::Int(value) => u64::try_from(*value).ok(),
+            _ => None,
+        }
+    }
+}
+
+/// Types and symbols of an ELF file with DWARF debug info, types are generated on first access.
+#[pyclass(name = "Dwarf")]
+pub struct PyDwarf {
+    sections: Sections,
+    big_endian: bool,
+    units: Vec<Unit>,
+    abbrevs: Vec<HashMap<u64, Abbrev>>,
+    /// Structures, unions and enums with a definition by name, used to resolve declarations.
+    definitions: IndexMap<String, usize>,
+    /// Definitions and typedefs by name.
+    names: IndexMap<String, usize>,
+    signatures: HashMap<u64, usize>,
+    symbols: IndexMap<String, u64>,
+    built: HashMap<usize, (PyObject, usize)>,
+    pinned_types: HashMap<(&'static str, usize), PyObject>,
+}
+
+impl PyDwarf {
+    fn parse(data: &[u8]) -> crate::Result<Self> {
+        let elf = Elf::parse(data).map_err(|e| invalid(e.to_string()))?;
+        if elf.header.e_type == ET_REL {
+            return Err(invalid(
+                "relocatable objects are not supported, link them first",
+            ));
+        }
+
+        let mut sections = Sections::default();
+        let mut types = Vec::new();
+        for header in &elf.section_headers {
+            let name = elf.shdr_strtab.get_at(header.sh_name).unwrap_or_default();
+            let section = match name {
+                ".debug_info" => &mut sections.info,
+                ".debug_types" => &mut types,
+                ".debug_abbrev" => &mut sections.abbrev,
+                ".debug_str" => &mut sections.str,
+                ".debug_line_str" => &mut sections.line_str,
+                ".debug_str_offsets" => &mut sections.str_offsets,
+                _ if name.starts_with(".zdebug_") => {
+                    return Err(invalid("compressed debug sections are not supported"))
+                }
+                _ => continue,
+            };
+            if header.sh_flags & SHF_COMPRESSED as u64 != 0 {
+                return Err(invalid("compressed debug sections are not supported"));
+            }
+            let range = header.file_range().unwrap_or_default();
+            *section = data
+                .get(range)
+                .ok_or_else(|| invalid(format!("section `{}` out of bounds", name)))?
+                .to_vec();
+        }
+        if sections.info.is_empty() {
+            return Err(invalid("missing `.debug_info` section"));
+        }
+        sections.info_len = sections.info.len();
+        sections.info.extend_from_slice(&types);
+
+        let mut dwarf = Self {
+            sections,
+            big_endian: !elf.little_endian,
+            units: Vec::new(),
+            abbrevs: Vec::new(),
+            definitions: IndexMap::new(),
+            names: IndexMap::new(),
+            signatures: HashMap::new(),
+            symbols: read_symbols(&elf),
+            built: HashMap::new(),
+            pinned_types: HashMap::new(),
+        };
+        dwarf.read_units()?;
+        dwarf.index()?;
+        Ok(dwarf)
+    }
+
+    fn read_units(&mut self) -> crate::Result<()> {
+        let mut abbrev_tables = HashMap::new();
+        let mut pos = 0;
+        while pos < self.sections.info.len() {
+            let mut r = Reader::new(&self.sections.info, pos, self.big_endian);
+            let (len, offset_size) = match r.uint(4)? {
+                0xffff_ffff => (r.uint(8)? as usize, 8),
+                len => (len as usize, 4),
+            };
+            let start = pos;
+            let end = r.pos + len;
+            let version = r.uint(2)? as u16;
+            let in_types = pos >= self.sections.info_len;
+            let (unit_type, addr_size, abbrev_offset) = match version {
+                2..=4 => {
+                    let abbrev_offset = r.uint(offset_size)? as usize;
+                    let addr_size = r.u8()?;
+                    let unit_type = if in_types { DW_UT_TYPE } else { 0 };
+                    (unit_type, addr_size, abbrev_offset)
+                }
+                5 => {
+                    let unit_type = r.u8()?;
+                    let addr_size = r.u8()?;
+                    (unit_type, addr_size, r.uint(offset_size)? as usize)
+                }
+                version => return Err(invalid(format!("unsupported DWARF version {}", version))),
+            };
+            match unit_type {
+                DW_UT_TYPE | DW_UT_SPLIT_TYPE => {
+                    let signature = r.uint(8)?;
+                    let type_offset = r.uint(offset_size)? as usize;
+                    self.signatures.insert(signature, start + type_offset);
+                }
+                DW_UT_SKELETON | DW_UT_SPLIT_COMPILE => {
+                    let _dwo_id = r.uint(8)?;
+                }
+                _ => {}
+            }
+            let abbrevs = match abbrev_tables.get(&abbrev_offset) {
+                Some(idx) => *idx,
+                None => {
+                    self.abbrevs.push(self.read_abbrevs(abbrev_offset)?);
+                    abbrev_tables.insert(abbrev_offset, self.abbrevs.len() - 1);
+                    self.abbrevs.len() - 1
+                }
+            };
+            self.units.push(Unit {
+                start,
+                end,
+                dies: r.pos,
+                version,
+                addr_size,
+                offset_size: offset_size as u8,
+                abbrevs,
+                // Default for units without `DW_AT_str_offsets_base`, right past the table header.
+                str_offsets_base: offset_size * 2,
+            });
+            pos = end;
+        }
+        Ok(())
+    }
+
+    fn read_abbrevs(&self, offset: usize) -> crate::Result<HashMap<u64, Abbrev>> {
+        let mut abbrevs = HashMap::new();
+        let mut r = Reader::new(&self.sections.abbrev, offset, self.big_endian);
+        loop {
+            let code = r.uleb()?;
+            if code == 0 {
+                return Ok(abbrevs);
+            }
+            let tag = r.uleb()? as u16;
+            let children = r.u8()? != 0;
+            let mut attrs = Vec::new();
+            loop {
+                let at = r.uleb()? as u16;
+                let form = r.uleb()? as u16;
+                if at == 0 && form == 0 {
+                    break;
+                }
+                let implicit_const = if form == 0x21 { r.sleb()? } else { 0 };
+                attrs.push(AttrSpec {
+                    at,
+                    form,
+                    implicit_const,
+                });
+            }
+            abbrevs.insert(
+                code,
+                Abbrev {
+                    tag,
+                    children,
+                    attrs,
+                },
+            );
+        }
+    }
+
+    /// Reads `DW_AT_str_offsets_base` of the root DIE, skipping its other attributes as their strings
+    /// may depend on it.
+    fn str_offsets_base(&self, unit: &Unit) -> crate::Result<Option<usize>> {
+        let mut r = Reader::new(&self.sections.info, unit.dies, self.big_endian);
+        let Some(abbrev) = self.abbrevs[unit.abbrevs].get(&r.uleb()?) else {
+            return Ok(None);
+        };
+        for spec in &abbrev.attrs {
+            let keep = spec.at == DW_AT_STR_OFFSETS_BASE;
+            if let Some(Value::Uint(base)) =
+                self.value(&mut r, unit, spec.form, spec.implicit_const, keep)?
+            {
+                return Ok(Some(base as usize));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Walks every unit once to find the named types.
+    fn index(&mut self) -> crate::Result<()> {
+        for idx in 0..self.units.len() {
+            if let Some(base) = self.str_offsets_base(&self.units[idx])? {
+                self.units[idx].str_offsets_base = base;
+            }
+        }
+        let mut typedefs = Vec::new();
+        for unit in &self.units {
+            let mut pos = unit.dies;
+            while pos < unit.end {
+                let offset = pos;
+                let mut r = Reader::new(&self.sections.info, pos, self.big_endian);
+                let code = r.uleb()?;
+                if code == 0 {
+                    pos = r.pos;
+                    continue;
+                }
+                let abbrev = self.abbrevs[unit.abbrevs]
+                    .get(&code)
+                    .ok_or_else(|| invalid(format!("unknown abbreviation at {:#x}", pos)))?;
+                let keep = matches!(
+                    abbrev.tag,
+                    DW_TAG_STRUCTURE_TYPE
+                        | DW_TAG_CLASS_TYPE
+                        | DW_TAG_UNION_TYPE
+                        | DW_TAG_ENUMERATION_TYPE
+                        | DW_TAG_TYPEDEF
+                );
+                let mut name = None;
+                let mut declaration = false;
+                for spec in &abbrev.attrs {
+                    let value = self.value(&mut r, unit, spec.form, spec.implicit_const, keep)?;
+                    match (spec.at, value) {
+                        (DW_AT_NAME, Some(Value::Str(value))) => name = Some(value),
+                        (DW_AT_DECLARATION, Some(Value::Flag(value))) => declaration = value,
+                        _ => {}
+                    }
+                }
+                pos = r.pos;
+                match (name, abbrev.tag) {
+                    (Some(name), DW_TAG_TYPEDEF) => typedefs.push((name, offset)),
+                    (Some(name), _) if !declaration => {
+                        self.definitions.entry(name).or_insert(offset);
+                    }
+                    _ => {}
+                }
+            }
+        }
+        // Tags take precedence over typedefs of the same name (`typedef struct foo foo`).
+        self.names = self.definitions.clone();
+        for (name, offset) in typedefs {
+            self.names.entry(name).or_insert(offset);
+        }
+        Ok(())
+    }
+
+    /// Reads an attribute value, `None` if it is skipped or of no use for the layout.
+    fn value(
+        &self,
+        r: &mut Reader,
+        unit: &Unit,
+        form: u16,
+        implicit_const: i64,
+        keep: bool,
+    ) -> crate::Result<Option<Value>> {
+        let offset_size = unit.offset_size as usize;
+        let addr_size = unit.addr_size as usize;
+        let value = match form {
+            // DW_FORM_addr
+            0x01 => Value::Uint(r.uint(addr_size)?),
+            // DW_FORM_block2, DW_FORM_block4, DW_FORM_block, DW_FORM_exprloc, DW_FORM_block1
+            0x03 | 0x04 | 0x09 | 0x18 | 0x0a => {
+                let len = match form {
+                    0x03 => r.uint(2)?,
+                    0x04 => r.uint(4)?,
+                    0x0a => r.u8()? as u64,
+                    _ => r.uleb()?,
+                } as usize;
+                let start = r.pos;
+                r.bytes(len)?;
+                Value::Block(start, start + len)
+            }
+            // DW_FORM_data2, DW_FORM_data4, DW_FORM_data8, DW_FORM_data1
+            0x05 => Value::Uint(r.uint(2)?),
+            0x06 => Value::Uint(r.uint(4)?),
+            0x07 => Value::Uint(r.uint(8)?),
+            0x0b => Value::Uint(r.u8()? as u64),
+            // DW_FORM_data16
+            0x1e => {
+                r.bytes(16)?;
+                return Ok(None);
+            }
+            // DW_FORM_string
+            0x08 => match keep {
+                true => Value::Str(r.cstr()?),
+                false => {
+                    let len = r.data[r.pos..].iter().position(|b| *b == 0);
+                    r.pos += len.ok_or_else(|| invalid("unterminated string"))? + 1;
+                    return Ok(None);
+                }
+            },
+            // DW_FORM_flag, DW_FORM_flag_present
+            0x0c => Value::Flag(r.u8()? != 0),
+            0x19 => Value::Flag(true),
+            // DW_FORM_sdata, DW_FORM_udata, DW_FORM_implicit_const
+            0x0d => Value::Int(r.sleb()?),
+            0x0f => Value::Uint(r.uleb()?),
+            0x21 => Value::Int(implicit_const),
+            // DW_FORM_strp, DW_FORM_line_strp
+            0x0e | 0x1f => {
+                let offset = r.uint(offset_size)? as usize;
+                if !keep {
+                    return Ok(None);
+                }
+                let section = if form == 0x0e {
+                    &self.sections.str
+                } else {
+                    &self.sections.line_str
+                };
+                Value::Str(Reader::new(section, offset, self.big_endian).cstr()?)
+            }
+            // DW_FORM_strx, DW_FORM_strx1 to DW_FORM_strx4
+            0x1a | 0x25..=0x28 => {
+                let idx = match form {
+                    0x1a => r.uleb()?,
+                    _ => r.uint((form - 0x24) as usize)?,
+                } as usize;
+                if !keep {
+                    return Ok(None);
+                }
+                let mut offsets = Reader::new(
+                    &self.sections.str_offsets,
+                    unit.str_offsets_base + idx * offset_size,
+                    self.big_endian,
+                );
+                let offset = offsets.uint(offset_size)? as usize;
+                Value::Str(Reader::new(&self.sections.str, offset, self.big_endian).cstr()?)
+            }
+            // DW_FORM_ref_addr, sized like an address before DWARF 3
+            0x10 => {
+                let size = if unit.version == 2 {
+                    addr_size
+                } else {
+                    offset_size
+                };
+                Value::Ref(r.uint(size)? as usize)
+            }
+            // DW_FORM_ref1, DW_FORM_ref2, DW_FORM_ref4, DW_FORM_ref8, DW_FORM_ref_udata
+            0x11 => Value::Ref(unit.start + r.u8()? as usize),
+            0x12 => Value::Ref(unit.start + r.uint(2)? as usize),
+            0x13 => Value::Ref(unit.start + r.uint(4)? as usize),
+            0x14 => Value::Ref(unit.start + r.uint(8)? as usize),
+            0x15 => Value::Ref(unit.start + r.uleb()? as usize),
+            // DW_FORM_ref_sig8
+            0x20 => Value::Sig(r.uint(8)?),
+            // DW_FORM_sec_offset
+            0x17 => Value::Uint(r.uint(offset_size)?),
+            // DW_FORM_addrx, DW_FORM_loclistx, DW_FORM_rnglistx
+            0x1b | 0x22 | 0x23 => {
+                r.uleb()?;
+                return Ok(None);
+            }
+            // DW_FORM_addrx1 to DW_FORM_addrx4
+            0x29..=0x2c => {
+                r.bytes((form - 0x28) as usize)?;
+                return Ok(None);
+            }
+            // DW_FORM_ref_sup4, DW_FORM_strp_sup, DW_FORM_ref_sup8
+            0x1c => {
+                r.bytes(4)?;
+                return Ok(None);
+            }
+            0x1d => {
+                r.bytes(offset_size)?;
+                return Ok(None);
+            }
+            0x24 => {
+                r.bytes(8)?;
+                return Ok(None);
+            }
+            // DW_FORM_indirect
+            0x16 => {
+                let form = r.uleb()? as u16;
+                return self.value(r, unit, form, implicit_const, keep);
+            }
+            form => return Err(invalid(format!("unsupported attribute form {:#x}", form))),
+        };
+        Ok(keep.then_some(value))
+    }
+
+    fn unit(&self, offset: usize) -> crate::Result<&Unit> {
+        let idx = self.units.partition_point(|unit| unit.start <= offset);
+        idx.checked_sub(1)
+            .map(|idx| &self.units[idx])
+            .filter(|unit| offset >= unit.dies && offset < unit.end)
+            .ok_or_else(|| invalid(format!("DIE offset {:#x} out of bounds", offset)))
+    }
+
+    /// Parses the DIE at `offset`, `None` for the null entry closing a list of children.
+    fn die(&self, offset: usize) -> crate::Result<Option<Die>> {
+        let unit = self.unit(offset)?;
+        let mut r = Reader::new(&self.sections.info, offset, self.big_endian);
+        let code = r.uleb()?;
+        if code == 0 {
+            return Ok(None);
+        }
+        let abbrev = self.abbrevs[unit.abbrevs]
+            .get(&code)
+            .ok_or_else(|| invalid(format!("unknown abbreviation at {:#x}", offset)))?;
+        let mut attrs = Vec::new();
+        for spec in &abbrev.attrs {
+            if let Some(value) = self.value(&mut r, unit, spec.form, spec.implicit_const, true)? {
+                attrs.push((spec.at, value));
+            }
+        }
+        Ok(Some(Die {
+            offset,
+            tag: abbrev.tag,
+            children: abbrev.children,
+            attrs,
+            end: r.pos,
+        }))
+    }
+
+    fn children(&self, die: &Die) -> crate::Result<Vec<Die>> {
+        let mut children = Vec::new();
+        if !die.children {
+            return Ok(children);
+        }
+        let mut pos = die.end;
+        while let Some(child) = self.die(pos)? {
+            pos = match (child.children, child.attr(DW_AT_SIBLING)) {
+                (false, _) => child.end,
+                (true, Some(Value::Ref(sibling))) => *sibling,
+                (true, _) => self.skip_children(child.end)?,
+            };
+            children.push(child);
+        }
+        Ok(children)
+    }
+
+    /// Returns the offset following the children starting at `pos`.
+    fn skip_children(&self, mut pos: usize) -> crate::Result<usize> {
+        let mut depth = 1;
+        while depth > 0 {
+            match self.die(pos)? {
+                Some(die) => {
+                    depth += die.children as usize;
+                    pos = die.end;
+                }
+                None => {
+                    depth -= 1;
+                    pos += 1;
+                }
+            }
+        }
+        Ok(pos)
+    }
+
+    fn type_ref(&self, die: &Die) -> Option<usize> {
+        match die.attr(DW_AT_TYPE)? {
+            Value::Ref(offset) => Some(*offset),
+            Value::Sig(signature) => self.signatures.get(signature).copied(),
+            _ => None,
+        }
+    }
+
+    /// Follows typedefs and qualifiers, `None` for `void`.
+    fn strip(&self, mut offset: usize) -> crate::Result<Option<Die>> {
+        loop {
+            let die = self
+                .die(offset)?
+                .ok_or_else(|| invalid(format!("missing type at {:#x}", offset)))?;
+            match die.tag {
+                DW_TAG_TYPEDEF
+                | DW_TAG_CONST_TYPE
+                | DW_TAG_VOLATILE_TYPE
+                | DW_TAG_RESTRICT_TYPE
+                | DW_TAG_ATOMIC_TYPE
+                | DW_TAG_IMMUTABLE_TYPE
+                | DW_TAG_PACKED_TYPE => match self.type_ref(&die) {
+                    Some(next) => offset = next,
+                    None => return Ok(None),
+                },
+                DW_TAG_UNSPECIFIED_TYPE => return Ok(None),
+                _ => return Ok(Some(die)),
+            }
+        }
+    }
+
+    fn build(&mut self, memflow: &PyModule, offset: usize) -> crate::Result<(PyObject, usize)> {
+        self.build_named(memflow, offset, None)
+    }
+
+    /// Returns the python type and size of the type at `offset`, anonymous records are named by `hint`.
+    fn build_named(
+        &mut self,
+        memflow: &PyModule,
+        offset: usize,
+        hint: Option<&str>,
+    ) -> crate::Result<(PyObject, usize)> {
+        let py = memflow.py();
+        if let Some((obj, size)) = self.built.get(&offset) {
+            return Ok((obj.clone_ref(py), *size));
+        }
+        let die = self
+            .die(offset)?
+            .ok_or_else(|| invalid(format!("missing type at {:#x}", offset)))?;
+        let addr_size = self.unit(offset)?.addr_size as usize;
+        let (obj, size) = match die.tag {
+            DW_TAG_BASE_TYPE => self.base_type(memflow, &die)?,
+            DW_TAG_POINTER_TYPE | DW_TAG_REFERENCE_TYPE | DW_TAG_RVALUE_REFERENCE_TYPE => {
+                let size = die.uint(DW_AT_BYTE_SIZE).map_or(addr_size, |s| s as usize);
+                (self.pointer_to(memflow, self.type_ref(&die), size)?, size)
+            }
+            DW_TAG_TYPEDEF
+            | DW_TAG_CONST_TYPE
+            | DW_TAG_VOLATILE_TYPE
+            | DW_TAG_RESTRICT_TYPE
+            | DW_TAG_ATOMIC_TYPE
+            | DW_TAG_IMMUTABLE_TYPE
+            | DW_TAG_PACKED_TYPE => {
+                let target = self
+                    .type_ref(&die)
+                    .ok_or_else(|| invalid("`void` can only be pointed to"))?;
+                let hint = match die.tag {
+                    DW_TAG_TYPEDEF => die.name().or(hint),
+                    _ => hint,
+                };
+                self.build_named(memflow, target, hint)?
+            }
+            DW_TAG_STRUCTURE_TYPE
+            | DW_TAG_CLASS_TYPE
+            | DW_TAG_UNION_TYPE
+            | DW_TAG_ENUMERATION_TYPE
+                if die.flag(DW_AT_DECLARATION) =>
+            {
+                match die.name().and_then(|name| self.definitions.get(name)) {
+                    Some(definition) => self.build_named(memflow, *definition, hint)?,
+                    // Types never defined are opaque.
+                    None => match die.tag {
+                        DW_TAG_ENUMERATION_TYPE => self.enum_class(memflow, &die, hint)?,
+                        _ => self.record_class(memflow, &die, hint)?,
+                    },
+                }
+            }
+            DW_TAG_STRUCTURE_TYPE | DW_TAG_CLASS_TYPE | DW_TAG_UNION_TYPE => {
+                self.record_class(memflow, &die, hint)?
+            }
+            DW_TAG_ENUMERATION_TYPE => self.enum_class(memflow, &die, hint)?,
+            DW_TAG_ARRAY_TYPE => self.array(memflow, &die)?,
+            // Pointers to members are stored as opaque bytes, their layout depends on the ABI.
+            DW_TAG_PTR_TO_MEMBER_TYPE => {
+                let size = die.uint(DW_AT_BYTE_SIZE).map_or(addr_size, |s| s as usize);
+                (self.bytes(memflow, size)?, size)
+            }
+            DW_TAG_SUBROUTINE_TYPE => return Err(invalid("functions can only be pointed to")),
+            DW_TAG_UNSPECIFIED_TYPE => return Err(invalid("`void` can only be pointed to")),
+            tag => {
+                return Err(invalid(format!(
+                    "unsupported type tag {:#x} at {:#x}",
+                    tag, offset
+                )))
+            }
+        };
+        self.built.insert(offset, (obj.clone_ref(py), size));
+        Ok((obj, size))
+    }
+
+    fn base_type(&mut self, memflow: &PyModule, die: &Die) -> crate::Result<(PyObject, usize)> {
+        let size = die.uint(DW_AT_BYTE_SIZE).unwrap_or(0) as usize;
+        let encoding = die.uint(DW_AT_ENCODING).unwrap_or(0);
+        let name = die.name().unwrap_or_default();
+        let obj = match encoding {
+            DW_ATE_BOOLEAN if size == 1 => memflow.getattr("c_bool")?.into(),
+            DW_ATE_FLOAT => match size {
+                4 => memflow.getattr("c_float")?.into(),
+                8 => memflow.getattr("c_double")?.into(),
+                _ if name.contains("long double") => {
+                    self.pinned_type(memflow, "c_longdouble", "c_longdouble", "_size_", size)?
+                }
+                _ => self.bytes(memflow, size)?,
+            },
+            DW_ATE_COMPLEX_FLOAT if size == 8 || size == 16 => {
+                let part = if size == 8 { "c_float" } else { "c_double" };
+                memflow
+                    .getattr("ARRAY")?
+                    .call1((memflow.getattr(part)?, 2))?
+                    .into()
+            }
+            // Plain `char` is text regardless of its signedness.
+            _ if name == "char" && size == 1 => memflow.getattr("c_char")?.into(),
+            _ => match self.int_type(memflow, size, is_signed(encoding)) {
+                Ok(obj) => obj,
+                Err(_) => self.bytes(memflow, size)?,
+            },
+        };
+        Ok((obj, size))
+    }
+
+    fn int_type(&self, memflow: &PyModule, size: usize, signed: bool) -> crate::Result<PyObject> {
+        if !matches!(size, 1 | 2 | 4 | 8) {
+            return Err(invalid(format!("no integer type of {} bytes", size)));
+        }
+        let name = match signed {
+            true => format!("c_int{}", size * 8),
+            false => format!("c_uint{}", size * 8),
+        };
+        Ok(memflow.getattr(name.as_str())?.into())
+    }
+
+    fn bytes(&self, memflow: &PyModule, size: usize) -> crate::Result<PyObject> {
+        Ok(memflow
+            .getattr("ARRAY")?
+            .call1((memflow.getattr("c_uint8")?, size))?
+            .into())
+    }
+
+    /// Subclass of `base` pinned by setting `attr` to `value`.
+    fn pinned_type(
+        &mut self,
+        memflow: &PyModule,
+        name: &'static str,
+        base: &str,
+        attr: &str,
+        value: usize,
+    ) -> crate::Result<PyObject> {
+        let py = memflow.py();
+        if let Some(obj) = self.pinned_types.get(&(name, value)) {
+            return Ok(obj.clone_ref(py));
+        }
+        let attrs = PyDict::new(py);
+        attrs.set_item(attr, value)?;
+        let obj = cdecl::new_class(memflow, name, base, attrs)?;
+        self.pinned_types.insert((name, value), obj.clone_ref(py));
+        Ok(obj)
+    }
+
+    fn pointer_to(
+        &mut self,
+        memflow: &PyModule,
+        target: Option<usize>,
+        size: usize,
+    ) -> crate::Result<PyObject> {
+        let target = match target {
+            Some(target) => self.strip(target)?,
+            None => None,
+        };
+        let Some(target) = target else {
+            return self.pinned_type(memflow, "c_void_p", "Pointer", "_byteness_", size);
+        };
+        match target.tag {
+            // Character pointers are strings, followed on demand like ctypes does.
+            DW_TAG_BASE_TYPE if target.name() == Some("char") => {
+                self.pinned_type(memflow, "c_char_p", "c_char_p", "_byteness_", size)
+            }
+            // Signatures that can not be expressed fall back to an untyped pointer.
+            DW_TAG_SUBROUTINE_TYPE => match self.function_pointer(memflow, &target, size) {
+                Ok(obj) => Ok(obj),
+                Err(_) => self.pinned_type(memflow, "c_void_p", "Pointer", "_byteness_", size),
+            },
+            _ => {
+                let (target, _) = self.build(memflow, target.offset)?;
+                Ok(memflow.getattr("POINTER")?.call1((target, size))?.into())
+            }
+        }
+    }
+
+    fn function_pointer(
+        &mut self,
+        memflow: &PyModule,
+        die: &Die,
+        size: usize,
+    ) -> crate::Result<PyObject> {
+        let py = memflow.py();
+        let restype = match self.type_ref(die) {
+            Some(ret) => match self.strip(ret)? {
+                Some(_) => self.build(memflow, ret)?.0,
+                None => py.None(),
+            },
+            None => py.None(),
+        };
+        let mut fn_args = vec![restype];
+        for child in self.children(die)? {
+            if child.tag != DW_TAG_FORMAL_PARAMETER {
+                continue;
+            }
+            let arg = self
+                .type_ref(&child)
+                .ok_or_else(|| invalid("parameter without a type"))?;
+            fn_args.push(self.build(memflow, arg)?.0);
+        }
+        let kwargs = PyDict::new(py);
+        kwargs.set_item("byteness", size)?;
+        Ok(memflow
+            .getattr("CFUNCTYPE")?
+            .call(PyTuple::new(py, fn_args), Some(kwargs))?
+            .into())
+    }
+
+    fn array(&mut self, memflow: &PyModule, die: &Die) -> crate::Result<(PyObject, usize)> {
+        let elem = self
+            .type_ref(die)
+            .ok_or_else(|| invalid("array without an element type"))?;
+        let mut dims = Vec::new();
+        for child in self.children(die)? {
+            if child.tag != DW_TAG_SUBRANGE_TYPE {
+                continue;
+            }
+            // Flexible and variable length arrays have no known length.
+            let len = match (child.uint(DW_AT_COUNT), child.uint(DW_AT_UPPER_BOUND)) {
+                (Some(count), _) => count,
+                (None, Some(upper)) => {
+                    (upper + 1).saturating_sub(child.uint(DW_AT_LOWER_BOUND).unwrap_or(0))
+                }
+                _ => 0,
+            };
+            dims.push(len as usize);
+        }
+        if dims.is_empty() {
+            dims.push(0);
+        }
+        let (mut obj, mut size) = self.build(memflow, elem)?;
+        for len in dims.into_iter().rev() {
+            obj = memflow.getattr("ARRAY")?.call1((obj, len))?.into();
+            size *= len;
+        }
+        Ok((obj, size))
+    }
+
+    fn record_class(
+        &mut self,
+        memflow: &PyModule,
+        die: &Die,
+        hint: Option<&str>,
+    ) -> crate::Result<(PyObject, usize)> {
+        let py = memflow.py();
+        let size = die.uint(DW_AT_BYTE_SIZE).unwrap_or(0) as usize;
+        let name = die
+            .name()
+            .or(hint)
+            .map(str::to_owned)
+            .unwrap_or_else(|| format!("_anon_0x{:x}", die.offset));
+        let union = die.tag == DW_TAG_UNION_TYPE;
+        // The class is registered before its fields so pointers back to it resolve.
+        let base = match (union, self.big_endian) {
+            (true, false) => "Union",
+            (true, true) => "BigEndianUnion",
+            (false, false) => "Structure",
+            (false, true) => "BigEndianStructure",
+        };
+        let obj = cdecl::new_class(memflow, &name, base, PyDict::new(py))?;
+        self.built.insert(die.offset, (obj.clone_ref(py), size));
+
+        let mut members = Vec::new();
+        for (idx, child) in self.children(die)?.into_iter().enumerate() {
+            self.member(memflow, &name, idx, &child, 0, !union, &mut members)?;
+        }
+        let (fields, offsets) = match union {
+            true => (cdecl::union_members(memflow, members, size)?, Vec::new()),
+            false => cdecl::struct_members(memflow, members, size)?,
+        };
+        obj.setattr(py, "_fields_", PyList::new(py, fields))?;
+        if !offsets.is_empty() {
+            obj.setattr(py, "_offsets_", PyList::new(py, offsets))?;
+        }
+        Ok((obj, size))
+    }
+
+    /// Adds the data member `die` at `base` to `members`, the members of anonymous records are
+    /// also added at their own offsets when `flatten` is set so they can be accessed directly.
+    #[allow(clippy::too_many_arguments)]
+    fn member(
+        &mut self,
+        memflow: &PyModule,
+        outer: &str,
+        idx: usize,
+        die: &Die,
+        base: usize,
+        flatten: bool,
+        members: &mut Vec<Member>,
+    ) -> crate::Result<()> {
+        // Static members are declarations without storage in the record.
+        if die.flag(DW_AT_DECLARATION) || die.flag(DW_AT_EXTERNAL) {
+            return Ok(());
+        }
+        let Some(ty) = self.type_ref(die) else {
+            return Ok(());
+        };
+        let name = match die.tag {
+            DW_TAG_MEMBER => die.name().map(|name| name.replace('.', "_")),
+            DW_TAG_INHERITANCE => self
+                .strip(ty)?
+                .and_then(|base| base.name().map(str::to_owned)),
+            _ => return Ok(()),
+        };
+        let offset = base + self.member_location(die)?;
+        let anon_name = format!("{}__anon_{}", outer, idx);
+        let (obj, size) = self.build_named(memflow, ty, Some(&anon_name))?;
+
+        if let Some(bits) = die.uint(DW_AT_BIT_SIZE) {
+            let Some(name) = name else {
+                return Ok(());
+            };
+            let bits = bits as usize;
+            let (unit_ty, unit_size) = self.bitfield_unit(memflow, ty)?;
+            // Bit offsets are counted in allocation order, from the most significant bit on big endian targets.
+            let bit_offset = match (die.uint(DW_AT_DATA_BIT_OFFSET), die.uint(DW_AT_BIT_OFFSET)) {
+                (Some(data_bit_offset), _) => offset * 8 + data_bit_offset as usize,
+                // Before DWARF 4 the offset counts from the most significant bit of the storage.
+                (None, Some(bit_offset)) if self.big_endian => offset * 8 + bit_offset as usize,
+                (None, Some(bit_offset)) => {
+                    let storage = die.uint(DW_AT_BYTE_SIZE).map_or(unit_size, |s| s as usize);
+                    (offset + storage) * 8 - bit_offset as usize - bits
+                }
+                _ => offset * 8,
+            };
+            let mut unit_offset = bit_offset / (unit_size * 8) * unit_size;
+            if bit_offset + bits > (unit_offset + unit_size) * 8 {
+                unit_offset = bit_offset / 8;
+            }
+            members.push(Member {
+                name,
+                offset: unit_offset,
+                ty: unit_ty,
+                size: unit_size,
+                bits: Some((bits as u32, (bit_offset - unit_offset * 8) as u32)),
+            });
+            return Ok(());
+        }
+
+        let anonymous = name.is_none();
+        members.push(Member {
+            name: name.unwrap_or_else(|| format!("_anon_{}", idx)),
+            offset,
+            ty: obj,
+            size,
+            bits: None,
+        });
+        if !(anonymous && flatten) {
+            return Ok(());
+        }
+        if let Some(record) = self.strip(ty)? {
+            if matches!(
+                record.tag,
+                DW_TAG_STRUCTURE_TYPE | DW_TAG_CLASS_TYPE | DW_TAG_UNION_TYPE
+            ) {
+                for (idx, child) in self.children(&record)?.into_iter().enumerate() {
+                    self.member(memflow, &anon_name, idx, &child, offset, true, members)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the byte offset of a member, given as a constant or a location expression.
+    fn member_location(&self, die: &Die) -> crate::Result<usize> {
+        match die.attr(DW_AT_DATA_MEMBER_LOCATION) {
+            Some(Value::Uint(offset)) => Ok(*offset as usize),
+            Some(Value::Int(offset)) => Ok(*offset as usize),
+            Some(Value::Block(start, end)) => {
+                let mut r = Reader::new(&self.sections.info[..*end], *start, self.big_endian);
+                match r.u8()? {
+                    DW_OP_PLUS_UCONST | DW_OP_CONSTU => Ok(r.uleb()? as usize),
+                    op => Err(invalid(format!(
+                        "unsupported member location operation {:#x}",
+                        op
+                    ))),
+                }
+            }
+            _ => Ok(0),
+        }
+    }
+
+    /// Integer storage unit of a bitfield, enums are stored as their underlying integer.
+    fn bitfield_unit(&mut self, memflow: &PyModule, ty: usize) -> crate::Result<(PyObject, usize)> {
+        let die = self
+            .strip(ty)?
+            .ok_or_else(|| invalid("bitfield of type `void`"))?;
+        let size = die.uint(DW_AT_BYTE_SIZE).unwrap_or(0) as usize;
+        match die.tag {
+            DW_TAG_BASE_TYPE if die.uint(DW_AT_ENCODING) == Some(DW_ATE_BOOLEAN) => {
+                Ok((memflow.getattr("c_bool")?.into(), size))
+            }
+            DW_TAG_BASE_TYPE => Ok((
+                self.int_type(
+                    memflow,
+                    size,
+                    is_signed(die.uint(DW_AT_ENCODING).unwrap_or(0)),
+                )?,
+                size,
+            )),
+            DW_TAG_ENUMERATION_TYPE => {
+                let (_, size, signed) = self.enum_storage(&die)?;
+                Ok((self.int_type(memflow, size, signed)?, size))
+            }
+            _ => Err(invalid(format!(
+                "bitfield of unsupported type at {:#x}",
+                die.offset
+            ))),
+        }
+    }
+
+    /// Returns the enumerators, size and signedness of an enum.
+    #[allow(clippy::type_complexity)]
+    fn enum_storage(&self, die: &Die) -> crate::Result<(Vec<(String, i128)>, usize, bool)> {
+        let underlying = match self.type_ref(die) {
+            Some(ty) => self.strip(ty)?,
+            None => None,
+        };
+        let size = die
+            .uint(DW_AT_BYTE_SIZE)
+            .or_else(|| underlying.as_ref()?.uint(DW_AT_BYTE_SIZE))
+            .unwrap_or(4) as usize;
+        let mut members = Vec::new();
+        let mut negative = false;
+        for child in self.children(die)? {
+            if child.tag != DW_TAG_ENUMERATOR {
+                continue;
+            }
+            let value = match child.attr(DW_AT_CONST_VALUE) {
+                Some(Value::Uint(value)) => *value as i128,
+                Some(Value::Int(value)) => {
+                    negative |= *value < 0;
+                    *value as i128
+                }
+                _ => continue,
+            };
+            members.push((child.name().unwrap_or_default().to_owned(), value));
+        }
+        // Without an underlying type negative enumerators make the enum signed.
+        let signed = match &underlying {
+            Some(underlying) => is_signed(underlying.uint(DW_AT_ENCODING).unwrap_or(0)),
+            None => negative,
+        };
+        // Fixed size constants are stored without their sign.
+        if signed && size < 16 {
+            let bits = size as u32 * 8;
+            for (_, value) in members.iter_mut() {
+                if *value >= 0 && *value >> (bits - 1) & 1 == 1 && *value < 1 << bits {
+                    *value -= 1 << bits;
+                }
+            }
+        }
+        Ok((members, size, signed))
+    }
+
+    fn enum_class(
+        &mut self,
+        memflow: &PyModule,
+        die: &Die,
+        hint: Option<&str>,
+    ) -> crate::Result<(PyObject, usize)> {
+        let py = memflow.py();
+        let (members, size, signed) = self.enum_storage(die)?;
+        let name = die
+            .name()
+            .or(hint)
+            .map(str::to_owned)
+            .unwrap_or_else(|| format!("_anon_0x{:x}", die.offset));
+        let int_enum = py
+            .import("enum")?
+            .getattr("IntEnum")?
+            .call1((name.as_str(), members))?;
+        let int_obj = self.int_type(memflow, size, signed)?;
+        let obj = memflow.getattr("ENUM")?.call1((int_enum, int_obj))?.into();
+        Ok((obj, size))
+    }
+}
+
+fn is_signed(encoding: u64) -> bool {
+    matches!(encoding, DW_ATE_SIGNED | DW_ATE_SIGNED_CHAR)
+}
+
+/// Reads the defined symbols relative to the lowest loaded address, publics of the static
+/// symbol table take precedence over the dynamic one.
+fn read_symbols(elf: &Elf) -> IndexMap<String, u64> {
+    let base = elf
+        .program_headers
+        .iter()
+        .filter(|header| header.p_type == PT_LOAD)
+        .map(|header| header.p_vaddr & !0xfff)
+        .min()
+        .unwrap_or(0);
+    let mut symbols = IndexMap::new();
+    for (syms, strtab) in [(&elf.syms, &elf.strtab), (&elf.dynsyms, &elf.dynstrtab)] {
+        for sym in syms.iter() {
+            if matches!(sym.st_shndx as u32, SHN_UNDEF | SHN_ABS)
+                || !matches!(sym.st_type(), STT_OBJECT | STT_FUNC | STT_NOTYPE)
+            {
+                continue;
+            }
+            match strtab.get_at(sym.st_name) {
+                Some(name) if !name.is_empty() => {
+                    symbols
+                        .entry(name.to_owned())
+                        .or_insert(sym.st_value.wrapping_sub(base));
+                }
+                _ => {}
+            }
+        }
+    }
+    symbols
+}
+
+#[pymethods]
+impl PyDwarf {
+    /// Loads the ELF file at `path` and indexes its DWARF debug info.
+    #[new]
+    fn new(path: &str) -> PyResult<Self> {
+        let data = std::fs::read(path).map_err(|e| invalid(format!("{}: {}", path, e)))?;
+        Ok(Self::parse(&data)?)
+    }
+
+    /// Names of the structures, unions, enums and typedefs defined in the debug info.
+    fn type_names(&self) -> Vec<String> {
+        self.names.keys().cloned().collect()
+    }
+
+    /// Function and data symbols mapped to their offset, add `ModuleInfo.base` for the address.
+    #[getter]
+    fn symbols<'py>(&self, py: Python<'py>) -> PyResult<&'py PyDict> {
+        let symbols = PyDict::new(py);
+        for (name, offset) in &self.symbols {
+            symbols.set_item(name, offset)?;
+        }
+        Ok(symbols)
+    }
+
+    /// Returns the structure, union, enum or typedef named `name`.
+    fn __getitem__(&mut self, py: Python<'_>, name: &str) -> PyResult<PyObject> {
+        let offset = *self
+            .names
+            .get(name)
+            .ok_or_else(|| PyKeyError::new_err(name.to_owned()))?;
+        let memflow = py.import("memflow")?;
+        Ok(self.build(memflow, offset)?.0)
+    }
+
+    /// Allows `types.task_struct` for names that are valid identifiers.
+    fn __getattr__(&mut self, py: Python<'_>, name: &str) -> PyResult<PyObject> {
+        let offset = *self
+            .names
+            .get(name)
+            .ok_or_else(|| PyAttributeError::new_err(name.to_owned()))?;
+        let memflow = py.import("memflow")?;
+        Ok(self.build(memflow, offset)?.0)
+    }
+
+    fn __contains__(&self, name: &str) -> bool {
+        self.names.contains_key(name)
+    }
+
+    fn __len__(&self) -> usize {
+        self.names.len()
+    }
+}
@@ -0,0 +1,319 @@
+// Conversion of the extended precision formats used for `long double`.
+//
+// The x87 80-bit format consists of a 64-bit significand with an explicit integer bit followed by
+// a 15-bit exponent (bias 16383) and the sign bit. IEEE 754 quadruple precision (binary128) shares
+// the exponent but stores a 112-bit fraction with an implicit integer bit.
+
+use memflow::prelude::ArchitectureIdent;
+use pyo3::prelude::*;
+use pyo3::types::PyFloat;
+
+use crate::MemflowPyError;
+
+const EXPONENT_BIAS: i64 = 16383;
+const EXPONENT_MAX: u16 = 0x7FFF;
+
+/// Encoding of `long double` values wider than a double.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    /// x87 extended precision, used by x86 targets.
+    X87,
+    /// IEEE 754 quadruple precision, used by 64-bit Arm Unix targets.
+    Binary128,
+}
+
+impl Default for Format {
+    fn default() -> Self {
+        Self::native()
+    }
+}
+
+impl TryFrom<&str> for Format {
+    type Error = MemflowPyError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "x87" => Ok(Self::X87),
+            "binary128" => Ok(Self::Binary128),
+            _ => Err(MemflowPyError::InvalidLongDoubleFormat(value.to_owned())),
+        }
+    }
+}
+
+impl Format {
+    /// Format of the system running python.
+    pub fn native() -> Self {
+        match cfg!(target_arch = "aarch64") {
+            true => Self::Binary128,
+            false => Self::X87,
+        }
+    }
+
+    pub fn from_arch(arch: ArchitectureIdent) -> Self {
+        match arch {
+            ArchitectureIdent::X86(..) => Self::X87,
+            ArchitectureIdent::AArch64(_) => Self::Binary128,
+            ArchitectureIdent::Unknown(_) => Self::native(),
+        }
+    }
+
+    /// Bytes of a value without padding.
+    pub fn size(&self) -> usize {
+        match self {
+            Self::X87 => 10,
+            Self::Binary128 => 16,
+        }
+    }
+
+    /// Bits of the significand including the integer bit.
+    fn precision(&self) -> i64 {
+        match self {
+            Self::X87 => 64,
+            Self::Binary128 => 113,
+        }
+    }
+
+    /// Shift that turns the significand of a denormal into its value (`2^(1 - bias - (precision - 1))`).
+    fn denormal_shift(&self) -> i64 {
+        EXPONENT_BIAS - 1 + self.precision() - 1
+    }
+}
+
+/// Value unpacked into sign, biased exponent and a significand with the integer bit at `precision - 1`.
+#[derive(Clone, Copy, Debug)]
+pub struct Extended {
+    sign: bool,
+    exponent: u16,
+    significand: u128,
+    format: Format,
+}
+
+impl Extended {
+    fn new(sign: bool, exponent: u16, significand: u128, format: Format) -> Self {
+        Self {
+            sign,
+            exponent,
+            significand,
+            format,
+        }
+    }
+
+    /// Unpacks the little endian encoding, `bytes` has to hold at least `format.size()` bytes.
+    pub fn from_bytes(bytes: &[u8], format: Format) -> Self {
+        match format {
+            Format::X87 => {
+                let significand = u64::from_le_bytes(bytes[..8].try_into().unwrap());
+                let sign_exponent = u16::from_le_bytes([bytes[8], bytes[9]]);
+                Self::new(
+                    sign_exponent & 0x8000 != 0,
+                    sign_exponent & EXPONENT_MAX,
+                    significand as u128,
+                    format,
+                )
+            }
+            Format::Binary128 => {
+                let bits = u128::from_le_bytes(bytes[..16].try_into().unwrap());
+                let exponent = (bits >> 112) as u16 & EXPONENT_MAX;
+                // Only denormals lack the integer bit.
+                let integer = ((exponent != 0) as u128) << 112;
+                Self::new(
+                    bits >> 127 == 1,
+                    exponent,
+                    integer | bits & ((1 << 112) - 1),
+                    format,
+                )
+            }
+        }
+    }
+
+    /// Packs the value into its little endian encoding of `format.size()` bytes.
+    pub fn to_bytes(self) -> Vec<u8> {
+        match self.format {
+            Format::X87 => {
+                let mut bytes = (self.significand as u64).to_le_bytes().to_vec();
+                let sign_exponent = self.exponent | if self.sign { 0x8000 } else { 0 };
+                bytes.extend(sign_exponent.to_le_bytes());
+                bytes
+            }
+            Format::Binary128 => {
+                let bits = (self.sign as u128) << 127
+                    | (self.exponent as u128) << 112
+                    | self.significand & ((1 << 112) - 1);
+                bits.to_le_bytes().to_vec()
+            }
+        }
+    }
+
+    /// Returns the exponent of the integer bit for finite values.
+    fn unbiased_exponent(&self) -> i64 {
+        match self.exponent {
+            // Denormals share the exponent of the smallest normal.
+            0 => 1 - EXPONENT_BIAS,
+            exponent => exponent as i64 - EXPONENT_BIAS,
+        }
+    }
+
+    /// Returns the power of two the significand is scaled by.
+    fn scale(&self) -> i64 {
+        self.unbiased_exponent() - (self.format.precision() - 1)
+    }
+
+    fn is_infinite(&self) -> bool {
+        self.exponent == EXPONENT_MAX
+            && self.significand & ((1 << (self.format.precision() - 1)) - 1) == 0
+    }
+}
+
+/// Multiplies `value` by `2^exp` without overflowing the intermediate power of two.
+fn ldexp(mut value: f64, mut exp: i64) -> f64 {
+    while exp > 1000 {
+        value *= 2_f64.powi(1000);
+        exp -= 1000;
+    }
+    while exp < -1000 {
+        value *= 2_f64.powi(-1000);
+        exp += 1000;
+    }
+    value * 2_f64.powi(exp as i32)
+}
+
+/// Converts to the nearest double, values outside of the double range become zero or infinity.
+pub fn to_f64(value: &Extended) -> f64 {
+    let magnitude = match value.exponent {
+        EXPONENT_MAX if value.is_infinite() => f64::INFINITY,
+        EXPONENT_MAX => f64::NAN,
+        _ => ldexp(value.significand as f64, value.scale()),
+    };
+    if value.sign {
+        -magnitude
+    } else {
+        magnitude
+    }
+}
+
+/// Converts a double, every double is exactly representable.
+pub fn from_f64(value: f64, format: Format) -> Extended {
+    let bits = value.to_bits();
+    let sign = bits >> 63 == 1;
+    let exponent = ((bits >> 52) & 0x7FF) as i64;
+    let fraction = (bits & ((1 << 52) - 1)) as u128;
+    // Moves the leading bit of a double significand to the integer bit.
+    let widen = format.precision() - 53;
+    let integer = 1_u128 << (format.precision() - 1);
+    match exponent {
+        0x7FF if fraction == 0 => Extended::new(sign, EXPONENT_MAX, integer, format),
+        // Keep the payload of NaNs and mark them as quiet.
+        0x7FF => Extended::new(
+            sign,
+            EXPONENT_MAX,
+            integer | integer >> 1 | fraction << widen,
+            format,
+        ),
+        0 if fraction == 0 => Extended::new(sign, 0, 0, format),
+        0 => {
+            // Normalize double denormals, they are normals in the wider exponent range.
+            let shift = fraction.leading_zeros() as i64 - (128 - 53);
+            let exp = -1022 - shift + EXPONENT_BIAS;
+            Extended::new(sign, exp as u16, fraction << (shift + widen), format)
+        }
+        _ => {
+            let exp = exponent - 1023 + EXPONENT_BIAS;
+            Extended::new(
+                sign,
+                exp as u16,
+                (integer >> widen | fraction) << widen,
+                format,
+            )
+        }
+    }
+}
+
+/// Converts losslessly into a `fractions.Fraction`, infinities and NaNs are returned as `float`.
+pub fn to_fraction(py: Python<'_>, value: &Extended) -> PyResult<PyObject> {
+    if value.exponent == EXPONENT_MAX {
+        return Ok(to_f64(value).to_object(py));
+    }
+    let scale = value.scale();
+    let one = 1_u8.to_object(py);
+    let numerator =
+        value
+            .significand
+            .to_object(py)
+            .call_method1(py, "__lshift__", (scale.max(0),))?;
+    let numerator = match value.sign {
+        true => numerator.call_method0(py, "__neg__")?,
+        false => numerator,
+    };
+    let denominator = one.call_method1(py, "__lshift__", ((-scale).max(0),))?;
+    let fraction = py.import("fractions")?.getattr("Fraction")?;
+    Ok(fraction.call1((numerator, denominator))?.into())
+}
+
+/// Converts a python number, rationals (`int`, `Fraction`, `Decimal`) are rounded to nearest even from their exact value.
+pub fn from_py(py: Python<'_>, obj: &PyObject, format: Format) -> PyResult<Extended> {
+    if obj.as_ref(py).is_instance_of::<PyFloat>() {
+        return Ok(from_f64(obj.extract(py)?, format));
+    }
+    match obj.call_method0(py, "as_integer_ratio") {
+        Ok(ratio) => {
+            let (numerator, denominator): (&PyAny, &PyAny) = ratio.extract(py)?;
+            from_ratio(numerator, denominator, format)
+        }
+        // Infinite decimals and other types only convertible to float.
+        Err(_) => Ok(from_f64(obj.extract(py)?, format)),
+    }
+}
+
+/// Rounds `numerator / denominator` (with a positive denominator) to the nearest value.
+fn from_ratio(numerator: &PyAny, denominator: &PyAny, format: Format) -> PyResult<Extended> {
+    let bit_length = |v: &PyAny| -> PyResult<i64> { v.call_method0("bit_length")?.extract() };
+    let sign = numerator.lt(0)?;
+    let numerator = numerator.call_method0("__abs__")?;
+    if !numerator.is_true()? {
+        return Ok(Extended::new(sign, 0, 0, format));
+    }
+
+    // Computes `round(numerator * 2^shift / denominator)` rounding half to even.
+    let scaled = |shift: i64| -> PyResult<u128> {
+        let (num, den) = match shift >= 0 {
+            true => (numerator.call_method1("__lshift__", (shift,))?, denominator),
+            false => (
+                numerator,
+                denominator.call_method1("__lshift__", (-shift,))?,
+            ),
+        };
+        let (quotient, remainder): (&PyAny, &PyAny) =
+            num.call_method1("__divmod__", (den,))?.extract()?;
+        // The quotient is at most one bit wider than the significand for the shifts used below.
+        let quotient: u128 = quotient.extract()?;
+        let half = remainder.call_method1("__lshift__", (1,))?.compare(den)?;
+        let round_up = half.is_gt() || (half.is_eq() && quotient & 1 == 1);
+        Ok(quotient + round_up as u128)
+    };
+
+    // Scale the value so that its integer bit ends up as the top bit of the significand.
+    let top = format.precision() - 1;
+    let mut shift = top - (bit_length(numerator)? - bit_length(denominator)?);
+    let mut significand = scaled(shift)?;
+    if significand < 1 << top {
+        shift += 1;
+        significand = scaled(shift)?;
+    }
+    if significand >= 1 << (top + 1) {
+        // Rounding carried into a new bit.
+        shift -= 1;
+        significand >>= 1;
+    }
+
+    let exponent = top - shift + EXPONENT_BIAS;
+    if exponent >= EXPONENT_MAX as i64 {
+        Ok(Extended::new(sign, EXPONENT_MAX, 1 << top, format))
+    } else if exponent <= 0 {
+        // Denormals have a fixed scale, rounding may still promote them to the smallest normal.
+        let significand = scaled(format.denormal_shift())?;
+        let exponent = (significand >> top) as u16;
+        Ok(Extended::new(sign, exponent, significand, format))
+    } else {
+        Ok(Extended::new(sign, exponent as u16, significand, format))
+    }
+}
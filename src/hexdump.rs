@@ -0,0 +1,159 @@
+use memflow::prelude::v1::*;
+use pyo3::{exceptions::PyException, prelude::*};
+
+use crate::{
+    internal::{InternalDT, MemorySource},
+    layout::{bit_range, sorted_fields},
+};
+
+/// Granularity memory is mapped at, bytes of a page that fails to read are all unknown.
+const PAGE_SIZE: umem = 0x1000;
+
+const LINE_WIDTH: usize = 16;
+
+/// Reads `len` bytes at `addr` page by page, bytes of pages that can not be read are `None`.
+/// Always returns `len` bytes, the last byte of the address space and anything past it can not be read either.
+fn read_bytes(mem: &mut (impl MemorySource + ?Sized), addr: umem, len: usize) -> Vec<Option<u8>> {
+    let mut out = Vec::with_capacity(len);
+    while out.len() < len {
+        // Reads have to end below the top of the address space, memory views add the length to the address.
+        let start = addr.checked_add(out.len() as umem).unwrap_or(umem::MAX);
+        let room = (umem::MAX - start).min(PAGE_SIZE - start % PAGE_SIZE) as usize;
+        if room == 0 {
+            out.resize(len, None);
+            break;
+        }
+        let mut buf = vec![0; room.min(len - out.len())];
+        match mem.read_raw_into(start.into(), &mut buf) {
+            Ok(()) => out.extend(buf.into_iter().map(Some)),
+            Err(_) => out.resize(out.len() + buf.len(), None),
+        }
+    }
+    out
+}
+
+/// Collects the byte ranges of the fields of `dt`, members of nested records are named by their path.
+fn field_ranges(dt: &InternalDT, base: usize, prefix: &str, out: &mut Vec<(usize, usize, String)>) {
+    for (name, offset, field_dt) in sorted_fields(dt) {
+        let name = format!("{}{}", prefix, name);
+        match field_dt {
+            InternalDT::Structure(..) | InternalDT::Union(..) => {
+                field_ranges(field_dt, base + offset, &format!("{}.", name), out)
+            }
+            _ => {
+                // Bitfields only cover the bytes their bits are in.
+                let (start, end) = bit_range(base + offset, field_dt);
+                out.push((start / 8, end.div_ceil(8), name));
+            }
+        }
+    }
+}
+
+/// Splits `len` bytes into runs covered by the same fields, labelled with their names or `padding`.
+fn overlay_runs(dt: &InternalDT, len: usize) -> Vec<(usize, usize, String)> {
+    let mut fields = Vec::new();
+    field_ranges(dt, 0, "", &mut fields);
+    let mut bounds = vec![0, len];
+    for (start, end, _) in &fields {
+        bounds.extend([(*start).min(len), (*end).min(len)]);
+    }
+    bounds.sort_unstable();
+    bounds.dedup();
+    bounds
+        .windows(2)
+        .map(|bound| {
+            let names = fields
+                .iter()
+                .filter(|(start, end, _)| *start < bound[1] && bound[0] < *end)
+                .map(|(_, _, name)| name.as_str())
+                .collect::<Vec<_>>();
+            // Bytes past the end of the record are left unlabelled.
+            let label = match names.is_empty() {
+                true if bound[0] < dt.size() => "padding".to_owned(),
+                true => String::new(),
+                false => names.join(", "),
+            };
+            (bound[0], bound[1], label)
+        })
+        .collect()
+}
+
+fn line(addr: umem, bytes: &[Option<u8>], label: Option<&str>) -> String {
+    let mut hex = String::new();
+    let mut ascii = String::new();
+    for idx in 0..LINE_WIDTH {
+        if idx == LINE_WIDTH / 2 {
+            hex.push(' ');
+        }
+        match bytes.get(idx) {
+            Some(Some(byte)) => {
+                hex += &format!("{:02x} ", byte);
+                ascii.push(match byte {
+                    0x20..=0x7e => *byte as char,
+                    _ => '.',
+                });
+            }
+            Some(None) => {
+                hex += "?? ";
+                ascii.push('?');
+            }
+            None => hex += "   ",
+        }
+    }
+    match label {
+        Some(label) => format!(
+            "{:016x}  {} |{:<width$}|  {}",
+            addr,
+            hex,
+            ascii,
+            label,
+            width = LINE_WIDTH
+        ),
+        None => format!("{:016x}  {} |{}|", addr, hex, ascii),
+    }
+}
+
+/// Returns the length to dump, the size of the overlaid record unless given.
+pub fn length(len: Option<usize>, overlay: Option<&InternalDT>) -> PyResult<usize> {
+    match (len, overlay) {
+        (Some(len), _) => Ok(len),
+        (None, Some(dt)) => Ok(dt.size()),
+        (None, None) => Err(PyException::new_err(
+            "hexdump needs a length or a type to overlay",
+        )),
+    }
+}
+
+/// Formats `len` bytes at `addr` as offset, hex and ASCII columns, bytes that can not be read show as `??`.
+/// With `overlay` lines are split at the fields of the record and labelled with their names.
+pub fn hexdump(
+    mem: &mut (impl MemorySource + ?Sized),
+    addr: umem,
+    len: usize,
+    overlay: Option<&InternalDT>,
+) -> String {
+    let bytes = read_bytes(mem, addr, len);
+    let runs = match overlay {
+        Some(dt) => overlay_runs(dt, len),
+        None => vec![(0, len, String::new())],
+    };
+    let mut lines = Vec::new();
+    for (start, end, label) in runs {
+        let run = bytes.get(start..end).unwrap_or_default();
+        for (idx, chunk) in run.chunks(LINE_WIDTH).enumerate() {
+            // Runs longer than a line are only labelled once.
+            let label = match (overlay, idx) {
+                (Some(_), 0) => Some(label.as_str()),
+                (Some(_), _) => Some(""),
+                (None, _) => None,
+            };
+            let offset = (start + idx * LINE_WIDTH) as umem;
+            lines.push(
+                line(addr.wrapping_add(offset), chunk, label)
+                    .trim_end()
+                    .to_owned(),
+            );
+        }
+    }
+    lines.join("\n")
+}
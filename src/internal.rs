@@ -2,14 +2,310 @@ use std::ffi::*;
 use std::mem::size_of;
 
 use indexmap::IndexMap;
+use memflow::prelude::{ArchitectureIdent, MemoryView, PartialResult, ReadData};
 use memflow::types::umem;
-use pyo3::prelude::*;
-use pyo3::types::{PyDict, PyTuple};
+use memflow::types::Address;
+use pyo3::types::{PyBytes, PyDict, PyList, PyTuple};
+use pyo3::{exceptions::PyException, prelude::*};
 
-use crate::MemflowPyError;
+use crate::float80::{self, Extended};
+use crate::pointer::{PointerSource, PyPointer};
+use crate::{strings, MemflowPyError};
 
 pub type DTMap = IndexMap<String, (usize, InternalDT)>;
 
+/// Object safe subset of `MemoryView` used to follow pointers while decoding.
+pub trait MemorySource {
+    fn read_raw_into(&mut self, addr: Address, out: &mut [u8]) -> PartialResult<()>;
+
+    /// Reads several regions at once, letting the memory batch them.
+    fn read_raw_list(&mut self, data: &mut [ReadData]) -> PartialResult<()>;
+
+    /// Object the memory is read through, decoded pointers are bound to it.
+    fn source(&self) -> Option<&PointerSource> {
+        None
+    }
+}
+
+impl<T: MemoryView> MemorySource for T {
+    fn read_raw_into(&mut self, addr: Address, out: &mut [u8]) -> PartialResult<()> {
+        MemoryView::read_raw_into(self, addr, out)
+    }
+
+    fn read_raw_list(&mut self, data: &mut [ReadData]) -> PartialResult<()> {
+        MemoryView::read_raw_list(self, data)
+    }
+}
+
+/// Memory that remembers the object it is read through, so decoded pointers can be followed later on.
+pub struct BoundMemory<'a, M: ?Sized> {
+    mem: &'a mut M,
+    source: PointerSource,
+}
+
+impl<'a, M: MemorySource + ?Sized> BoundMemory<'a, M> {
+    pub fn new(mem: &'a mut M, source: PointerSource) -> Self {
+        Self { mem, source }
+    }
+}
+
+impl<M: MemorySource + ?Sized> MemorySource for BoundMemory<'_, M> {
+    fn read_raw_into(&mut self, addr: Address, out: &mut [u8]) -> PartialResult<()> {
+        self.mem.read_raw_into(addr, out)
+    }
+
+    fn read_raw_list(&mut self, data: &mut [ReadData]) -> PartialResult<()> {
+        self.mem.read_raw_list(data)
+    }
+
+    fn source(&self) -> Option<&PointerSource> {
+        Some(&self.source)
+    }
+}
+
+/// Python base classes a datatype can be derived from.
+const BASE_NAMES: &[&str] = &[
+    "CDataType",
+    "_SimpleCData",
+    "Pointer",
+    "Array",
+    "CharArray",
+    "WideCharArray",
+    "Structure",
+    "Union",
+];
+
+/// Byte order of a datatype in target memory.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Endianness {
+    #[default]
+    Little,
+    Big,
+}
+
+impl Endianness {
+    /// Reads the byte order of a python type from its `_endianness_` attribute.
+    ///
+    /// Types using the ctypes `BigEndianStructure` are marked with `_swappedbytes_` instead.
+    fn from_type(value: &PyObject) -> crate::Result<Option<Self>> {
+        Python::with_gil(|py| {
+            if let Ok(endianness) = value.getattr(py, "_endianness_") {
+                match endianness.extract::<String>(py)?.as_str() {
+                    "little" => Ok(Some(Self::Little)),
+                    "big" => Ok(Some(Self::Big)),
+                    other => Err(MemflowPyError::InvalidEndianness(other.to_owned())),
+                }
+            } else if value.getattr(py, "_swappedbytes_").is_ok() {
+                Ok(Some(Self::Big))
+            } else {
+                Ok(None)
+            }
+        })
+    }
+}
+
+/// C data model of the introspection target, decides the width of `long`, `wchar_t` and pointers.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DataModel {
+    /// Uses the widths of the system running python.
+    #[default]
+    Native,
+    /// 32-bit Unix targets, `int`, `long` and pointers are all 4 bytes.
+    ILP32,
+    /// 32-bit Windows targets, like `ILP32` but `long double` is a double.
+    Win32,
+    /// 64-bit Windows targets, `long` is 4 bytes while pointers are 8 bytes.
+    LLP64,
+    /// 64-bit Unix targets, `long` and pointers are 8 bytes.
+    LP64,
+}
+
+impl DataModel {
+    /// Derives the data model from the architecture of the target, Windows and Unix targets differ in the width of `long`
+    /// and `long double`.
+    pub fn from_arch(arch: ArchitectureIdent, windows: bool) -> Self {
+        match arch {
+            ArchitectureIdent::X86(32, _) if windows => Self::Win32,
+            ArchitectureIdent::X86(32, _) => Self::ILP32,
+            ArchitectureIdent::X86(_, _) | ArchitectureIdent::AArch64(_) if windows => Self::LLP64,
+            ArchitectureIdent::X86(_, _) | ArchitectureIdent::AArch64(_) => Self::LP64,
+            ArchitectureIdent::Unknown(_) => Self::Native,
+        }
+    }
+
+    pub fn long_size(&self) -> usize {
+        match self {
+            Self::Native => size_of::<c_long>(),
+            Self::ILP32 | Self::Win32 | Self::LLP64 => 4,
+            Self::LP64 => 8,
+        }
+    }
+
+    /// `wchar_t` is 4 bytes on Unix targets while Windows uses 2 byte UTF-16 code units.
+    pub fn wchar_size(&self) -> usize {
+        match self {
+            Self::Native => size_of::<libc::wchar_t>(),
+            Self::Win32 | Self::LLP64 => 2,
+            Self::ILP32 | Self::LP64 => 4,
+        }
+    }
+
+    /// Storage size of `long double`, MSVC treats it as a double while Unix targets pad the extended precision format.
+    pub fn long_double_size(&self) -> usize {
+        match self {
+            Self::Native
+                if cfg!(any(
+                    windows,
+                    all(target_vendor = "apple", target_arch = "aarch64")
+                )) =>
+            {
+                8
+            }
+            Self::Native if cfg!(target_arch = "x86") => 12,
+            Self::Native => 16,
+            Self::ILP32 => 12,
+            Self::Win32 | Self::LLP64 => 8,
+            Self::LP64 => 16,
+        }
+    }
+
+    /// MSVC targets only share a bitfield storage unit between bitfields of the same size.
+    pub fn is_msvc(&self) -> bool {
+        match self {
+            Self::Native => cfg!(windows),
+            Self::Win32 | Self::LLP64 => true,
+            Self::ILP32 | Self::LP64 => false,
+        }
+    }
+
+    /// Alignment of a scalar of `size` bytes inside a record, the i386 System V ABI caps it at 4 bytes.
+    pub fn scalar_align(&self, size: usize) -> usize {
+        let max_align = match self {
+            Self::Native if cfg!(all(target_arch = "x86", not(windows))) => 4,
+            Self::ILP32 => 4,
+            _ => 16,
+        };
+        // Padded extended precision is not always a power of two, i.e. 12 bytes on x86.
+        (1 << size.max(1).trailing_zeros()).min(max_align)
+    }
+
+    pub fn pointer_size(&self) -> usize {
+        match self {
+            Self::Native => size_of::<usize>(),
+            Self::ILP32 | Self::Win32 => 4,
+            Self::LLP64 | Self::LP64 => 8,
+        }
+    }
+}
+
+impl TryFrom<&str> for DataModel {
+    type Error = MemflowPyError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "Native" => Ok(Self::Native),
+            "ILP32" => Ok(Self::ILP32),
+            "Win32" => Ok(Self::Win32),
+            "LLP64" => Ok(Self::LLP64),
+            "LP64" => Ok(Self::LP64),
+            _ => Err(MemflowPyError::InvalidDataModel(value.to_owned())),
+        }
+    }
+}
+
+impl std::fmt::Display for DataModel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Native => f.pad("Native"),
+            Self::ILP32 => f.pad("ILP32"),
+            Self::Win32 => f.pad("Win32"),
+            Self::LLP64 => f.pad("LLP64"),
+            Self::LP64 => f.pad("LP64"),
+        }
+    }
+}
+
+/// Options applied when converting a python type into a datatype.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DTOptions {
+    /// Byte order of primitives that do not specify their own, inherited from the enclosing structure.
+    pub endianness: Endianness,
+    /// Data model of the target, decides the width of target dependent primitives.
+    pub data_model: DataModel,
+    /// Encoding of `long double` values wider than a double, decided by the architecture of the target.
+    pub extended: float80::Format,
+}
+
+impl From<DataModel> for DTOptions {
+    /// Named data models do not tell the architecture, their extended precision values are assumed to be x87.
+    fn from(data_model: DataModel) -> Self {
+        Self {
+            data_model,
+            extended: match data_model {
+                DataModel::Native => float80::Format::native(),
+                _ => float80::Format::X87,
+            },
+            ..Default::default()
+        }
+    }
+}
+
+/// Run of bitfields sharing storage units, `_fields_` and parsed C declarations are laid out with the same rules.
+///
+/// Bitfields follow the ctypes rules, a bitfield goes into the naturally aligned storage unit of its type that still
+/// has room. MSVC additionally starts a new run whenever the type size changes.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct BitfieldRun {
+    /// Open run as (start offset, used bits, size of the last unit).
+    open: Option<(usize, u32, usize)>,
+}
+
+impl BitfieldRun {
+    /// Returns whether a bitfield with a storage unit of `unit_size` bytes starts a new run.
+    pub fn starts_new(&self, unit_size: usize, data_model: DataModel) -> bool {
+        match self.open {
+            Some((_, _, size)) => size != unit_size && data_model.is_msvc(),
+            None => true,
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open.is_some()
+    }
+
+    /// Ends the run, the next bitfield starts a new one.
+    pub fn close(&mut self) {
+        self.open = None;
+    }
+
+    /// Places a bitfield and returns the offset of its storage unit and its bit offset in there.
+    ///
+    /// A new run starts at `offset`.
+    pub fn place(
+        &mut self,
+        offset: usize,
+        unit_size: usize,
+        bit_width: u32,
+        data_model: DataModel,
+    ) -> (usize, u32) {
+        let (run_offset, used) = match self.open {
+            Some((run_offset, used, _)) if !self.starts_new(unit_size, data_model) => {
+                (run_offset, used)
+            }
+            _ => (offset, 0),
+        };
+        let unit_bits = unit_size as u32 * 8;
+        let mut unit_start = used / unit_bits * unit_bits;
+        let mut bit_pos = used;
+        if bit_pos + bit_width > unit_start + unit_bits {
+            unit_start += unit_bits;
+            bit_pos = unit_start;
+        }
+        self.open = Some((run_offset, bit_pos + bit_width, unit_size));
+        (run_offset + unit_start as usize / 8, bit_pos - unit_start)
+    }
+}
+
 /// Please stick to explicit widths, no c_int nonsense!
 #[derive(Clone, Debug)]
 pub enum InternalDT {
@@ -17,15 +313,21 @@ pub enum InternalDT {
     Byte,
     /// Represents the C unsigned char datatype, it interprets the value as small integer.
     UByte,
+    /// Represents the C _Bool datatype, and interprets the value as a bool.
+    Bool,
     /// Represents the C char datatype, and interprets the value as a single character.
     Char,
     /// Represents the C wchar_t datatype, and interprets the value as a single character unicode string.
-    WideChar,
+    /// Holds the width of the datatype in bytes as decided by the data model.
+    WideChar(usize),
     /// Represents the C double datatype.
     Double,
     /// Represents the C long double datatype. On platforms where sizeof(long double) == sizeof(double) it is an alias to c_double.
     /// For more info see: https://github.com/rust-lang/rust-bindgen/issues/1549
-    LongDouble,
+    ///
+    /// Holds the storage size in bytes, anything wider than a double is the extended precision format padded to the size.
+    /// The flag decodes values losslessly into a `fractions.Fraction` instead of a `float`.
+    LongDouble(usize, float80::Format, bool),
     /// Represents the C float datatype.
     Float,
     /// Represents the C signed short datatype. no overflow checking is done.
@@ -36,38 +338,74 @@ pub enum InternalDT {
     Int,
     /// Represents the C unsigned int datatype. no overflow checking is done. On platforms where sizeof(int) == sizeof(long) it is an alias for c_ulong.
     UInt,
-    /// Represents the C signed long datatype.
-    Long,
-    /// Represents the C unsigned long datatype.
-    ULong,
+    /// Represents the C signed long datatype, holds the width in bytes as decided by the data model.
+    Long(usize),
+    /// Represents the C unsigned long datatype, holds the width in bytes as decided by the data model.
+    ULong(usize),
     /// Represents the C signed long long datatype.
     LongLong,
     /// Represents the C unsigned long long datatype.
     ULongLong,
     /// Native pointer type, backed by `MF_Pointer`.
     Pointer(PyObject, usize),
+    /// Pointer to a NUL terminated narrow string, backed by `c_char_p`.
+    /// Holds the pointer width followed by the max length in bytes when the string is read eagerly.
+    CharPtr(PyObject, usize, Option<usize>),
+    /// Pointer to a NUL terminated wide string, backed by `c_wchar_p`.
+    /// Holds the pointer width, the width of `wchar_t` and the max length in bytes when the string is read eagerly.
+    WideCharPtr(PyObject, usize, usize, Option<usize>),
     // Backed by the ctypes (ctype * size) syntax.
     Array(PyObject, Box<InternalDT>, u32),
+    /// Flexible array member ending a structure, its length is read from the named field of the same structure.
+    DynArray(PyObject, Box<InternalDT>, String),
     /// Any python class with a ctypes _fields_ attribute.
     Structure(PyObject, DTMap),
+    /// Any python class with a ctypes _fields_ attribute where every member starts at offset zero.
+    Union(PyObject, DTMap),
+    /// Primitive datatype stored in big endian byte order.
+    BigEndian(Box<InternalDT>),
+    /// Integer stored as the inner type and decoded into members of the python enum (i.e. `enum.IntEnum`).
+    Enum(PyObject, Box<InternalDT>),
+    /// Integer field packed into a storage unit of the inner type, backed by the ctypes `(name, ctype, bits)` syntax.
+    /// Holds the bit offset from the least significant bit of the storage unit followed by the bit width.
+    Bitfield(Box<InternalDT>, u32, u32),
 }
 
 impl InternalDT {
-    pub fn py_from_bytes(&self, bytes: Vec<u8>) -> crate::Result<PyObject> {
+    /// Decodes the bytes, `mem` is the memory they were read from and is used to follow string pointers eagerly.
+    pub fn py_from_bytes(
+        &self,
+        bytes: Vec<u8>,
+        mut mem: Option<&mut dyn MemorySource>,
+    ) -> crate::Result<PyObject> {
         Python::with_gil(|py| match self {
             InternalDT::Byte => Ok(i8::from_le_bytes(bytes[..].try_into()?).to_object(py)),
             InternalDT::UByte => Ok(u8::from_le_bytes(bytes[..].try_into()?).to_object(py)),
-            InternalDT::Char => Ok(c_char::from_le_bytes(bytes[..].try_into()?).to_object(py)),
-            InternalDT::WideChar => Ok(u16::from_le_bytes(bytes[..].try_into()?).to_object(py)),
+            InternalDT::Bool => Ok((u8::from_le_bytes(bytes[..].try_into()?) != 0).to_object(py)),
+            InternalDT::Char => Ok(PyBytes::new(py, &bytes).to_object(py)),
+            InternalDT::WideChar(size) => {
+                Ok(strings::decode_wide_chars(&bytes, *size, false)?.to_object(py))
+            }
             InternalDT::Double => Ok(c_double::from_le_bytes(bytes[..].try_into()?).to_object(py)),
-            InternalDT::LongDouble => todo!(),
+            InternalDT::LongDouble(size, format, exact) => {
+                let value = match size {
+                    8 => float80::from_f64(f64::from_le_bytes(bytes[..].try_into()?), *format),
+                    _ => Extended::from_bytes(&bytes, *format),
+                };
+                match exact {
+                    true => Ok(float80::to_fraction(py, &value)?),
+                    false => Ok(float80::to_f64(&value).to_object(py)),
+                }
+            }
             InternalDT::Float => Ok(c_float::from_le_bytes(bytes[..].try_into()?).to_object(py)),
             InternalDT::Short => Ok(c_short::from_le_bytes(bytes[..].try_into()?).to_object(py)),
             InternalDT::UShort => Ok(c_ushort::from_le_bytes(bytes[..].try_into()?).to_object(py)),
             InternalDT::Int => Ok(c_int::from_le_bytes(bytes[..].try_into()?).to_object(py)),
             InternalDT::UInt => Ok(c_uint::from_le_bytes(bytes[..].try_into()?).to_object(py)),
-            InternalDT::Long => Ok(c_long::from_le_bytes(bytes[..].try_into()?).to_object(py)),
-            InternalDT::ULong => Ok(c_ulong::from_le_bytes(bytes[..].try_into()?).to_object(py)),
+            InternalDT::Long(4) => Ok(i32::from_le_bytes(bytes[..].try_into()?).to_object(py)),
+            InternalDT::Long(_) => Ok(i64::from_le_bytes(bytes[..].try_into()?).to_object(py)),
+            InternalDT::ULong(4) => Ok(u32::from_le_bytes(bytes[..].try_into()?).to_object(py)),
+            InternalDT::ULong(_) => Ok(u64::from_le_bytes(bytes[..].try_into()?).to_object(py)),
             InternalDT::LongLong => {
                 Ok(c_longlong::from_le_bytes(bytes[..].try_into()?).to_object(py))
             }
@@ -75,25 +413,88 @@ impl InternalDT {
                 Ok(c_ulonglong::from_le_bytes(bytes[..].try_into()?).to_object(py))
             }
             InternalDT::Pointer(class, _) => {
-                Ok(class.call1(py, (umem::from_le_bytes(bytes[..self.size()].try_into()?),))?)
-            }
-            InternalDT::Array(class, dt, _) => Ok(class.call1(
-                py,
-                PyTuple::new(
-                    py,
-                    bytes
-                        .chunks(dt.size())
-                        .into_iter()
-                        .map(|w| dt.py_from_bytes(w.to_vec()).unwrap()),
-                ),
-            )?),
-            InternalDT::Structure(class, dts) => {
+                let ptr = class.call1(py, (addr_from_bytes(&bytes),))?;
+                if let (Some(source), Ok(ptr)) = (
+                    mem.as_ref().and_then(|mem| mem.source()),
+                    ptr.downcast::<PyCell<PyPointer>>(py),
+                ) {
+                    ptr.borrow_mut().bind(source.clone());
+                }
+                Ok(ptr)
+            }
+            InternalDT::CharPtr(class, _, max_bytes)
+            | InternalDT::WideCharPtr(class, _, _, max_bytes) => {
+                let addr = addr_from_bytes(&bytes);
+                match (mem, max_bytes) {
+                    (_, Some(_)) if addr == 0 => Ok(py.None()),
+                    (Some(mem), Some(max_bytes)) => {
+                        match self {
+                            InternalDT::CharPtr(_, _, _) => Ok(strings::read_char_string(
+                                mem,
+                                addr.into(),
+                                *max_bytes,
+                                None,
+                                None,
+                            )?
+                            .to_object(py)),
+                            InternalDT::WideCharPtr(_, _, width, _) => Ok(
+                                strings::read_wide_chars(mem, addr.into(), *max_bytes, *width)?
+                                    .to_object(py),
+                            ),
+                            _ => unreachable!(),
+                        }
+                    }
+                    // Without the memory the string can only be followed on demand.
+                    _ => Ok(class.call1(py, (addr,))?),
+                }
+            }
+            // Character arrays are handed over as a whole like ctypes does, the class trims them.
+            InternalDT::Array(class, dt, _) if dt.char_width().is_some() => {
+                if let Ok(from_buffer_copy) = class.getattr(py, "from_buffer_copy") {
+                    // ctypes arrays keep the raw buffer themselves.
+                    return Ok(from_buffer_copy.call1(py, (PyBytes::new(py, &bytes),))?);
+                }
+                match dt.char_width() {
+                    Some(1) => Ok(class.call1(py, (PyBytes::new(py, &bytes),))?),
+                    _ => {
+                        let text =
+                            strings::decode_wide_chars(&bytes, dt.size(), dt.is_big_endian())?;
+                        Ok(class.call1(py, (text,))?)
+                    }
+                }
+            }
+            InternalDT::Array(class, dt, _) => {
+                let items = bytes
+                    .chunks(dt.size())
+                    .map(|w| dt.py_from_bytes(w.to_vec(), reborrow(&mut mem)))
+                    .collect::<crate::Result<Vec<_>>>()?;
+                Ok(class.call1(py, PyTuple::new(py, items))?)
+            }
+            InternalDT::Structure(class, dts) | InternalDT::Union(class, dts) => {
                 let dict = PyDict::new(py);
                 dts.into_iter()
                     .try_for_each::<_, crate::Result<()>>(|(name, (offset, dt))| {
                         let start = *offset;
+                        // The length of a dynamic array has been decoded already from a preceding field.
+                        let sized_dt;
+                        let dt = match dt {
+                            InternalDT::DynArray(_, _, length_field) => {
+                                sized_dt = dt.with_length(
+                                    dict.get_item(length_field)?
+                                        .ok_or_else(|| {
+                                            MemflowPyError::MissingAttribute(length_field.clone())
+                                        })?
+                                        .extract()?,
+                                );
+                                &sized_dt
+                            }
+                            _ => dt,
+                        };
                         let size = dt.size();
-                        let val = dt.py_from_bytes(bytes[start..(start + size)].to_vec())?;
+                        let field_bytes = bytes
+                            .get(start..(start + size))
+                            .ok_or_else(|| MemflowPyError::InvalidDynamicArray(name.clone()))?;
+                        let val = dt.py_from_bytes(field_bytes.to_vec(), reborrow(&mut mem))?;
                         dict.set_item(name.as_str(), val)?;
                         Ok(())
                     })?;
@@ -101,6 +502,27 @@ impl InternalDT {
                 let class_inst = class.call(py, (), Some(dict))?;
                 Ok(class_inst)
             }
+            InternalDT::BigEndian(dt) => {
+                Ok(dt.py_from_bytes(bytes.into_iter().rev().collect(), mem)?)
+            }
+            // Only structures know the length.
+            InternalDT::DynArray(class, _, _) => Err(MemflowPyError::InvalidDynamicArray(
+                class.getattr(py, "__name__")?.to_string(),
+            )),
+            InternalDT::Enum(enum_obj, dt) => {
+                let value = dt.py_from_bytes(bytes, mem)?;
+                // Values without a member are kept as int.
+                Ok(enum_obj.call1(py, (value.clone_ref(py),)).unwrap_or(value))
+            }
+            InternalDT::Bitfield(dt, bit_offset, bit_width) => {
+                let raw = (dt.unit_from_bytes(&bytes) >> bit_offset) & bitfield_mask(*bit_width);
+                if dt.is_signed() && raw >> (bit_width - 1) & 1 == 1 {
+                    // Sign extend the value to the full width.
+                    Ok(((raw | !bitfield_mask(*bit_width)) as i64).to_object(py))
+                } else {
+                    Ok(raw.to_object(py))
+                }
+            }
         })
     }
 
@@ -108,18 +530,55 @@ impl InternalDT {
         Python::with_gil(|py| match self {
             InternalDT::Byte => Ok(obj.extract::<i8>(py)?.to_le_bytes().to_vec()),
             InternalDT::UByte => Ok(obj.extract::<u8>(py)?.to_le_bytes().to_vec()),
-            InternalDT::Char => Ok(obj.extract::<c_char>(py)?.to_le_bytes().to_vec()),
-            // OS widechar encoding.
-            InternalDT::WideChar => Ok(obj.extract::<u16>(py)?.to_le_bytes().to_vec()),
+            InternalDT::Bool => Ok(vec![obj.is_true(py)? as u8]),
+            // Like ctypes a single byte or its ordinal.
+            InternalDT::Char => match obj.extract::<&[u8]>(py) {
+                Ok(&[c]) => Ok(vec![c]),
+                Ok(_) => Err(MemflowPyError::ValueOutOfRange(
+                    obj.as_ref(py).repr()?.to_string(),
+                    "c_char".to_owned(),
+                )),
+                Err(_) => {
+                    let ordinal: i64 = obj.extract(py)?;
+                    let c = u8::try_from(ordinal).map_err(|_| {
+                        MemflowPyError::ValueOutOfRange(ordinal.to_string(), "c_char".to_owned())
+                    })?;
+                    Ok(vec![c])
+                }
+            },
+            // A single character that has to fit into one code unit of the OS widechar encoding.
+            InternalDT::WideChar(size) => {
+                let text: &str = obj.extract(py)?;
+                let bytes = strings::encode_wide_chars(text, *size, false)?;
+                match text.chars().count() == 1 && bytes.len() == *size {
+                    true => Ok(bytes),
+                    false => Err(MemflowPyError::ValueOutOfRange(
+                        obj.as_ref(py).repr()?.to_string(),
+                        "c_wchar".to_owned(),
+                    )),
+                }
+            }
             InternalDT::Double => Ok(obj.extract::<c_double>(py)?.to_le_bytes().to_vec()),
-            InternalDT::LongDouble => todo!(),
+            InternalDT::LongDouble(size, format, _) => {
+                let value = float80::from_py(py, &obj, *format)?;
+                match size {
+                    8 => Ok(float80::to_f64(&value).to_le_bytes().to_vec()),
+                    _ => {
+                        let mut bytes = value.to_bytes();
+                        bytes.resize(*size, 0);
+                        Ok(bytes)
+                    }
+                }
+            }
             InternalDT::Float => Ok(obj.extract::<c_float>(py)?.to_le_bytes().to_vec()),
             InternalDT::Short => Ok(obj.extract::<c_short>(py)?.to_le_bytes().to_vec()),
             InternalDT::UShort => Ok(obj.extract::<c_ushort>(py)?.to_le_bytes().to_vec()),
             InternalDT::Int => Ok(obj.extract::<c_int>(py)?.to_le_bytes().to_vec()),
             InternalDT::UInt => Ok(obj.extract::<c_uint>(py)?.to_le_bytes().to_vec()),
-            InternalDT::Long => Ok(obj.extract::<c_long>(py)?.to_le_bytes().to_vec()),
-            InternalDT::ULong => Ok(obj.extract::<c_ulong>(py)?.to_le_bytes().to_vec()),
+            InternalDT::Long(4) => Ok(obj.extract::<i32>(py)?.to_le_bytes().to_vec()),
+            InternalDT::Long(_) => Ok(obj.extract::<i64>(py)?.to_le_bytes().to_vec()),
+            InternalDT::ULong(4) => Ok(obj.extract::<u32>(py)?.to_le_bytes().to_vec()),
+            InternalDT::ULong(_) => Ok(obj.extract::<u64>(py)?.to_le_bytes().to_vec()),
             InternalDT::LongLong => Ok(obj.extract::<c_longlong>(py)?.to_le_bytes().to_vec()),
             InternalDT::ULongLong => Ok(obj.extract::<c_ulonglong>(py)?.to_le_bytes().to_vec()),
             InternalDT::Pointer(_, _) => Ok(obj
@@ -127,6 +586,37 @@ impl InternalDT {
                 .extract::<umem>(py)?
                 .to_le_bytes()[..self.size()]
                 .to_vec()),
+            InternalDT::CharPtr(..) | InternalDT::WideCharPtr(..) => {
+                // Accepts the pointer object, a raw address or `None` for a null pointer.
+                let addr: umem = match obj.getattr(py, "addr") {
+                    _ if obj.is_none(py) => 0,
+                    Ok(addr) => addr.extract(py)?,
+                    Err(_) => obj.extract(py)?,
+                };
+                Ok(addr.to_le_bytes()[..self.size()].to_vec())
+            }
+            InternalDT::Array(_, dt, _)
+                if dt.char_width().is_some() && !obj_is_sequence(py, &obj) =>
+            {
+                // Text is padded with NUL characters, the full buffer is kept for values read before.
+                let obj = obj.getattr(py, "raw").unwrap_or(obj);
+                let mut bytes = match dt.char_width() {
+                    Some(1) => match obj.extract::<&str>(py) {
+                        Ok(text) => text.as_bytes().to_vec(),
+                        Err(_) => obj.extract::<Vec<u8>>(py)?,
+                    },
+                    _ => strings::encode_wide_chars(
+                        obj.extract::<&str>(py)?,
+                        dt.size(),
+                        dt.is_big_endian(),
+                    )?,
+                };
+                if bytes.len() > self.size() {
+                    return Err(MemflowPyError::StringTooLong(bytes.len(), self.size()));
+                }
+                bytes.resize(self.size(), 0);
+                Ok(bytes)
+            }
             InternalDT::Array(_, dt, len) => {
                 let mut bytes = Vec::new();
                 for i in 0..*len {
@@ -137,12 +627,33 @@ impl InternalDT {
             }
             // NOTE: The passed object is not checked to be type of structure.
             InternalDT::Structure(_, dts) => {
-                let mut bytes = Vec::new();
-                bytes.resize(self.size(), 0);
+                let mut bytes = vec![0; self.size()];
                 dts.into_iter()
                     .try_for_each::<_, crate::Result<()>>(|(name, (offset, dt))| {
                         if let Ok(val_obj) = obj.getattr(py, name.as_str()) {
-                            bytes.splice(offset..&(offset + dt.size()), dt.py_to_bytes(val_obj)?);
+                            let sized_dt;
+                            let dt = match dt {
+                                InternalDT::DynArray(_, _, length_field) => {
+                                    sized_dt = dt.with_length(
+                                        obj.getattr(py, length_field.as_str())?.extract(py)?,
+                                    );
+                                    // The tail grows the structure past its fixed size.
+                                    bytes.resize(bytes.len().max(offset + sized_dt.size()), 0);
+                                    &sized_dt
+                                }
+                                _ => dt,
+                            };
+                            let field_bytes = dt.py_to_bytes(val_obj)?;
+                            let field_range = *offset..(offset + dt.size());
+                            if let InternalDT::Bitfield(_, _, _) = dt {
+                                // Bitfields share their storage unit, merge instead of overwriting.
+                                bytes[field_range]
+                                    .iter_mut()
+                                    .zip(field_bytes)
+                                    .for_each(|(b, fb)| *b |= fb);
+                            } else {
+                                bytes.splice(field_range, field_bytes);
+                            }
                             Ok(())
                         } else {
                             Err(MemflowPyError::MissingAttribute(name.to_owned()))
@@ -150,6 +661,49 @@ impl InternalDT {
                     })?;
                 Ok(bytes)
             }
+            // Only a single member is encoded, the one last set on the object or otherwise the largest one.
+            InternalDT::Union(_, dts) => {
+                let set_name: Option<String> = obj
+                    .getattr(py, "_set_")
+                    .ok()
+                    .and_then(|n| n.extract(py).ok());
+                let (name, (offset, dt)) = match set_name {
+                    Some(set_name) => dts
+                        .get_key_value(&set_name)
+                        .ok_or(MemflowPyError::MissingAttribute(set_name))?,
+                    None => dts
+                        .iter()
+                        .rev()
+                        .max_by_key(|(_, (_, dt))| dt.size())
+                        .ok_or_else(|| MemflowPyError::NoType("_fields_".to_owned()))?,
+                };
+                let val_obj = obj
+                    .getattr(py, name.as_str())
+                    .map_err(|_| MemflowPyError::MissingAttribute(name.to_owned()))?;
+                let mut bytes = vec![0; self.size()];
+                bytes.splice(*offset..(offset + dt.size()), dt.py_to_bytes(val_obj)?);
+                Ok(bytes)
+            }
+            InternalDT::DynArray(class, _, _) => Err(MemflowPyError::InvalidDynamicArray(
+                class.getattr(py, "__name__")?.to_string(),
+            )),
+            InternalDT::Enum(_, dt) => {
+                let value = obj.call_method0(py, "__index__")?;
+                dt.py_to_bytes(value)
+            }
+            InternalDT::BigEndian(dt) => {
+                let mut bytes = dt.py_to_bytes(obj)?;
+                bytes.reverse();
+                Ok(bytes)
+            }
+            InternalDT::Bitfield(dt, bit_offset, bit_width) => {
+                // Values are truncated to the bit width like ctypes does.
+                let raw = match dt.is_signed() {
+                    true => obj.extract::<i64>(py)? as u64,
+                    false => obj.extract::<u64>(py)?,
+                };
+                Ok(dt.unit_to_bytes((raw & bitfield_mask(*bit_width)) << bit_offset))
+            }
         })
     }
 
@@ -157,38 +711,278 @@ impl InternalDT {
         match self {
             InternalDT::Byte => size_of::<c_schar>(),
             InternalDT::UByte => size_of::<c_uchar>(),
+            InternalDT::Bool => size_of::<bool>(),
             InternalDT::Char => size_of::<c_char>(),
-            InternalDT::WideChar => size_of::<c_char>() * 2,
+            InternalDT::WideChar(size) => *size,
             InternalDT::Short => size_of::<c_short>(),
             InternalDT::UShort => size_of::<c_ushort>(),
             InternalDT::Double => size_of::<c_double>(),
-            InternalDT::LongDouble => size_of::<c_double>() * 2,
+            InternalDT::LongDouble(size, ..) => *size,
             InternalDT::Float => size_of::<c_float>(),
             InternalDT::Int => size_of::<c_int>(),
             InternalDT::UInt => size_of::<c_uint>(),
-            InternalDT::Long => size_of::<c_long>(),
-            InternalDT::ULong => size_of::<c_ulong>(),
+            InternalDT::Long(size) | InternalDT::ULong(size) => *size,
             InternalDT::LongLong => size_of::<c_longlong>(),
             InternalDT::ULongLong => size_of::<c_ulonglong>(),
-            InternalDT::Pointer(_, byteness) => *byteness as usize,
+            InternalDT::Pointer(_, byteness)
+            | InternalDT::CharPtr(_, byteness, _)
+            | InternalDT::WideCharPtr(_, byteness, _, _) => *byteness,
             InternalDT::Array(_, dt, len) => dt.size() * (*len as usize),
-            InternalDT::Structure(_, dts) => {
-                let (_, max_dt) = dts
-                    .iter()
-                    .max_by(|(_, x), (_, y)| (x.0 + x.1.size()).cmp(&(y.0 + y.1.size())))
-                    .unwrap();
-                // Offset + dt size
-                max_dt.0 + max_dt.1.size()
+            // Like C flexible array members do not count towards the size.
+            InternalDT::DynArray(_, _, _) => 0,
+            InternalDT::Structure(_, dts) | InternalDT::Union(_, dts) => {
+                // Offset + dt size of the field reaching furthest, structures without fields are empty.
+                dts.values()
+                    .map(|(offset, dt)| offset + dt.size())
+                    .max()
+                    .unwrap_or(0)
+            }
+            InternalDT::BigEndian(dt)
+            | InternalDT::Enum(_, dt)
+            | InternalDT::Bitfield(dt, _, _) => dt.size(),
+        }
+    }
+
+    /// Reads the storage unit of an integer datatype as raw bits.
+    fn unit_from_bytes(&self, bytes: &[u8]) -> u64 {
+        let mut unit = [0_u8; 8];
+        match self {
+            InternalDT::BigEndian(_) => {
+                unit[8 - bytes.len()..].copy_from_slice(bytes);
+                u64::from_be_bytes(unit)
+            }
+            _ => {
+                unit[..bytes.len()].copy_from_slice(bytes);
+                u64::from_le_bytes(unit)
             }
         }
     }
+
+    /// Writes raw bits into the storage unit of an integer datatype.
+    fn unit_to_bytes(&self, unit: u64) -> Vec<u8> {
+        match self {
+            InternalDT::BigEndian(_) => unit.to_be_bytes()[8 - self.size()..].to_vec(),
+            _ => unit.to_le_bytes()[..self.size()].to_vec(),
+        }
+    }
+
+    /// Returns the size including the dynamic array tail of a structure, `bytes` has to hold at least the fixed size.
+    pub fn size_from_bytes(&self, bytes: &[u8]) -> crate::Result<usize> {
+        let InternalDT::Structure(_, dts) = self else {
+            return Ok(self.size());
+        };
+        let Some((offset, (_, dt, length_field))) =
+            dts.values().find_map(|(offset, dt)| match dt {
+                InternalDT::DynArray(class, dt, length_field) => {
+                    Some((offset, (class, dt, length_field)))
+                }
+                _ => None,
+            })
+        else {
+            return Ok(self.size());
+        };
+        let (length_offset, length_dt) = &dts[length_field];
+        let length: usize = Python::with_gil(|py| {
+            length_dt
+                .py_from_bytes(
+                    bytes[*length_offset..(length_offset + length_dt.size())].to_vec(),
+                    None,
+                )?
+                .extract(py)
+                .map_err(MemflowPyError::from)
+        })?;
+        Ok(self.size().max(offset + dt.size() * length))
+    }
+
+    /// Reads the datatype at `addr`, following up with the dynamic array tail sized by the fixed part.
+    pub fn read_bytes(
+        &self,
+        mem: &mut (impl MemorySource + ?Sized),
+        addr: Address,
+    ) -> PyResult<Vec<u8>> {
+        let mut bytes = vec![0; self.size()];
+        mem.read_raw_into(addr, &mut bytes)
+            .map_err(|e| PyException::new_err(format!("failed to read bytes {}", e)))?;
+        self.read_tail(mem, addr, bytes)
+    }
+
+    /// Completes the fixed size `bytes` read from `addr` with the dynamic array tail.
+    pub fn read_tail(
+        &self,
+        mem: &mut (impl MemorySource + ?Sized),
+        addr: Address,
+        mut bytes: Vec<u8>,
+    ) -> PyResult<Vec<u8>> {
+        let fixed_size = bytes.len();
+        let size = self.size_from_bytes(&bytes)?;
+        if size > fixed_size {
+            bytes.resize(size, 0);
+            mem.read_raw_into(addr + fixed_size, &mut bytes[fixed_size..])
+                .map_err(|e| PyException::new_err(format!("failed to read bytes {}", e)))?;
+        }
+        Ok(bytes)
+    }
+
+    /// Replaces the bits of an encoded bitfield in its storage unit, keeping the other bitfields.
+    pub fn merge_bitfield(&self, unit: &[u8], field_bytes: &[u8]) -> Vec<u8> {
+        let InternalDT::Bitfield(dt, bit_offset, bit_width) = self else {
+            return field_bytes.to_vec();
+        };
+        let mask = dt.unit_to_bytes(bitfield_mask(*bit_width) << bit_offset);
+        unit.iter()
+            .zip(mask)
+            .zip(field_bytes)
+            .map(|((u, m), b)| u & !m | b)
+            .collect()
+    }
+
+    /// Turns a dynamic array into a fixed array of `len` items.
+    pub fn with_length(&self, len: u32) -> InternalDT {
+        match self {
+            InternalDT::DynArray(class, dt, _) => InternalDT::Array(class.clone(), dt.clone(), len),
+            _ => self.clone(),
+        }
+    }
+
+    /// Returns whether following the datatype can lead to pointers, unions are not looked into.
+    pub fn has_pointers(&self) -> bool {
+        match self {
+            InternalDT::Pointer(_, _) => true,
+            InternalDT::BigEndian(dt) => dt.has_pointers(),
+            InternalDT::Structure(_, dts) => dts.values().any(|(_, dt)| dt.has_pointers()),
+            InternalDT::Array(_, dt, _) | InternalDT::DynArray(_, dt, _) => dt.has_pointers(),
+            _ => false,
+        }
+    }
+
+    /// Returns the width of `c_char` and `c_wchar` datatypes.
+    pub fn char_width(&self) -> Option<usize> {
+        match self {
+            InternalDT::Char => Some(1),
+            InternalDT::WideChar(size) => Some(*size),
+            InternalDT::BigEndian(dt) => dt.char_width(),
+            _ => None,
+        }
+    }
+
+    fn is_big_endian(&self) -> bool {
+        matches!(self, InternalDT::BigEndian(_))
+    }
+
+    /// Returns true if the datatype is an integer that can back a bitfield.
+    pub fn is_integer(&self) -> bool {
+        if let InternalDT::BigEndian(dt) = self {
+            return dt.is_integer();
+        }
+        matches!(
+            self,
+            InternalDT::Byte
+                | InternalDT::UByte
+                | InternalDT::Bool
+                | InternalDT::Short
+                | InternalDT::UShort
+                | InternalDT::Int
+                | InternalDT::UInt
+                | InternalDT::Long(_)
+                | InternalDT::ULong(_)
+                | InternalDT::LongLong
+                | InternalDT::ULongLong
+        )
+    }
+
+    /// Stores the datatype in the given byte order, single bytes are left untouched.
+    fn with_endianness(self, endianness: Endianness) -> Self {
+        match endianness {
+            Endianness::Big if self.size() > 1 => InternalDT::BigEndian(Box::new(self)),
+            _ => self,
+        }
+    }
+
+    /// Turns the datatype into a bitfield starting at `bit_offset` counted in allocation order.
+    ///
+    /// Allocation starts at the least significant bit for little endian and at the most significant bit for big endian.
+    /// Single byte units carry no byte order themselves so the structure byte order is used for them.
+    fn into_bitfield(self, bit_offset: u32, bit_width: u32, endianness: Endianness) -> Self {
+        let big_endian = match self {
+            InternalDT::BigEndian(_) => true,
+            _ => self.size() == 1 && endianness == Endianness::Big,
+        };
+        let bit_offset = match big_endian {
+            true => self.size() as u32 * 8 - bit_offset - bit_width,
+            false => bit_offset,
+        };
+        InternalDT::Bitfield(Box::new(self), bit_offset, bit_width)
+    }
+
+    /// Checks that the datatype can back a bitfield of `bit_width` bits.
+    fn check_bitfield(&self, name: &str, bit_width: u32) -> crate::Result<()> {
+        if !self.is_integer() || bit_width == 0 || bit_width as usize > self.size() * 8 {
+            return Err(MemflowPyError::InvalidBitfield(name.to_owned()));
+        }
+        Ok(())
+    }
+
+    /// Returns true if the datatype is a signed integer.
+    pub fn is_signed(&self) -> bool {
+        if let InternalDT::BigEndian(dt) = self {
+            return dt.is_signed();
+        }
+        matches!(
+            self,
+            InternalDT::Byte
+                | InternalDT::Short
+                | InternalDT::Int
+                | InternalDT::Long(_)
+                | InternalDT::LongLong
+        )
+    }
 }
 
-impl TryFrom<PyObject> for InternalDT {
-    type Error = MemflowPyError;
+/// Reborrows the memory for a nested decode, shortening the trait object lifetime to the borrow.
+fn reborrow<'a>(mem: &'a mut Option<&mut dyn MemorySource>) -> Option<&'a mut dyn MemorySource> {
+    match mem {
+        Some(mem) => Some(&mut **mem),
+        None => None,
+    }
+}
 
-    fn try_from(value: PyObject) -> Result<Self, Self::Error> {
+/// Returns true for lists and tuples of items, as opposed to text.
+fn obj_is_sequence(py: Python<'_>, obj: &PyObject) -> bool {
+    obj.as_ref(py).is_instance_of::<PyList>() || obj.as_ref(py).is_instance_of::<PyTuple>()
+}
+
+/// Reads a pointer value, pointers narrower than `umem` are zero extended.
+pub(crate) fn addr_from_bytes(bytes: &[u8]) -> umem {
+    let mut addr = [0_u8; size_of::<umem>()];
+    addr[..bytes.len()].copy_from_slice(bytes);
+    umem::from_le_bytes(addr)
+}
+
+fn bitfield_mask(bit_width: u32) -> u64 {
+    match bit_width {
+        64 => u64::MAX,
+        _ => (1 << bit_width) - 1,
+    }
+}
+
+impl InternalDT {
+    /// Converts a python type into a datatype using the given options.
+    pub fn from_py(value: PyObject, opts: DTOptions) -> crate::Result<Self> {
+        // Type expressions (i.e. `"POINT*"`) resolve through the default type library.
+        let value = Python::with_gil(|py| crate::typelib::resolve(py, value))?;
         let base_name: String = Python::with_gil(|py| {
+            // The bare `Pointer` class is a pointer of the data model's width.
+            if value.as_ref(py).is(py.get_type::<PyPointer>()) {
+                return Ok("Pointer".to_owned());
+            }
+            // Walk the mro so subclasses of other types (i.e. `class c_uint32_be(c_uint32)`) resolve to their base.
+            let mro: Vec<PyObject> = value.getattr(py, "__mro__")?.extract(py)?;
+            for base_obj in mro.iter().skip(1) {
+                let name: String = base_obj.getattr(py, "__name__")?.extract(py)?;
+                if BASE_NAMES.contains(&name.as_str()) {
+                    return Ok(name);
+                }
+            }
             let base_obj: PyObject = value.getattr(py, "__base__")?.extract(py)?;
             base_obj.getattr(py, "__name__")?.extract(py)
         })?;
@@ -201,69 +995,162 @@ impl TryFrom<PyObject> for InternalDT {
                     Python::with_gil(|py| value.getattr(py, "_type_")?.extract(py))?;
                 let dt = match type_ident.as_str() {
                     "b" => Self::Byte,
-                    "B" | "?" => Self::UByte,
+                    "B" => Self::UByte,
+                    "?" => Self::Bool,
                     "c" => Self::Char,
-                    "u" => Self::WideChar,
+                    "u" => Self::WideChar(opts.data_model.wchar_size()),
                     "z" | "Z" => {
-                        unimplemented!("please use `read_char_string` and `read_wchar_string`")
+                        let (byteness, max_bytes) =
+                            Python::with_gil::<_, crate::Result<_>>(|py| {
+                                let byteness = match value.getattr(py, "_byteness_") {
+                                    Ok(byteness) => byteness.extract(py)?,
+                                    Err(_) => opts.data_model.pointer_size(),
+                                };
+                                let max_bytes = match value.getattr(py, "_max_bytes_") {
+                                    Ok(max_bytes) => max_bytes.extract(py)?,
+                                    Err(_) => None,
+                                };
+                                Ok((byteness, max_bytes))
+                            })?;
+                        match type_ident.as_str() {
+                            "z" => Self::CharPtr(value.clone(), byteness, max_bytes),
+                            _ => Self::WideCharPtr(
+                                value.clone(),
+                                byteness,
+                                opts.data_model.wchar_size(),
+                                max_bytes,
+                            ),
+                        }
                     }
                     "d" => Self::Double,
-                    "g" => Self::LongDouble,
+                    "g" => {
+                        let (size, format, exact) =
+                            Python::with_gil::<_, crate::Result<_>>(|py| {
+                                // An explicit storage size holds the x87 format unless `_format_` names another one.
+                                let (size, format) = match value.getattr(py, "_size_") {
+                                    Ok(size) => (size.extract(py)?, float80::Format::X87),
+                                    Err(_) => (opts.data_model.long_double_size(), opts.extended),
+                                };
+                                let format = match value.getattr(py, "_format_") {
+                                    Ok(format) => format.extract::<&str>(py)?.try_into()?,
+                                    Err(_) => format,
+                                };
+                                let exact = match value.getattr(py, "_exact_") {
+                                    Ok(exact) => exact.is_true(py)?,
+                                    Err(_) => false,
+                                };
+                                Ok((size, format, exact))
+                            })?;
+                        if size != 8 && size < format.size() {
+                            return Err(MemflowPyError::InvalidLongDoubleSize(size));
+                        }
+                        Self::LongDouble(size, format, exact)
+                    }
                     "f" => Self::Float,
                     "h" => Self::Short,
                     "H" => Self::UShort,
                     "i" => Self::Int,
                     "I" => Self::UInt,
-                    "l" => Self::Long,
-                    "L" => Self::ULong,
+                    "l" => Self::Long(opts.data_model.long_size()),
+                    "L" => Self::ULong(opts.data_model.long_size()),
                     "q" => Self::LongLong,
                     "Q" => Self::ULongLong,
                     name => unreachable!("unknown type identifier `{}`", name),
                 };
-                Ok(dt)
+                let endianness = Endianness::from_type(&value)?.unwrap_or(opts.endianness);
+                let dt = dt.with_endianness(endianness);
+                match Python::with_gil(|py| value.getattr(py, "_enum_").ok()) {
+                    Some(enum_obj) if dt.is_integer() => Ok(Self::Enum(enum_obj, Box::new(dt))),
+                    Some(_) => Err(MemflowPyError::InvalidEnum(type_ident)),
+                    None => Ok(dt),
+                }
             }
             "Pointer" => {
                 let byteness: usize =
                     Python::with_gil(|py| match value.getattr(py, "_byteness_") {
                         Ok(val) => val.extract(py),
-                        // If we are passed a pointer with no set byteness we assume the pointer to be local system width.
-                        Err(_) => Ok(size_of::<usize>()),
+                        // If we are passed a pointer with no set byteness we assume the pointer width of the data model.
+                        Err(_) => Ok(opts.data_model.pointer_size()),
                     })?;
-                Ok(Self::Pointer(value, byteness))
-            }
-            "Array" => {
-                let (len, ty_obj) = Python::with_gil::<_, crate::Result<(u32, PyObject)>>(|py| {
-                    Ok((
-                        value.getattr(py, "_length_")?.extract(py)?,
-                        value.getattr(py, "_type_")?.extract(py)?,
-                    ))
+                Ok(Self::Pointer(value, byteness).with_endianness(opts.endianness))
+            }
+            "Array" | "CharArray" | "WideCharArray" => {
+                let (len, length_field, ty_obj) = Python::with_gil::<_, crate::Result<_>>(|py| {
+                    let length_field = match value.getattr(py, "_length_field_") {
+                        Ok(length_field) => Some(length_field.extract::<String>(py)?),
+                        Err(_) => None,
+                    };
+                    let len = match length_field {
+                        Some(_) => 0,
+                        None => value.getattr(py, "_length_")?.extract(py)?,
+                    };
+                    Ok((len, length_field, value.getattr(py, "_type_")?))
                 })?;
-                Ok(InternalDT::Array(value, Box::new(ty_obj.try_into()?), len))
+                let dt = Box::new(Self::from_py(ty_obj, opts)?);
+                match length_field {
+                    Some(length_field) => Ok(InternalDT::DynArray(value, dt, length_field)),
+                    None => Ok(InternalDT::Array(value, dt, len)),
+                }
             }
             "Structure" => {
+                let opts = DTOptions {
+                    endianness: Endianness::from_type(&value)?.unwrap_or(opts.endianness),
+                    ..opts
+                };
                 let fields = Python::with_gil(|py| {
                     value
                         .getattr(py, "_fields_")?
                         .extract::<Vec<Vec<PyObject>>>(py)
                 })?;
 
-                // TODO: Clean this up with a zip iter (offset, field_tuple)
                 let mut current_offset = 0_usize;
+                let mut bitfield_run = BitfieldRun::default();
                 let mut dt_fields = fields
                     .into_iter()
                     .map(|field| {
                         let mut it = field.into_iter();
-                        let field_offset = current_offset;
                         let field_name = it.next().unwrap().to_string();
-                        let field_type: InternalDT = it
+                        let field_type = Self::from_py(
+                            it.next()
+                                .ok_or_else(|| MemflowPyError::NoType(field_name.clone()))?,
+                            opts,
+                        )?;
+                        let bit_width: Option<u32> = it
                             .next()
-                            .ok_or_else(|| MemflowPyError::NoType(field_name.clone()))?
-                            .try_into()?;
-                        current_offset += field_type.size();
-                        Ok((field_name, (field_offset, field_type)))
+                            .map(|obj| Python::with_gil(|py| obj.extract(py)))
+                            .transpose()?;
+                        let Some(bit_width) = bit_width else {
+                            bitfield_run.close();
+                            let field_offset = current_offset;
+                            current_offset += field_type.size();
+                            return Ok((field_name, (field_offset, field_type)));
+                        };
+
+                        field_type.check_bitfield(&field_name, bit_width)?;
+                        let unit_size = field_type.size();
+                        let (unit_offset, bit_offset) = bitfield_run.place(
+                            current_offset,
+                            unit_size,
+                            bit_width,
+                            opts.data_model,
+                        );
+                        current_offset = current_offset.max(unit_offset + unit_size);
+                        let bitfield_type =
+                            field_type.into_bitfield(bit_offset, bit_width, opts.endianness);
+                        Ok((field_name, (unit_offset, bitfield_type)))
                     })
                     .collect::<Result<DTMap, MemflowPyError>>()?;
 
+                // A dynamic array has to end the fields and be sized by one of the fields before it.
+                for (idx, (name, (_, dt))) in dt_fields.iter().enumerate() {
+                    if let InternalDT::DynArray(_, _, length_field) = dt {
+                        let length_idx = dt_fields.get_index_of(length_field);
+                        if idx + 1 != dt_fields.len() || length_idx.is_none_or(|i| i >= idx) {
+                            return Err(MemflowPyError::InvalidDynamicArray(name.clone()));
+                        }
+                    }
+                }
+
                 // TODO: Clean this up
                 if let Some(offset_fields) =
                     Python::with_gil::<_, Result<Option<DTMap>, MemflowPyError>>(|py| {
@@ -276,10 +1163,35 @@ impl TryFrom<PyObject> for InternalDT {
                                     let mut it = field.into_iter();
                                     let field_offset: usize = it.next().unwrap().extract(py)?;
                                     let field_name = it.next().unwrap().to_string();
-                                    let field_type: InternalDT = it
-                                        .next()
-                                        .ok_or_else(|| MemflowPyError::NoType(field_name.clone()))?
-                                        .try_into()?;
+                                    let field_type = Self::from_py(
+                                        it.next().ok_or_else(|| {
+                                            MemflowPyError::NoType(field_name.clone())
+                                        })?,
+                                        opts,
+                                    )?;
+                                    // Bitfields name their bit width followed by the bit offset in the storage unit.
+                                    let field_type = match it.next() {
+                                        Some(bit_width) => {
+                                            let bit_width: u32 = bit_width.extract(py)?;
+                                            let bit_offset: u32 = match it.next() {
+                                                Some(bit_offset) => bit_offset.extract(py)?,
+                                                None => 0,
+                                            };
+                                            field_type.check_bitfield(&field_name, bit_width)?;
+                                            if bit_offset + bit_width > field_type.size() as u32 * 8
+                                            {
+                                                return Err(MemflowPyError::InvalidBitfield(
+                                                    field_name,
+                                                ));
+                                            }
+                                            field_type.into_bitfield(
+                                                bit_offset,
+                                                bit_width,
+                                                opts.endianness,
+                                            )
+                                        }
+                                        None => field_type,
+                                    };
                                     Ok((field_name, (field_offset, field_type)))
                                 })
                                 .collect::<Result<DTMap, MemflowPyError>>()?;
@@ -295,7 +1207,53 @@ impl TryFrom<PyObject> for InternalDT {
 
                 Ok(Self::Structure(value, dt_fields))
             }
+            "Union" => {
+                let opts = DTOptions {
+                    endianness: Endianness::from_type(&value)?.unwrap_or(opts.endianness),
+                    ..opts
+                };
+                let fields = Python::with_gil(|py| {
+                    value
+                        .getattr(py, "_fields_")?
+                        .extract::<Vec<Vec<PyObject>>>(py)
+                })?;
+
+                let dt_fields = fields
+                    .into_iter()
+                    .map(|field| {
+                        let mut it = field.into_iter();
+                        let field_name = it.next().unwrap().to_string();
+                        let field_type = Self::from_py(
+                            it.next()
+                                .ok_or_else(|| MemflowPyError::NoType(field_name.clone()))?,
+                            opts,
+                        )?;
+                        let bit_width: Option<u32> = it
+                            .next()
+                            .map(|obj| Python::with_gil(|py| obj.extract(py)))
+                            .transpose()?;
+                        let field_type = match bit_width {
+                            Some(bit_width) => {
+                                field_type.check_bitfield(&field_name, bit_width)?;
+                                field_type.into_bitfield(0, bit_width, opts.endianness)
+                            }
+                            None => field_type,
+                        };
+                        Ok((field_name, (0, field_type)))
+                    })
+                    .collect::<Result<DTMap, MemflowPyError>>()?;
+
+                Ok(Self::Union(value, dt_fields))
+            }
             _ => Err(MemflowPyError::InvalidType(base_name)),
         }
     }
 }
+
+impl TryFrom<PyObject> for InternalDT {
+    type Error = MemflowPyError;
+
+    fn try_from(value: PyObject) -> Result<Self, Self::Error> {
+        Self::from_py(value, DTOptions::default())
+    }
+}
@@ -141,8 +141,8 @@ impl PyInventory {
                 input.map(|c| c.into()),
                 args.and_then(|a| str::parse::<OsArgs>(a).ok()).as_ref(),
             )
-            .map_err(MemflowPyError::Memflow)?
-            .into())
+            .map_err(MemflowPyError::Memflow)
+            .map(|os| PyOs::new(os, is_windows_os(name)))?)
     }
 
     // TODO:
@@ -182,3 +182,12 @@ impl From<PyTargetInfo> for TargetInfo {
         py_info.0
     }
 }
+
+/// Tells whether the os plugin `name` inspects Windows targets, the native plugin inspects the running system.
+fn is_windows_os(name: &str) -> bool {
+    match name {
+        "win32" => true,
+        "native" => cfg!(windows),
+        _ => false,
+    }
+}
@@ -0,0 +1,701 @@
+use std::collections::{HashMap, HashSet};
+
+use pyo3::{
+    prelude::*,
+    types::{PyDict, PyList},
+};
+
+use crate::{
+    internal::{DTOptions, DataModel, InternalDT},
+    MemflowPyError,
+};
+
+/// Joins a type name with a declarator, i.e. `uint32_t` and `*next` or `[4]`.
+fn join(name: &str, inner: &str) -> String {
+    match inner.is_empty() || inner.starts_with('[') || inner.chars().all(|c| c == '*') {
+        true => format!("{}{}", name, inner),
+        false => format!("{} {}", name, inner),
+    }
+}
+
+fn class_name(py: Python<'_>, class: &PyObject) -> crate::Result<String> {
+    Ok(class.getattr(py, "__name__")?.extract(py)?)
+}
+
+/// Field of a record with its name and offset.
+type Field<'a> = (&'a String, usize, &'a InternalDT);
+
+/// Fields of a record ordered by offset, overlapping fields keep their declaration order.
+pub(crate) fn sorted_fields(dt: &InternalDT) -> Vec<Field<'_>> {
+    let mut fields = match dt {
+        InternalDT::Structure(_, dts) | InternalDT::Union(_, dts) => dts
+            .iter()
+            .map(|(name, (offset, dt))| (name, *offset, dt))
+            .collect::<Vec<_>>(),
+        _ => Vec::new(),
+    };
+    fields.sort_by_key(|(_, offset, _)| *offset);
+    fields
+}
+
+/// Bit offset of a bitfield counted in declaration order, big endian units allocate from the most significant bit.
+fn declared_bit_offset(dt: &InternalDT, bit_offset: u32, bit_width: u32) -> u32 {
+    match dt {
+        InternalDT::BigEndian(_) => dt.size() as u32 * 8 - bit_offset - bit_width,
+        _ => bit_offset,
+    }
+}
+
+/// Writes C declarations of datatypes, records are named after their class and defined on demand.
+struct HeaderWriter<'py> {
+    py: Python<'py>,
+    opts: DTOptions,
+    names: HashMap<usize, String>,
+    taken: HashSet<String>,
+    /// Records referenced so far in the order they were found.
+    records: Vec<(usize, InternalDT)>,
+    defined: HashSet<usize>,
+    enums: Vec<String>,
+    enum_names: HashSet<usize>,
+    definitions: Vec<String>,
+    pads: usize,
+}
+
+impl<'py> HeaderWriter<'py> {
+    fn new(py: Python<'py>, opts: DTOptions) -> Self {
+        Self {
+            py,
+            opts,
+            names: HashMap::new(),
+            taken: HashSet::new(),
+            records: Vec::new(),
+            defined: HashSet::new(),
+            enums: Vec::new(),
+            enum_names: HashSet::new(),
+            definitions: Vec::new(),
+            pads: 0,
+        }
+    }
+
+    /// Returns the tag of a record, i.e. `struct _LIST_ENTRY`.
+    fn record_tag(&mut self, dt: &InternalDT) -> crate::Result<String> {
+        let (class, kind) = match dt {
+            InternalDT::Structure(class, _) => (class, "struct"),
+            InternalDT::Union(class, _) => (class, "union"),
+            _ => unreachable!(),
+        };
+        let key = class.as_ptr() as usize;
+        if !self.names.contains_key(&key) {
+            let base_name = class_name(self.py, class)?;
+            let mut name = base_name.clone();
+            let mut idx = 1;
+            while !self.taken.insert(name.clone()) {
+                idx += 1;
+                name = format!("{}_{}", base_name, idx);
+            }
+            self.names.insert(key, name);
+            self.records.push((key, dt.clone()));
+        }
+        Ok(format!("{} {}", kind, self.names[&key]))
+    }
+
+    /// Writes the declaration of `dt` around the declarator `inner`, i.e. `int32_t (*inner)[4]`.
+    fn decl(&mut self, dt: &InternalDT, inner: &str) -> crate::Result<String> {
+        let py = self.py;
+        let name = match dt {
+            InternalDT::Byte => "int8_t",
+            InternalDT::UByte => "uint8_t",
+            InternalDT::Bool => "_Bool",
+            InternalDT::Char => "char",
+            InternalDT::WideChar(size) if *size == self.opts.data_model.wchar_size() => "wchar_t",
+            InternalDT::WideChar(2) => "uint16_t",
+            InternalDT::WideChar(_) => "uint32_t",
+            InternalDT::Double => "double",
+            InternalDT::LongDouble(8, ..) => "double",
+            InternalDT::LongDouble(..) => "long double",
+            InternalDT::Float => "float",
+            InternalDT::Short => "int16_t",
+            InternalDT::UShort => "uint16_t",
+            InternalDT::Int | InternalDT::Long(4) => "int32_t",
+            InternalDT::UInt | InternalDT::ULong(4) => "uint32_t",
+            InternalDT::LongLong | InternalDT::Long(_) => "int64_t",
+            InternalDT::ULongLong | InternalDT::ULong(_) => "uint64_t",
+            // Pointers of another width than the data model can only be kept as their address.
+            InternalDT::Pointer(_, byteness)
+            | InternalDT::CharPtr(_, byteness, _)
+            | InternalDT::WideCharPtr(_, byteness, _, _)
+                if *byteness != self.opts.data_model.pointer_size() =>
+            {
+                return Ok(join(&format!("uint{}_t", byteness * 8), inner));
+            }
+            InternalDT::CharPtr(..) => return Ok(join("char", &format!("*{}", inner))),
+            InternalDT::WideCharPtr(..) => return Ok(join("wchar_t", &format!("*{}", inner))),
+            InternalDT::Pointer(class, _) => {
+                let class = class.as_ref(py);
+                if let (Ok(restype), Ok(argtypes)) =
+                    (class.getattr("_restype_"), class.getattr("_argtypes_"))
+                {
+                    let args = argtypes
+                        .iter()?
+                        .map(|arg| {
+                            let arg = InternalDT::from_py(arg?.into(), self.opts)?;
+                            self.decl(&arg, "")
+                        })
+                        .collect::<crate::Result<Vec<_>>>()?;
+                    let args = match args.is_empty() {
+                        true => "void".to_owned(),
+                        false => args.join(", "),
+                    };
+                    let inner = format!("(*{})({})", inner, args);
+                    return match restype.is_none() {
+                        true => Ok(join("void", &inner)),
+                        false => {
+                            let restype = InternalDT::from_py(restype.into(), self.opts)?;
+                            self.decl(&restype, &inner)
+                        }
+                    };
+                }
+                return match class.getattr("_type_") {
+                    Ok(target) => {
+                        let target = InternalDT::from_py(target.into(), self.opts)?;
+                        self.decl(&target, &format!("*{}", inner))
+                    }
+                    Err(_) => Ok(join("void", &format!("*{}", inner))),
+                };
+            }
+            InternalDT::Array(_, dt, len) => {
+                let inner = match inner.starts_with('*') {
+                    true => format!("({})[{}]", inner, len),
+                    false => format!("{}[{}]", inner, len),
+                };
+                return self.decl(dt, &inner);
+            }
+            InternalDT::DynArray(_, dt, _) => return self.decl(dt, &format!("{}[]", inner)),
+            InternalDT::Structure(..) | InternalDT::Union(..) => {
+                let tag = self.record_tag(dt)?;
+                return Ok(join(&tag, inner));
+            }
+            InternalDT::Enum(enum_obj, dt) => {
+                self.define_enum(enum_obj)?;
+                return self.decl(dt, inner);
+            }
+            InternalDT::BigEndian(dt) | InternalDT::Bitfield(dt, _, _) => {
+                return self.decl(dt, inner)
+            }
+        };
+        Ok(join(name, inner))
+    }
+
+    /// Notes on what the C declaration of `dt` can not express.
+    fn comment(&self, dt: &InternalDT) -> crate::Result<Option<String>> {
+        Ok(match dt {
+            InternalDT::Enum(enum_obj, _) => {
+                Some(format!("enum {}", class_name(self.py, enum_obj)?))
+            }
+            InternalDT::BigEndian(_) => Some("big endian".to_owned()),
+            InternalDT::Pointer(..) | InternalDT::CharPtr(..) | InternalDT::WideCharPtr(..)
+                if dt.size() != self.opts.data_model.pointer_size() =>
+            {
+                Some(format!("{} bit pointer", dt.size() * 8))
+            }
+            InternalDT::Array(_, dt, _)
+            | InternalDT::DynArray(_, dt, _)
+            | InternalDT::Bitfield(dt, _, _) => self.comment(dt)?,
+            _ => None,
+        })
+    }
+
+    fn define_enum(&mut self, enum_obj: &PyObject) -> crate::Result<()> {
+        if !self.enum_names.insert(enum_obj.as_ptr() as usize) {
+            return Ok(());
+        }
+        let py = self.py;
+        let mut out = format!("enum {} {{\n", class_name(py, enum_obj)?);
+        let members = enum_obj.getattr(py, "__members__")?;
+        for item in members.call_method0(py, "items")?.as_ref(py).iter()? {
+            let (name, member): (String, &PyAny) = item?.extract()?;
+            let value: i128 = member.getattr("value")?.extract()?;
+            out += &format!("    {} = {},\n", name, value);
+        }
+        out += "};\n";
+        self.enums.push(out);
+        Ok(())
+    }
+
+    /// Defines the records `dt` holds by value, they have to be complete before `dt`.
+    fn define_members(&mut self, dt: &InternalDT) -> crate::Result<()> {
+        match dt {
+            InternalDT::Structure(..) | InternalDT::Union(..) => {
+                self.record_tag(dt)?;
+                self.define(dt)
+            }
+            InternalDT::Array(_, dt, _) | InternalDT::DynArray(_, dt, _) => self.define_members(dt),
+            _ => Ok(()),
+        }
+    }
+
+    fn define(&mut self, dt: &InternalDT) -> crate::Result<()> {
+        let (class, union) = match dt {
+            InternalDT::Structure(class, _) => (class, false),
+            InternalDT::Union(class, _) => (class, true),
+            _ => unreachable!(),
+        };
+        if !self.defined.insert(class.as_ptr() as usize) {
+            return Ok(());
+        }
+        let fields = sorted_fields(dt);
+        for (_, _, field_dt) in &fields {
+            self.define_members(field_dt)?;
+        }
+
+        let tag = self.record_tag(dt)?;
+        let mut out = format!("{} {{\n", tag);
+        match union {
+            true => {
+                for (name, offset, field_dt) in fields {
+                    out += &self.member(name, offset, field_dt, 1)?;
+                }
+            }
+            false => out += &self.struct_body(&fields, 0, 1)?,
+        }
+        out += &format!(
+            "}};\n_Static_assert(sizeof({}) == 0x{:x}, \"size of {}\");\n",
+            tag,
+            dt.size(),
+            tag
+        );
+        self.definitions.push(out);
+        Ok(())
+    }
+
+    /// Writes a member line, bitfields fill their storage unit on their own.
+    fn member(
+        &mut self,
+        name: &str,
+        offset: usize,
+        dt: &InternalDT,
+        depth: usize,
+    ) -> crate::Result<String> {
+        if let InternalDT::Bitfield(_, bit_offset, bit_width) = dt {
+            let unit = [(name.to_owned(), *bit_offset, *bit_width)];
+            return self.bitfield_unit(&unit, offset, dt, depth);
+        }
+        let decl = self.decl(dt, name)?;
+        self.line(offset, &decl, self.comment(dt)?, depth)
+    }
+
+    fn line(
+        &self,
+        offset: usize,
+        decl: &str,
+        comment: Option<String>,
+        depth: usize,
+    ) -> crate::Result<String> {
+        let indent = "    ".repeat(depth);
+        Ok(match comment {
+            Some(comment) => format!(
+                "{}/* 0x{:04x} */ {}; /* {} */\n",
+                indent, offset, decl, comment
+            ),
+            None => format!("{}/* 0x{:04x} */ {};\n", indent, offset, decl),
+        })
+    }
+
+    /// Writes bitfields sharing one storage unit, unnamed bitfields fill the bits around them.
+    fn bitfield_unit(
+        &mut self,
+        bits: &[(String, u32, u32)],
+        offset: usize,
+        dt: &InternalDT,
+        depth: usize,
+    ) -> crate::Result<String> {
+        let InternalDT::Bitfield(unit_dt, _, _) = dt else {
+            unreachable!()
+        };
+        let unit_type = self.decl(unit_dt, "")?;
+        let comment = self.comment(unit_dt)?;
+        let unit_bits = unit_dt.size() as u32 * 8;
+        let mut out = String::new();
+        let mut used = 0;
+        for (name, bit_offset, bit_width) in bits {
+            let start = declared_bit_offset(unit_dt, *bit_offset, *bit_width);
+            if start > used {
+                out += &self.line(
+                    offset,
+                    &format!("{} : {}", unit_type, start - used),
+                    None,
+                    depth,
+                )?;
+            }
+            let decl = format!("{} : {}", join(&unit_type, name), bit_width);
+            out += &self.line(offset, &decl, comment.clone(), depth)?;
+            used = start + bit_width;
+        }
+        if used < unit_bits {
+            out += &self.line(
+                offset,
+                &format!("{} : {}", unit_type, unit_bits - used),
+                None,
+                depth,
+            )?;
+        }
+        Ok(out)
+    }
+
+    /// Writes structure fields starting at `base`, holes become padding and overlapping fields anonymous unions.
+    fn struct_body(
+        &mut self,
+        fields: &[Field<'_>],
+        base: usize,
+        depth: usize,
+    ) -> crate::Result<String> {
+        // Bitfields of one storage unit are laid out together.
+        let mut items: Vec<(usize, usize, Vec<Field<'_>>)> = Vec::new();
+        for field in fields {
+            let (_, offset, dt) = *field;
+            if let (
+                Some((item_offset, _, members)),
+                InternalDT::Bitfield(unit_dt, bit_offset, bit_width),
+            ) = (items.last_mut(), dt)
+            {
+                let start = declared_bit_offset(unit_dt, *bit_offset, *bit_width);
+                let fits = members.iter().all(|(_, _, other)| match other {
+                    InternalDT::Bitfield(other_unit, other_offset, other_width) => {
+                        other_unit.size() == unit_dt.size()
+                            && declared_bit_offset(other_unit, *other_offset, *other_width)
+                                + other_width
+                                <= start
+                    }
+                    _ => false,
+                });
+                if *item_offset == offset && fits {
+                    members.push(*field);
+                    continue;
+                }
+            }
+            items.push((offset, dt.size(), vec![*field]));
+        }
+
+        let mut out = String::new();
+        let mut current = base;
+        let mut idx = 0;
+        while idx < items.len() {
+            // Items reaching into each other form one cluster.
+            let start = items[idx].0;
+            let mut end = start + items[idx].1;
+            let mut next = idx + 1;
+            while next < items.len() && items[next].0 < end {
+                end = end.max(items[next].0 + items[next].1);
+                next += 1;
+            }
+            if start > current {
+                // Padding of overlapping members shares the scope of the record, a counter keeps it unique.
+                let name = match depth {
+                    1 => format!("_pad_0x{:x}", current),
+                    _ => {
+                        self.pads += 1;
+                        format!("_pad_0x{:x}_{}", current, self.pads)
+                    }
+                };
+                let decl = format!("uint8_t {}[{}]", name, start - current);
+                out += &self.line(current, &decl, None, depth)?;
+            }
+            match next - idx {
+                1 => out += &self.item(&items[idx].2, depth)?,
+                _ => {
+                    let indent = "    ".repeat(depth);
+                    out += &format!("{}/* 0x{:04x} */ union {{\n", indent, start);
+                    for (offset, _, members) in &items[idx..next] {
+                        // Bitfields are written along with the bits around them, they need a structure of their own.
+                        if let [(_, _, dt)] = members.as_slice() {
+                            if *offset == start && !matches!(dt, InternalDT::Bitfield(..)) {
+                                out += &self.item(members, depth + 1)?;
+                                continue;
+                            }
+                        }
+                        out += &format!("{}    struct {{\n", indent);
+                        out += &self.struct_body(members, start, depth + 2)?;
+                        out += &format!("{}    }};\n", indent);
+                    }
+                    out += &format!("{}}};\n", indent);
+                }
+            }
+            current = current.max(end);
+            idx = next;
+        }
+        Ok(out)
+    }
+
+    fn item(&mut self, members: &[Field<'_>], depth: usize) -> crate::Result<String> {
+        let (name, offset, dt) = members[0];
+        match dt {
+            InternalDT::Bitfield(..) => {
+                let bits = members
+                    .iter()
+                    .filter_map(|(name, _, dt)| match dt {
+                        InternalDT::Bitfield(_, bit_offset, bit_width) => {
+                            Some(((*name).clone(), *bit_offset, *bit_width))
+                        }
+                        _ => None,
+                    })
+                    .collect::<Vec<_>>();
+                self.bitfield_unit(&bits, offset, dt, depth)
+            }
+            _ => self.member(name, offset, dt, depth),
+        }
+    }
+}
+
+/// Returns the C type of `dt`, i.e. `struct _LIST_ENTRY*`.
+pub(crate) fn type_name(py: Python<'_>, dt: &InternalDT, opts: DTOptions) -> crate::Result<String> {
+    HeaderWriter::new(py, opts).decl(dt, "")
+}
+
+/// Writes a C header defining `dt` and the records it uses, packed so offsets match byte for byte.
+pub fn to_c_header(py: Python<'_>, dt: &InternalDT, opts: DTOptions) -> crate::Result<String> {
+    let (InternalDT::Structure(class, _) | InternalDT::Union(class, _)) = dt else {
+        return Err(MemflowPyError::InvalidType(type_name(py, dt, opts)?));
+    };
+    let mut writer = HeaderWriter::new(py, opts);
+    writer.record_tag(dt)?;
+    writer.define(dt)?;
+    // Records only pointed to are defined after the ones `dt` depends on.
+    let mut idx = 0;
+    while idx < writer.records.len() {
+        let (_, record) = writer.records[idx].clone();
+        writer.define(&record)?;
+        idx += 1;
+    }
+
+    let mut out = format!(
+        "/* Layout of {} for the {} data model. */\n\n#include <stddef.h>\n#include <stdint.h>\n\n",
+        class_name(py, class)?,
+        opts.data_model
+    );
+    for (key, record) in &writer.records {
+        let kind = match record {
+            InternalDT::Union(..) => "union",
+            _ => "struct",
+        };
+        out += &format!("{} {};\n", kind, writer.names[key]);
+    }
+    for def in &writer.enums {
+        out += &format!("\n{}", def);
+    }
+    out += "\n#pragma pack(push, 1)\n";
+    for def in &writer.definitions {
+        out += &format!("\n{}", def);
+    }
+    out += "\n#pragma pack(pop)\n";
+    Ok(out)
+}
+
+/// Describes the fields of a record with their offset, size and C type, nested records are described in place.
+fn describe(
+    writer: &mut HeaderWriter<'_>,
+    name: &str,
+    offset: usize,
+    dt: &InternalDT,
+) -> crate::Result<PyObject> {
+    let py = writer.py;
+    let entry = PyDict::new(py);
+    entry.set_item("name", name)?;
+    entry.set_item("offset", offset)?;
+    entry.set_item("size", dt.size())?;
+    entry.set_item("type", writer.decl(dt, "")?)?;
+    if let InternalDT::Bitfield(unit_dt, bit_offset, bit_width) = dt {
+        entry.set_item(
+            "bit_offset",
+            declared_bit_offset(unit_dt, *bit_offset, *bit_width),
+        )?;
+        entry.set_item("bit_size", bit_width)?;
+    }
+    if let InternalDT::Structure(..) | InternalDT::Union(..) = dt {
+        let fields = PyList::empty(py);
+        for (name, offset, field_dt) in sorted_fields(dt) {
+            fields.append(describe(writer, name, offset, field_dt)?)?;
+        }
+        entry.set_item("fields", fields)?;
+    }
+    Ok(entry.into())
+}
+
+/// Writes the layout of `dt` as JSON, offsets of nested fields are relative to their record.
+pub fn to_layout_json(
+    py: Python<'_>,
+    dt: &InternalDT,
+    opts: DTOptions,
+    indent: Option<usize>,
+) -> crate::Result<String> {
+    let (InternalDT::Structure(class, _) | InternalDT::Union(class, _)) = dt else {
+        return Err(MemflowPyError::InvalidType(type_name(py, dt, opts)?));
+    };
+    let mut writer = HeaderWriter::new(py, opts);
+    let layout = describe(&mut writer, &class_name(py, class)?, 0, dt)?;
+    let kwargs = PyDict::new(py);
+    kwargs.set_item("indent", indent)?;
+    Ok(py
+        .import("json")?
+        .getattr("dumps")?
+        .call((layout,), Some(kwargs))?
+        .extract()?)
+}
+
+/// Returns the offset of the member at `path` (i.e. `field.sub[3].x`) from the start of `dt`.
+/// Bitfields resolve to the offset of their storage unit.
+pub fn offset_of(dt: &InternalDT, path: &str) -> crate::Result<usize> {
+    let invalid = |msg: String| MemflowPyError::InvalidFieldPath(path.to_owned(), msg);
+    let mut offset = 0;
+    let mut dt = dt;
+    let mut chars = path.trim().chars().peekable();
+    let mut first = true;
+    while let Some(c) = chars.next() {
+        match c {
+            '[' => {
+                let index: String = chars.by_ref().take_while(|c| *c != ']').collect();
+                let index: usize = index
+                    .trim()
+                    .parse()
+                    .map_err(|_| invalid(format!("`{}` is not a valid index", index.trim())))?;
+                dt = match dt {
+                    InternalDT::Array(_, elem, len) if index < *len as usize => elem,
+                    InternalDT::Array(_, _, len) => {
+                        return Err(invalid(format!(
+                            "index {} is out of bounds for length {}",
+                            index, len
+                        )))
+                    }
+                    // The length of a dynamic array is only known when reading.
+                    InternalDT::DynArray(_, elem, _) => elem,
+                    _ => {
+                        return Err(invalid(format!(
+                            "index {} of a type that is no array",
+                            index
+                        )))
+                    }
+                };
+                offset += index * dt.size();
+            }
+            // Only the first field can leave out the leading dot.
+            c if c == '.' || (first && (c.is_alphabetic() || c == '_')) => {
+                let mut name = match c {
+                    '.' => String::new(),
+                    c => c.to_string(),
+                };
+                while let Some(c) = chars.next_if(|c| c.is_alphanumeric() || *c == '_') {
+                    name.push(c);
+                }
+                offset += field(dt, &name).map_err(invalid)?;
+                dt = member(dt, &name);
+            }
+            c if c.is_whitespace() => continue,
+            c => return Err(invalid(format!("unexpected `{}`", c))),
+        }
+        first = false;
+    }
+    Ok(offset)
+}
+
+fn field(dt: &InternalDT, name: &str) -> Result<usize, String> {
+    match dt {
+        InternalDT::Structure(_, dts) | InternalDT::Union(_, dts) => dts
+            .get(name)
+            .map(|(offset, _)| *offset)
+            .ok_or_else(|| format!("no field `{}`", name)),
+        _ => Err(format!(
+            "field `{}` of a type that is no structure or union",
+            name
+        )),
+    }
+}
+
+fn member<'a>(dt: &'a InternalDT, name: &str) -> &'a InternalDT {
+    match dt {
+        InternalDT::Structure(_, dts) | InternalDT::Union(_, dts) => &dts[name].1,
+        _ => unreachable!(),
+    }
+}
+
+/// Returns the alignment a C compiler for the data model would give `dt`, fields are still laid out packed.
+pub fn align_of(dt: &InternalDT, data_model: DataModel) -> usize {
+    match dt {
+        InternalDT::Array(_, dt, _)
+        | InternalDT::DynArray(_, dt, _)
+        | InternalDT::BigEndian(dt)
+        | InternalDT::Enum(_, dt)
+        | InternalDT::Bitfield(dt, _, _) => align_of(dt, data_model),
+        InternalDT::Structure(_, dts) | InternalDT::Union(_, dts) => dts
+            .values()
+            .map(|(_, dt)| align_of(dt, data_model))
+            .max()
+            .unwrap_or(1),
+        dt => data_model.scalar_align(dt.size()),
+    }
+}
+
+/// Bit range a field covers within its record.
+pub(crate) fn bit_range(offset: usize, dt: &InternalDT) -> (usize, usize) {
+    match dt {
+        InternalDT::Bitfield(unit_dt, bit_offset, bit_width) => {
+            let start = offset * 8 + declared_bit_offset(unit_dt, *bit_offset, *bit_width) as usize;
+            (start, start + *bit_width as usize)
+        }
+        _ => (offset * 8, (offset + dt.size()) * 8),
+    }
+}
+
+/// Lists the fields of a record ordered by offset along with the padding holes between them.
+/// Fields sharing bits with others name them under `overlaps`.
+pub fn layout(py: Python<'_>, dt: &InternalDT, opts: DTOptions) -> crate::Result<PyObject> {
+    if !matches!(dt, InternalDT::Structure(..) | InternalDT::Union(..)) {
+        return Err(MemflowPyError::InvalidType(type_name(py, dt, opts)?));
+    }
+    let mut writer = HeaderWriter::new(py, opts);
+    let fields = sorted_fields(dt);
+    let ranges = fields
+        .iter()
+        .map(|(_, offset, dt)| bit_range(*offset, dt))
+        .collect::<Vec<_>>();
+
+    let table = PyList::empty(py);
+    let mut covered = 0;
+    for (idx, (name, offset, field_dt)) in fields.iter().enumerate() {
+        if *offset > covered {
+            let hole = PyDict::new(py);
+            hole.set_item("name", py.None())?;
+            hole.set_item("offset", covered)?;
+            hole.set_item("size", offset - covered)?;
+            hole.set_item("padding", true)?;
+            table.append(hole)?;
+        }
+        covered = covered.max(offset + field_dt.size());
+
+        let (start, end) = ranges[idx];
+        let overlaps = fields
+            .iter()
+            .zip(&ranges)
+            .enumerate()
+            .filter(|(other, (_, (other_start, other_end)))| {
+                *other != idx && *other_start < end && start < *other_end
+            })
+            .map(|(_, ((name, _, _), _))| name.as_str())
+            .collect::<Vec<_>>();
+
+        let entry = PyDict::new(py);
+        entry.set_item("name", name.as_str())?;
+        entry.set_item("offset", offset)?;
+        entry.set_item("size", field_dt.size())?;
+        entry.set_item("type", writer.decl(field_dt, "")?)?;
+        if let InternalDT::Bitfield(unit_dt, bit_offset, bit_width) = field_dt {
+            entry.set_item(
+                "bit_offset",
+                declared_bit_offset(unit_dt, *bit_offset, *bit_width),
+            )?;
+            entry.set_item("bit_size", bit_width)?;
+        }
+        entry.set_item("padding", false)?;
+        entry.set_item("overlaps", overlaps)?;
+        table.append(entry)?;
+    }
+    Ok(table.into())
+}
@@ -1,15 +1,36 @@
 use std::array::TryFromSliceError;
 
-use internal::InternalDT;
-use pyo3::{exceptions::PyException, prelude::*};
+use internal::{DataModel, InternalDT};
+use pyo3::{exceptions::PyException, prelude::*, types::PyDict};
 use thiserror::Error;
 
+// pyo3 0.20 expands some `#[pymethods]` into impls inside function bodies, which newer rustc lints against.
+// The lint is only silenced for the modules with such methods.
+pub(crate) mod cdecl;
 pub(crate) mod connector;
+#[allow(non_local_definitions)]
 pub(crate) mod dummy;
+pub(crate) mod dump;
+#[allow(non_local_definitions)]
+pub(crate) mod dwarf;
+pub(crate) mod float80;
+pub(crate) mod hexdump;
 pub(crate) mod internal;
+#[allow(non_local_definitions)]
 pub(crate) mod inventory;
+pub(crate) mod layout;
 pub(crate) mod os;
+#[allow(non_local_definitions)]
+pub(crate) mod pdb;
+#[allow(non_local_definitions)]
+pub(crate) mod pointer;
+#[allow(non_local_definitions)]
 pub(crate) mod process;
+pub(crate) mod strings;
+#[allow(non_local_definitions)]
+pub(crate) mod typelib;
+#[allow(non_local_definitions)]
+pub(crate) mod view;
 
 pub type Result<T> = std::result::Result<T, MemflowPyError>;
 
@@ -31,6 +52,52 @@ pub enum MemflowPyError {
     MissingCGlueImpl(String, String),
     #[error("The arch {0} is not valid")]
     InvalidArch(String),
+    #[error("Bitfield `{0}` must be an integer type with a bit width that fits its storage unit")]
+    InvalidBitfield(String),
+    #[error("Endianness `{0}` is not valid, expected `little` or `big`")]
+    InvalidEndianness(String),
+    #[error(
+        "Data model `{0}` is not valid, expected `ILP32`, `Win32`, `LLP64`, `LP64` or `Native`"
+    )]
+    InvalidDataModel(String),
+    #[error(
+        "Long double size `{0}` is not valid, expected 8 or at least the size of the extended format"
+    )]
+    InvalidLongDoubleSize(usize),
+    #[error("Long double format `{0}` is not valid, expected `x87` or `binary128`")]
+    InvalidLongDoubleFormat(String),
+    #[error("Encoding `{0}` is not supported")]
+    InvalidEncoding(String),
+    #[error("Error handler `{0}` is not valid, expected `strict`, `replace` or `ignore`")]
+    InvalidErrors(String),
+    #[error("Failed to decode string: {0}")]
+    InvalidString(String),
+    #[error("String of {0} bytes exceeds the maximum of {1} bytes")]
+    StringTooLong(usize, usize),
+    #[error("Value `{0}` is out of range for `{1}`")]
+    ValueOutOfRange(String, String),
+    #[error("Enum must be stored as an integer type, found type `{0}`")]
+    InvalidEnum(String),
+    #[error("Dynamic array `{0}` must end a structure that is read on its own and be sized by a preceding field")]
+    InvalidDynamicArray(String),
+    #[error("Failed to parse C declarations, line {0}: {1}")]
+    InvalidCDecl(usize, String),
+    #[error("Failed to parse PDB: {0}")]
+    InvalidPdb(String),
+    #[error("Failed to parse DWARF debug info: {0}")]
+    InvalidDwarf(String),
+    #[error("Invalid type expression `{0}`: {1}")]
+    InvalidTypeExpression(String, String),
+    #[error("Unknown type name `{0}`")]
+    UnknownTypeName(String),
+    #[error("Type name `{0}` is already registered to another type")]
+    TypeConflict(String),
+    #[error("Type `{0}` can not be written as a type expression")]
+    InexpressibleType(String),
+    #[error("Failed to load type library: {0}")]
+    InvalidTypeLibrary(String),
+    #[error("Invalid field path `{0}`: {1}")]
+    InvalidFieldPath(String, String),
 }
 
 impl From<MemflowPyError> for PyErr {
@@ -43,17 +110,84 @@ impl From<MemflowPyError> for PyErr {
 }
 
 #[pyfunction]
-fn sizeof(ty: PyObject) -> PyResult<usize> {
-    let dt: InternalDT = ty.try_into()?;
+fn sizeof(ty: PyObject, data_model: Option<&str>) -> PyResult<usize> {
+    let data_model = data_model.map(DataModel::try_from).transpose()?;
+    let dt = InternalDT::from_py(ty, data_model.unwrap_or_default().into())?;
     Ok(dt.size())
 }
 
+/// Returns the offset of a member, nested members are given as a path like `field.sub[3].x`.
+#[pyfunction]
+fn offsetof(ty: PyObject, path: &str, data_model: Option<&str>) -> PyResult<usize> {
+    let data_model = data_model.map(DataModel::try_from).transpose()?;
+    let dt = InternalDT::from_py(ty, data_model.unwrap_or_default().into())?;
+    Ok(layout::offset_of(&dt, path)?)
+}
+
+/// Returns the alignment C would require for the type, memflow itself lays out fields packed.
+#[pyfunction]
+fn alignof(ty: PyObject, data_model: Option<&str>) -> PyResult<usize> {
+    let data_model = data_model
+        .map(DataModel::try_from)
+        .transpose()?
+        .unwrap_or_default();
+    let dt = InternalDT::from_py(ty, data_model.into())?;
+    Ok(layout::align_of(&dt, data_model))
+}
+
+/// Returns the fields of a structure or union with their offset, size and C type, padding holes and overlaps.
+#[pyfunction]
+#[pyo3(name = "layout")]
+fn layout_of(py: Python<'_>, ty: PyObject, data_model: Option<&str>) -> PyResult<PyObject> {
+    let data_model = data_model.map(DataModel::try_from).transpose()?;
+    let opts = data_model.unwrap_or_default().into();
+    let dt = InternalDT::from_py(ty, opts)?;
+    Ok(layout::layout(py, &dt, opts)?)
+}
+
+/// Writes a C header with the layout of a structure or union, padding is explicit and offsets are noted per field.
+#[pyfunction]
+fn to_c_header(py: Python<'_>, ty: PyObject, data_model: Option<&str>) -> PyResult<String> {
+    let data_model = data_model.map(DataModel::try_from).transpose()?;
+    let opts = data_model.unwrap_or_default().into();
+    let dt = InternalDT::from_py(ty, opts)?;
+    Ok(layout::to_c_header(py, &dt, opts)?)
+}
+
+/// Writes the layout of a structure or union as JSON, listing the name, offset, size and C type of every field.
+#[pyfunction]
+#[pyo3(signature = (ty, data_model=None, indent=None))]
+fn to_layout_json(
+    py: Python<'_>,
+    ty: PyObject,
+    data_model: Option<&str>,
+    indent: Option<usize>,
+) -> PyResult<String> {
+    let data_model = data_model.map(DataModel::try_from).transpose()?;
+    let opts = data_model.unwrap_or_default().into();
+    let dt = InternalDT::from_py(ty, opts)?;
+    Ok(layout::to_layout_json(py, &dt, opts, indent)?)
+}
+
+/// Parses C declarations into types, keyed by their tag or typedef name.
+#[pyfunction]
+fn parse_c<'py>(py: Python<'py>, source: &str, data_model: Option<&str>) -> PyResult<&'py PyDict> {
+    let data_model = data_model.map(DataModel::try_from).transpose()?;
+    Ok(cdecl::parse(py, source, data_model.unwrap_or_default())?)
+}
+
 #[pymodule]
 #[pyo3(name = "memflow")]
 fn memflow_py(_py: Python<'_>, m: &PyModule) -> PyResult<()> {
     pyo3_log::init();
     dummy::register_dummy_module(_py, m)?;
     m.add_function(wrap_pyfunction!(sizeof, m)?)?;
+    m.add_function(wrap_pyfunction!(parse_c, m)?)?;
+    m.add_function(wrap_pyfunction!(offsetof, m)?)?;
+    m.add_function(wrap_pyfunction!(alignof, m)?)?;
+    m.add_function(wrap_pyfunction!(layout_of, m)?)?;
+    m.add_function(wrap_pyfunction!(to_c_header, m)?)?;
+    m.add_function(wrap_pyfunction!(to_layout_json, m)?)?;
     m.add_class::<inventory::PyInventory>()?;
     m.add_class::<process::PyProcess>()?;
     m.add_class::<process::PyProcessInfo>()?;
@@ -61,6 +195,12 @@ fn memflow_py(_py: Python<'_>, m: &PyModule) -> PyResult<()> {
     m.add_class::<process::PyArchitectureIdent>()?;
     m.add_class::<process::PyProcessState>()?;
     m.add_class::<os::PyOs>()?;
+    m.add_class::<pointer::PyPointer>()?;
+    m.add_class::<pdb::PyPdb>()?;
+    m.add_class::<dwarf::PyDwarf>()?;
+    m.add_class::<typelib::PyTypeLibrary>()?;
+    m.add_class::<dump::PyTypeDump>()?;
+    m.add_class::<view::PyView>()?;
     m.add_class::<process::PyProcess>()?;
     Ok(())
 }
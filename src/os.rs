@@ -4,17 +4,60 @@ use memflow::{
     types::umem,
 };
 use pyo3::{exceptions::PyException, prelude::*};
-use std::cell::RefCell;
+use std::cell::{RefCell, RefMut};
 
 use crate::{
-    internal::InternalDT,
+    dump::{self, PyTypeDump},
+    float80, hexdump,
+    internal::{BoundMemory, DTOptions, DataModel, InternalDT},
+    pointer::{self, PointerSource},
     process::{PyModuleInfo, PyProcess, PyProcessInfo},
+    strings::{self, DecodeErrors, WideEncoding},
+    typelib,
+    view::{PyView, ViewOwner},
     MemflowPyError,
 };
 
+/// Os instance with the data model override and whether the os plugin targets Windows.
 #[derive(Clone)]
 #[pyclass(name = "Os")]
-pub struct PyOs(RefCell<OsInstanceArcBox<'static>>);
+pub struct PyOs(RefCell<OsInstanceArcBox<'static>>, Option<DataModel>, bool);
+
+impl PyOs {
+    pub fn new(inst: OsInstanceArcBox<'static>, windows: bool) -> Self {
+        Self(RefCell::new(inst), None, windows)
+    }
+
+    /// Returns the explicitly set data model or derives it from the os architecture.
+    pub fn target_data_model(&self) -> DataModel {
+        self.1
+            .unwrap_or_else(|| DataModel::from_arch(self.0.borrow_mut().info().arch, self.2))
+    }
+
+    fn dt_options(&self) -> DTOptions {
+        // The os is borrowed once at a time, the struct literal would hold on to it while deriving the data model.
+        let extended = float80::Format::from_arch(self.0.borrow_mut().info().arch);
+        DTOptions {
+            extended,
+            ..self.target_data_model().into()
+        }
+    }
+
+    /// Os instance, shared with the views created from it.
+    pub fn memory(&self) -> PyResult<RefMut<'_, OsInstanceArcBox<'static>>> {
+        self.0
+            .try_borrow_mut()
+            .map_err(|e| PyException::new_err(e.to_string()))
+    }
+}
+
+/// Virtual memory of the os, not every os plugin provides one.
+pub fn memory_view<'a>(
+    os: &'a mut OsInstanceArcBox<'static>,
+) -> crate::Result<&'a mut (impl MemoryView + 'static)> {
+    os.as_mut_impl_memoryview()
+        .ok_or_else(|| MemflowPyError::MissingCGlueImpl("Os".to_owned(), "MemoryView".to_owned()))
+}
 
 #[pymethods]
 impl PyOs {
@@ -33,6 +76,18 @@ impl PyOs {
         self.0.borrow_mut().info().size
     }
 
+    #[getter]
+    fn data_model(&self) -> String {
+        self.target_data_model().to_string()
+    }
+
+    /// Sets the data model used for target dependent types, processes created afterwards inherit it.
+    #[setter]
+    fn set_data_model(&mut self, data_model: Option<&str>) -> PyResult<()> {
+        self.1 = data_model.map(DataModel::try_from).transpose()?;
+        Ok(())
+    }
+
     pub fn process_info_list(&mut self) -> PyResult<Vec<PyProcessInfo>> {
         Ok(self
             .0
@@ -46,24 +101,38 @@ impl PyOs {
 
     pub fn process_from_info(&mut self, info: PyProcessInfo) -> PyResult<PyProcess> {
         let t = self.0.borrow_mut().clone();
-        Ok(PyProcess::new(t.into_process_by_info(info.into()).unwrap()))
+        Ok(PyProcess::with_data_model(
+            t.into_process_by_info(info.into()).unwrap(),
+            self.1,
+            self.2,
+        ))
     }
 
     pub fn process_from_addr(&mut self, addr: umem) -> PyResult<PyProcess> {
         let t = self.0.borrow_mut().clone();
-        Ok(PyProcess::new(
+        Ok(PyProcess::with_data_model(
             t.into_process_by_address(addr.into()).unwrap(),
+            self.1,
+            self.2,
         ))
     }
 
     pub fn process_from_pid(&mut self, pid: Pid) -> PyResult<PyProcess> {
         let t = self.0.borrow_mut().clone();
-        Ok(PyProcess::new(t.into_process_by_pid(pid).unwrap()))
+        Ok(PyProcess::with_data_model(
+            t.into_process_by_pid(pid).unwrap(),
+            self.1,
+            self.2,
+        ))
     }
 
     pub fn process_from_name(&mut self, name: &str) -> PyResult<PyProcess> {
         let t = self.0.borrow_mut().clone();
-        Ok(PyProcess::new(t.into_process_by_name(name).unwrap()))
+        Ok(PyProcess::with_data_model(
+            t.into_process_by_name(name).unwrap(),
+            self.1,
+            self.2,
+        ))
     }
 
     fn module_info_list(&mut self) -> PyResult<Vec<PyModuleInfo>> {
@@ -86,89 +155,271 @@ impl PyOs {
             .into())
     }
 
-    fn read(&mut self, addr: umem, ty: PyObject) -> PyResult<PyObject> {
-        let dt: InternalDT = ty.try_into()?;
+    /// Reads `ty` at `addr`, pointers are dereferenced up to `follow` pointers deep.
+    #[pyo3(signature = (addr, ty, follow=None))]
+    fn read(
+        slf: &PyCell<Self>,
+        addr: umem,
+        ty: PyObject,
+        follow: Option<u32>,
+    ) -> PyResult<PyObject> {
+        // Resolved up front so following pointers sees the same type as the read.
+        let ty = typelib::resolve(slf.py(), ty)?;
+        let this = slf.try_borrow()?;
+        let opts = this.dt_options();
+        let dt = InternalDT::from_py(ty.clone_ref(slf.py()), opts)?;
+        let source = PointerSource::new(slf, "read", this.target_data_model())?;
+
+        let mut os = this.0.borrow_mut();
+        let mem = os.as_mut_impl_memoryview().ok_or_else(|| {
+            MemflowPyError::MissingCGlueImpl("Os".to_owned(), "MemoryView".to_owned())
+        })?;
+        let mut mem = BoundMemory::new(mem, source);
+        let bytes = dt.read_bytes(&mut mem, addr.into())?;
+
+        let value = dt.py_from_bytes(bytes, Some(&mut mem))?;
+        if let Some(depth) = follow {
+            pointer::follow(slf.py(), &ty, &dt, addr, &value, &mut mem, opts, depth)?;
+        }
+        Ok(value)
+    }
 
-        let bytes = self
-            .0
+    fn read_ptr(slf: &PyCell<Self>, ptr_inst: PyObject) -> PyResult<PyObject> {
+        let this = slf.try_borrow()?;
+        let addr: umem = Python::with_gil(|py| ptr_inst.getattr(py, "addr")?.extract(py))?;
+        let dt_options = this.dt_options();
+        let dt = Python::with_gil(|py| {
+            InternalDT::from_py(ptr_inst.getattr(py, "_type_")?, dt_options)
+        })?;
+        let source = PointerSource::new(slf, "read", this.target_data_model())?;
+
+        let mut os = this.0.borrow_mut();
+        let mem = os.as_mut_impl_memoryview().ok_or_else(|| {
+            MemflowPyError::MissingCGlueImpl("Os".to_owned(), "MemoryView".to_owned())
+        })?;
+        let mut mem = BoundMemory::new(mem, source);
+        let bytes = dt.read_bytes(&mut mem, addr.into())?;
+
+        Ok(dt.py_from_bytes(bytes, Some(&mut mem))?)
+    }
+
+    fn write(&mut self, addr: umem, ty: PyObject, value: PyObject) -> PyResult<()> {
+        let dt = InternalDT::from_py(ty, self.dt_options())?;
+
+        self.0
             .borrow_mut()
             .as_mut_impl_memoryview()
             .ok_or_else(|| {
                 MemflowPyError::MissingCGlueImpl("Os".to_owned(), "MemoryView".to_owned())
             })?
-            .read_raw(addr.into(), dt.size())
-            .map_err(|e| PyException::new_err(format!("failed to read bytes {}", e)))?;
+            .write_raw(addr.into(), &dt.py_to_bytes(value)?)
+            .map_err(|e| PyException::new_err(format!("failed to write bytes {}", e)))?;
 
-        Ok(dt.py_from_bytes(bytes)?)
+        Ok(())
     }
 
-    fn read_ptr(&mut self, ptr_inst: PyObject) -> PyResult<PyObject> {
-        let addr: umem = Python::with_gil(|py| ptr_inst.getattr(py, "addr")?.extract(py))?;
-        let dt: InternalDT = Python::with_gil(|py| ptr_inst.getattr(py, "_type_")?.try_into())?;
+    fn read_char_string(
+        &mut self,
+        addr: umem,
+        max_bytes: Option<usize>,
+        encoding: Option<&str>,
+        errors: Option<&str>,
+    ) -> PyResult<String> {
+        let mut os = self.0.borrow_mut();
+        let mem = os.as_mut_impl_memoryview().ok_or_else(|| {
+            MemflowPyError::MissingCGlueImpl("Os".to_owned(), "MemoryView".to_owned())
+        })?;
+        strings::read_char_string(
+            mem,
+            addr.into(),
+            max_bytes.unwrap_or(4096),
+            encoding,
+            errors,
+        )
+    }
 
-        let bytes = self
-            .0
+    fn read_wchar_string(
+        &mut self,
+        addr: umem,
+        max_bytes: Option<usize>,
+        encoding: Option<&str>,
+        errors: Option<&str>,
+    ) -> PyResult<String> {
+        let encoding = encoding.map(WideEncoding::try_from).transpose()?;
+        let errors = errors.map(DecodeErrors::try_from).transpose()?;
+
+        let mut os = self.0.borrow_mut();
+        let mem = os.as_mut_impl_memoryview().ok_or_else(|| {
+            MemflowPyError::MissingCGlueImpl("Os".to_owned(), "MemoryView".to_owned())
+        })?;
+        strings::read_wchar_string(
+            mem,
+            addr.into(),
+            max_bytes.unwrap_or(4096),
+            encoding.unwrap_or_default(),
+            errors.unwrap_or_default(),
+        )
+    }
+
+    /// Reads the text of a Windows `UNICODE_STRING` (length, maximum length and buffer pointer).
+    fn read_unicode_string(&mut self, addr: umem, errors: Option<&str>) -> PyResult<String> {
+        let pointer_size = self.target_data_model().pointer_size();
+        let errors = errors.map(DecodeErrors::try_from).transpose()?;
+        let mut os = self.0.borrow_mut();
+        strings::read_unicode_string(
+            memory_view(&mut os)?,
+            addr.into(),
+            pointer_size,
+            errors.unwrap_or_default(),
+        )
+    }
+
+    /// Dumps the fields of the structure `ty` at `addr` like the WinDbg `dt` command.
+    /// Nested structures and structures behind pointers are expanded while `depth` is above one.
+    #[pyo3(signature = (addr, ty, depth=1))]
+    fn dump_type(
+        &mut self,
+        py: Python<'_>,
+        addr: umem,
+        ty: PyObject,
+        depth: u32,
+    ) -> PyResult<PyTypeDump> {
+        let opts = self.dt_options();
+        let dt = InternalDT::from_py(ty, opts)?;
+        let mut os = self.0.borrow_mut();
+        Ok(dump::dump(
+            py,
+            &dt,
+            addr.into(),
+            memory_view(&mut os)?,
+            opts,
+            depth,
+        )?)
+    }
+
+    /// Returns a lazy view of `ty` at `addr`, fields are read and written on access.
+    fn view(slf: Py<Self>, py: Python<'_>, addr: umem, ty: PyObject) -> PyResult<PyView> {
+        let dt = InternalDT::from_py(ty, slf.borrow(py).dt_options())?;
+        Ok(PyView::new(ViewOwner::Os(slf), addr, dt))
+    }
+
+    #[pyo3(signature = (addr, value, encoding=None, errors=None, terminate=true, max_bytes=None))]
+    fn write_char_string(
+        &mut self,
+        addr: umem,
+        value: &str,
+        encoding: Option<&str>,
+        errors: Option<&str>,
+        terminate: bool,
+        max_bytes: Option<usize>,
+    ) -> PyResult<()> {
+        let bytes = strings::encode_char_string(value, encoding, errors, terminate, max_bytes)?;
+        self.0
             .borrow_mut()
             .as_mut_impl_memoryview()
             .ok_or_else(|| {
                 MemflowPyError::MissingCGlueImpl("Os".to_owned(), "MemoryView".to_owned())
             })?
-            .read_raw(addr.into(), dt.size())
-            .map_err(|e| PyException::new_err(format!("failed to read bytes {}", e)))?;
+            .write_raw(addr.into(), &bytes)
+            .map_err(|e| PyException::new_err(format!("failed to write bytes {}", e)))?;
 
-        Ok(dt.py_from_bytes(bytes)?)
+        Ok(())
     }
 
-    fn write(&mut self, addr: umem, ty: PyObject, value: PyObject) -> PyResult<()> {
-        let dt: InternalDT = ty.try_into()?;
-
+    #[pyo3(signature = (addr, value, encoding=None, terminate=true, max_bytes=None))]
+    fn write_wchar_string(
+        &mut self,
+        addr: umem,
+        value: &str,
+        encoding: Option<&str>,
+        terminate: bool,
+        max_bytes: Option<usize>,
+    ) -> PyResult<()> {
+        let encoding = encoding.map(WideEncoding::try_from).transpose()?;
+        let bytes = strings::encode_wchar_string(
+            value,
+            encoding.unwrap_or_default(),
+            terminate,
+            max_bytes,
+        )?;
         self.0
             .borrow_mut()
             .as_mut_impl_memoryview()
             .ok_or_else(|| {
                 MemflowPyError::MissingCGlueImpl("Os".to_owned(), "MemoryView".to_owned())
             })?
-            .write_raw(addr.into(), &dt.py_to_bytes(value)?)
+            .write_raw(addr.into(), &bytes)
             .map_err(|e| PyException::new_err(format!("failed to write bytes {}", e)))?;
 
         Ok(())
     }
 
-    fn phys_read(&mut self, addr: umem, ty: PyObject) -> PyResult<PyObject> {
-        let dt: InternalDT = ty.try_into()?;
-        let mut raw: Vec<u8> = vec![0; dt.size()];
+    /// Formats `len` bytes at the virtual address `addr` as a hexdump, bytes that can not be read show as `??`.
+    /// The fields of the structure `ty` label the bytes they cover, `len` defaults to its size.
+    /// `data_model` lays out `ty` for another data model than the one of the os.
+    #[pyo3(signature = (addr, len=None, ty=None, data_model=None))]
+    fn hexdump(
+        &mut self,
+        addr: umem,
+        len: Option<usize>,
+        ty: Option<PyObject>,
+        data_model: Option<&str>,
+    ) -> PyResult<String> {
+        let mut opts = self.dt_options();
+        if let Some(data_model) = data_model {
+            opts.data_model = DataModel::try_from(data_model)?;
+        }
+        let dt = ty.map(|ty| InternalDT::from_py(ty, opts)).transpose()?;
+        let len = hexdump::length(len, dt.as_ref())?;
+        let mut os = self.0.borrow_mut();
+        let mem = os.as_mut_impl_memoryview().ok_or_else(|| {
+            MemflowPyError::MissingCGlueImpl("Os".to_owned(), "MemoryView".to_owned())
+        })?;
+        Ok(hexdump::hexdump(mem, addr, len, dt.as_ref()))
+    }
 
-        self.0
-            .borrow_mut()
+    fn phys_read(slf: &PyCell<Self>, addr: umem, ty: PyObject) -> PyResult<PyObject> {
+        let this = slf.try_borrow()?;
+        let dt = InternalDT::from_py(ty, this.dt_options())?;
+        let source = PointerSource::new(slf, "phys_read", this.target_data_model())?;
+
+        let mut os = this.0.borrow_mut();
+        let mut view = os
             .as_mut_impl_physicalmemory()
             .ok_or_else(|| {
                 MemflowPyError::MissingCGlueImpl("Os".to_owned(), "PhysicalMemory".to_owned())
             })?
-            .phys_read_into(addr.into(), raw.as_mut_slice())
-            .map_err(|e| PyException::new_err(format!("failed to read bytes {}", e)))?;
+            .phys_view();
+        let mut mem = BoundMemory::new(&mut view, source);
+        let raw = dt.read_bytes(&mut mem, addr.into())?;
 
-        Ok(dt.py_from_bytes(raw)?)
+        Ok(dt.py_from_bytes(raw, Some(&mut mem))?)
     }
 
-    fn phys_read_ptr(&mut self, ptr_inst: PyObject) -> PyResult<PyObject> {
+    fn phys_read_ptr(slf: &PyCell<Self>, ptr_inst: PyObject) -> PyResult<PyObject> {
+        let this = slf.try_borrow()?;
         let addr: umem = Python::with_gil(|py| ptr_inst.getattr(py, "addr")?.extract(py))?;
-        let dt: InternalDT = Python::with_gil(|py| ptr_inst.getattr(py, "_type_")?.try_into())?;
-        let mut raw: Vec<u8> = vec![0; dt.size()];
-
-        self.0
-            .borrow_mut()
+        let dt_options = this.dt_options();
+        let dt = Python::with_gil(|py| {
+            InternalDT::from_py(ptr_inst.getattr(py, "_type_")?, dt_options)
+        })?;
+        let source = PointerSource::new(slf, "phys_read", this.target_data_model())?;
+
+        let mut os = this.0.borrow_mut();
+        let mut view = os
             .as_mut_impl_physicalmemory()
             .ok_or_else(|| {
                 MemflowPyError::MissingCGlueImpl("Os".to_owned(), "PhysicalMemory".to_owned())
             })?
-            .phys_read_into(addr.into(), raw.as_mut_slice())
-            .map_err(|e| PyException::new_err(format!("failed to read bytes {}", e)))?;
+            .phys_view();
+        let mut mem = BoundMemory::new(&mut view, source);
+        let raw = dt.read_bytes(&mut mem, addr.into())?;
 
-        Ok(dt.py_from_bytes(raw)?)
+        Ok(dt.py_from_bytes(raw, Some(&mut mem))?)
     }
 
     fn phys_write(&mut self, addr: umem, ty: PyObject, value: PyObject) -> PyResult<()> {
-        let dt: InternalDT = ty.try_into()?;
+        let dt = InternalDT::from_py(ty, self.dt_options())?;
 
         self.0
             .borrow_mut()
@@ -185,7 +436,7 @@ impl PyOs {
 
 impl From<OsInstanceArcBox<'static>> for PyOs {
     fn from(inst: OsInstanceArcBox<'static>) -> Self {
-        Self(RefCell::new(inst))
+        Self::new(inst, false)
     }
 }
 
@@ -0,0 +1,887 @@
+use std::collections::HashMap;
+
+use indexmap::IndexMap;
+use pyo3::{
+    exceptions::PyKeyError,
+    prelude::*,
+    types::{PyDict, PyList, PyModule, PyTuple},
+};
+
+use crate::{
+    cdecl::{self, Member},
+    MemflowPyError,
+};
+
+const MSF_MAGIC: &[u8] = b"Microsoft C/C++ MSF 7.00\r\n\x1aDS\0\0\0";
+
+/// Fixed stream indices of the PDB format.
+const TPI_STREAM: usize = 2;
+const DBI_STREAM: usize = 3;
+
+/// Index of the section header stream in the optional debug header of the DBI stream.
+const SECTION_HEADER_DBG_INDEX: usize = 5;
+
+const LF_VTSHAPE: u16 = 0x000a;
+const LF_MODIFIER: u16 = 0x1001;
+const LF_POINTER: u16 = 0x1002;
+const LF_PROCEDURE: u16 = 0x1008;
+const LF_MFUNCTION: u16 = 0x1009;
+const LF_ARGLIST: u16 = 0x1201;
+const LF_FIELDLIST: u16 = 0x1203;
+const LF_BITFIELD: u16 = 0x1205;
+const LF_BCLASS: u16 = 0x1400;
+const LF_VBCLASS: u16 = 0x1401;
+const LF_IVBCLASS: u16 = 0x1402;
+const LF_INDEX: u16 = 0x1404;
+const LF_VFUNCTAB: u16 = 0x1409;
+const LF_ENUMERATE: u16 = 0x1502;
+const LF_ARRAY: u16 = 0x1503;
+const LF_CLASS: u16 = 0x1504;
+const LF_STRUCTURE: u16 = 0x1505;
+const LF_UNION: u16 = 0x1506;
+const LF_ENUM: u16 = 0x1507;
+const LF_MEMBER: u16 = 0x150d;
+const LF_STMEMBER: u16 = 0x150e;
+const LF_METHOD: u16 = 0x150f;
+const LF_NESTTYPE: u16 = 0x1510;
+const LF_ONEMETHOD: u16 = 0x1511;
+const LF_INTERFACE: u16 = 0x1519;
+
+const S_LDATA32: u16 = 0x110c;
+const S_GDATA32: u16 = 0x110d;
+const S_PUB32: u16 = 0x110e;
+
+/// Forward reference flag of the record properties.
+const PROP_FWDREF: u16 = 0x80;
+/// Unique (decorated) name flag of the record properties.
+const PROP_HAS_UNIQUE_NAME: u16 = 0x200;
+
+fn invalid(msg: impl Into<String>) -> MemflowPyError {
+    MemflowPyError::InvalidPdb(msg.into())
+}
+
+/// Little endian cursor over a record.
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    fn bytes(&mut self, len: usize) -> crate::Result<&'a [u8]> {
+        let bytes = self
+            .data
+            .get(self.pos..self.pos + len)
+            .ok_or_else(|| invalid("unexpected end of record"))?;
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> crate::Result<u8> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn u16(&mut self) -> crate::Result<u16> {
+        Ok(u16::from_le_bytes(self.bytes(2)?.try_into()?))
+    }
+
+    fn u32(&mut self) -> crate::Result<u32> {
+        Ok(u32::from_le_bytes(self.bytes(4)?.try_into()?))
+    }
+
+    fn u64(&mut self) -> crate::Result<u64> {
+        Ok(u64::from_le_bytes(self.bytes(8)?.try_into()?))
+    }
+
+    /// Reads a numeric leaf, small values are stored inline while larger ones are prefixed by their kind.
+    fn numeric(&mut self) -> crate::Result<i128> {
+        let value = self.u16()?;
+        Ok(match value {
+            0..=0x7fff => value as i128,
+            0x8000 => self.u8()? as i8 as i128,
+            0x8001 => self.u16()? as i16 as i128,
+            0x8002 => self.u16()? as i128,
+            0x8003 => self.u32()? as i32 as i128,
+            0x8004 => self.u32()? as i128,
+            0x8009 => self.u64()? as i64 as i128,
+            0x800a => self.u64()? as i128,
+            kind => return Err(invalid(format!("unsupported numeric leaf {:#x}", kind))),
+        })
+    }
+
+    fn cstr(&mut self) -> crate::Result<String> {
+        let len = self.data[self.pos..]
+            .iter()
+            .position(|b| *b == 0)
+            .ok_or_else(|| invalid("unterminated name"))?;
+        let name = String::from_utf8_lossy(&self.data[self.pos..self.pos + len]).into_owned();
+        self.pos += len + 1;
+        Ok(name)
+    }
+
+    /// Skips the `LF_PAD` bytes aligning the members of a field list.
+    fn skip_padding(&mut self) {
+        while self.data.get(self.pos).is_some_and(|b| *b >= 0xf0) {
+            self.pos += 1;
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.pos >= self.data.len()
+    }
+}
+
+/// Multi-stream file container (MSF 7.0) holding the PDB streams.
+struct Msf {
+    data: Vec<u8>,
+    block_size: usize,
+    /// Size and blocks of every stream, `None` for nil streams.
+    streams: Vec<Option<(usize, Vec<u32>)>>,
+}
+
+impl Msf {
+    fn parse(data: Vec<u8>) -> crate::Result<Self> {
+        if !data.starts_with(MSF_MAGIC) {
+            return Err(invalid("missing MSF 7.00 signature"));
+        }
+        let mut header = Reader::new(&data[MSF_MAGIC.len()..]);
+        let block_size = header.u32()? as usize;
+        let _free_block_map = header.u32()?;
+        let _num_blocks = header.u32()?;
+        let directory_size = header.u32()? as usize;
+        let _unknown = header.u32()?;
+        let block_map = header.u32()? as usize;
+        if block_size == 0 {
+            return Err(invalid("block size of zero"));
+        }
+
+        let mut msf = Self {
+            data,
+            block_size,
+            streams: Vec::new(),
+        };
+        let directory_blocks = directory_size.div_ceil(block_size);
+        let mut block_map = Reader::new(msf.block(block_map)?);
+        let directory_blocks = (0..directory_blocks)
+            .map(|_| block_map.u32())
+            .collect::<crate::Result<Vec<_>>>()?;
+        let directory = msf.read_blocks(&directory_blocks, directory_size)?;
+
+        let mut directory = Reader::new(&directory);
+        let num_streams = directory.u32()? as usize;
+        let sizes = (0..num_streams)
+            .map(|_| directory.u32())
+            .collect::<crate::Result<Vec<_>>>()?;
+        for size in sizes {
+            let stream = match size {
+                u32::MAX => None,
+                size => {
+                    let size = size as usize;
+                    let blocks = (0..size.div_ceil(block_size))
+                        .map(|_| directory.u32())
+                        .collect::<crate::Result<Vec<_>>>()?;
+                    Some((size, blocks))
+                }
+            };
+            msf.streams.push(stream);
+        }
+        Ok(msf)
+    }
+
+    fn block(&self, idx: usize) -> crate::Result<&[u8]> {
+        self.data
+            .get(idx * self.block_size..(idx + 1) * self.block_size)
+            .ok_or_else(|| invalid(format!("block {} out of bounds", idx)))
+    }
+
+    fn read_blocks(&self, blocks: &[u32], size: usize) -> crate::Result<Vec<u8>> {
+        let mut bytes = Vec::with_capacity(blocks.len() * self.block_size);
+        for block in blocks {
+            bytes.extend_from_slice(self.block(*block as usize)?);
+        }
+        bytes.truncate(size);
+        Ok(bytes)
+    }
+
+    /// Returns the contents of the stream, `None` for nil or missing streams.
+    fn stream(&self, idx: usize) -> crate::Result<Option<Vec<u8>>> {
+        match self.streams.get(idx) {
+            Some(Some((size, blocks))) => Ok(Some(self.read_blocks(blocks, *size)?)),
+            _ => Ok(None),
+        }
+    }
+}
+
+/// Member of a field list.
+enum Field {
+    Member {
+        ty: u32,
+        offset: usize,
+        name: String,
+    },
+    Base {
+        ty: u32,
+        offset: usize,
+    },
+    VfTable {
+        ty: u32,
+    },
+    Enumerate {
+        value: i128,
+        name: String,
+    },
+}
+
+/// Structure, class or union record.
+struct RecordLeaf {
+    union: bool,
+    fields: u32,
+    size: usize,
+    name: String,
+    unique_name: Option<String>,
+    forward: bool,
+}
+
+/// Type records of the TPI stream, addressed by type index.
+struct TypeTable {
+    begin: u32,
+    data: Vec<u8>,
+    /// Kind and byte range of every record.
+    records: Vec<(u16, std::ops::Range<usize>)>,
+}
+
+impl TypeTable {
+    fn parse(data: Vec<u8>) -> crate::Result<Self> {
+        let mut header = Reader::new(&data);
+        let _version = header.u32()?;
+        let header_size = header.u32()? as usize;
+        let begin = header.u32()?;
+        let _end = header.u32()?;
+        let record_bytes = header.u32()? as usize;
+
+        let end = (header_size + record_bytes).min(data.len());
+        let mut records = Vec::new();
+        let mut pos = header_size;
+        while pos + 4 <= end {
+            let len = u16::from_le_bytes([data[pos], data[pos + 1]]) as usize;
+            // The length covers the kind, anything shorter would end the record before it starts.
+            if len < 2 {
+                return Err(invalid(format!("type record at {:#x} is too short", pos)));
+            }
+            let kind = u16::from_le_bytes([data[pos + 2], data[pos + 3]]);
+            let range = pos + 4..(pos + 2 + len).min(end);
+            records.push((kind, range));
+            pos += 2 + len;
+        }
+        Ok(Self {
+            begin,
+            data,
+            records,
+        })
+    }
+
+    fn record(&self, ti: u32) -> crate::Result<(u16, Reader<'_>)> {
+        let (kind, range) = ti
+            .checked_sub(self.begin)
+            .and_then(|idx| self.records.get(idx as usize))
+            .ok_or_else(|| invalid(format!("type index {:#x} out of range", ti)))?;
+        Ok((*kind, Reader::new(&self.data[range.clone()])))
+    }
+
+    fn kind(&self, ti: u32) -> Option<u16> {
+        self.record(ti).ok().map(|(kind, _)| kind)
+    }
+
+    fn record_leaf(&self, ti: u32) -> crate::Result<Option<RecordLeaf>> {
+        let (kind, mut r) = self.record(ti)?;
+        let union = match kind {
+            LF_CLASS | LF_STRUCTURE | LF_INTERFACE => false,
+            LF_UNION => true,
+            _ => return Ok(None),
+        };
+        let _count = r.u16()?;
+        let property = r.u16()?;
+        let fields = r.u32()?;
+        if !union {
+            let _derived = r.u32()?;
+            let _vshape = r.u32()?;
+        }
+        let size = r.numeric()? as usize;
+        let name = r.cstr()?;
+        let unique_name = match property & PROP_HAS_UNIQUE_NAME {
+            0 => None,
+            _ => r.cstr().ok(),
+        };
+        Ok(Some(RecordLeaf {
+            union,
+            fields,
+            size,
+            name,
+            unique_name,
+            forward: property & PROP_FWDREF != 0,
+        }))
+    }
+
+    /// Returns the underlying type, field list, name, unique name and forward reference flag of an enum.
+    #[allow(clippy::type_complexity)]
+    fn enum_leaf(&self, ti: u32) -> crate::Result<(u32, u32, String, Option<String>, bool)> {
+        let (_, mut r) = self.record(ti)?;
+        let _count = r.u16()?;
+        let property = r.u16()?;
+        let underlying = r.u32()?;
+        let fields = r.u32()?;
+        let name = r.cstr()?;
+        let unique_name = match property & PROP_HAS_UNIQUE_NAME {
+            0 => None,
+            _ => r.cstr().ok(),
+        };
+        Ok((
+            underlying,
+            fields,
+            name,
+            unique_name,
+            property & PROP_FWDREF != 0,
+        ))
+    }
+
+    /// Reads a field list, following `LF_INDEX` continuations. Members after an unknown kind are dropped.
+    fn fields(&self, mut ti: u32) -> crate::Result<Vec<Field>> {
+        let mut fields = Vec::new();
+        while ti != 0 {
+            let (kind, mut r) = self.record(ti)?;
+            if kind != LF_FIELDLIST {
+                return Err(invalid(format!("type {:#x} is not a field list", ti)));
+            }
+            ti = 0;
+            while !r.is_empty() {
+                match r.u16()? {
+                    LF_MEMBER => {
+                        let _attr = r.u16()?;
+                        let ty = r.u32()?;
+                        let offset = r.numeric()? as usize;
+                        let name = r.cstr()?;
+                        fields.push(Field::Member { ty, offset, name });
+                    }
+                    LF_ENUMERATE => {
+                        let _attr = r.u16()?;
+                        let value = r.numeric()?;
+                        let name = r.cstr()?;
+                        fields.push(Field::Enumerate { value, name });
+                    }
+                    LF_BCLASS => {
+                        let _attr = r.u16()?;
+                        let ty = r.u32()?;
+                        let offset = r.numeric()? as usize;
+                        fields.push(Field::Base { ty, offset });
+                    }
+                    LF_VBCLASS | LF_IVBCLASS => {
+                        let _attr = r.u16()?;
+                        let _base = r.u32()?;
+                        let _vbptr = r.u32()?;
+                        let _vbptr_offset = r.numeric()?;
+                        let _vbtable_offset = r.numeric()?;
+                    }
+                    LF_VFUNCTAB => {
+                        let _pad = r.u16()?;
+                        fields.push(Field::VfTable { ty: r.u32()? });
+                    }
+                    LF_STMEMBER => {
+                        let _attr = r.u16()?;
+                        let _ty = r.u32()?;
+                        r.cstr()?;
+                    }
+                    LF_METHOD => {
+                        let _count = r.u16()?;
+                        let _method_list = r.u32()?;
+                        r.cstr()?;
+                    }
+                    LF_ONEMETHOD => {
+                        let attr = r.u16()?;
+                        let _ty = r.u32()?;
+                        // Introducing virtual methods carry their vtable offset.
+                        if matches!((attr >> 2) & 7, 4 | 6) {
+                            let _vtable_offset = r.u32()?;
+                        }
+                        r.cstr()?;
+                    }
+                    LF_NESTTYPE => {
+                        let _pad = r.u16()?;
+                        let _ty = r.u32()?;
+                        r.cstr()?;
+                    }
+                    LF_INDEX => {
+                        let _pad = r.u16()?;
+                        ti = r.u32()?;
+                    }
+                    _ => break,
+                }
+                r.skip_padding();
+            }
+        }
+        Ok(fields)
+    }
+}
+
+/// Reads the public and global data symbols as RVAs, publics take precedence.
+fn read_symbols(msf: &Msf) -> crate::Result<IndexMap<String, u32>> {
+    let mut symbols = IndexMap::new();
+    let Some(dbi) = msf.stream(DBI_STREAM)? else {
+        return Ok(symbols);
+    };
+    let mut header = Reader::new(&dbi);
+    header.bytes(20)?;
+    let sym_record_stream = header.u16()? as usize;
+    let _pdb_dll_rbld = header.u16()?;
+    // Substreams in front of the optional debug header.
+    let mut dbg_header_offset = 64;
+    for _ in 0..5 {
+        dbg_header_offset += header.u32()? as usize;
+    }
+    let _mfc_type_server = header.u32()?;
+    let dbg_header_size = header.u32()? as usize;
+    dbg_header_offset += header.u32()? as usize;
+
+    let section_stream = dbi
+        .get(dbg_header_offset..dbg_header_offset + dbg_header_size)
+        .and_then(|dbg_header| {
+            let offset = SECTION_HEADER_DBG_INDEX * 2;
+            dbg_header.get(offset..offset + 2)
+        })
+        .map(|idx| u16::from_le_bytes([idx[0], idx[1]]) as usize);
+    let sections = match section_stream {
+        Some(idx) => msf
+            .stream(idx)?
+            .unwrap_or_default()
+            .chunks_exact(40)
+            .map(|header| u32::from_le_bytes(header[12..16].try_into().unwrap()))
+            .collect(),
+        None => Vec::new(),
+    };
+
+    let Some(records) = msf.stream(sym_record_stream)? else {
+        return Ok(symbols);
+    };
+    let mut pos = 0;
+    while pos + 4 <= records.len() {
+        let len = u16::from_le_bytes([records[pos], records[pos + 1]]) as usize;
+        let kind = u16::from_le_bytes([records[pos + 2], records[pos + 3]]);
+        let mut r = Reader::new(&records[pos + 4..(pos + 2 + len).min(records.len())]);
+        pos += 2 + len;
+        if !matches!(kind, S_PUB32 | S_GDATA32 | S_LDATA32) {
+            continue;
+        }
+        // Public symbols start with flags, data symbols with their type, followed by the same layout.
+        let _flags_or_type = r.u32()?;
+        let offset = r.u32()?;
+        let segment = r.u16()? as usize;
+        let name = r.cstr()?;
+        let Some(addr) = segment
+            .checked_sub(1)
+            .and_then(|idx| sections.get(idx))
+            .and_then(|section| section.checked_add(offset))
+        else {
+            continue;
+        };
+        symbols.entry(name).or_insert(addr);
+    }
+    Ok(symbols)
+}
+
+/// Types and symbols of a PDB file, types are generated on first access.
+#[pyclass(name = "Pdb")]
+pub struct PyPdb {
+    types: TypeTable,
+    /// Type index of the definition of every structure, union and enum by name.
+    names: IndexMap<String, u32>,
+    unique_names: HashMap<String, u32>,
+    symbols: IndexMap<String, u32>,
+    classes: HashMap<u32, PyObject>,
+    pointer_types: HashMap<(&'static str, usize), PyObject>,
+}
+
+impl PyPdb {
+    fn parse(data: Vec<u8>) -> crate::Result<Self> {
+        let msf = Msf::parse(data)?;
+        let types = TypeTable::parse(
+            msf.stream(TPI_STREAM)?
+                .ok_or_else(|| invalid("missing TPI stream"))?,
+        )?;
+        let mut names = IndexMap::new();
+        let mut unique_names = HashMap::new();
+        for idx in 0..types.records.len() {
+            let ti = types.begin + idx as u32;
+            let (name, unique_name, forward) = match types.kind(ti) {
+                Some(LF_ENUM) => {
+                    let (_, _, name, unique_name, forward) = types.enum_leaf(ti)?;
+                    (name, unique_name, forward)
+                }
+                _ => match types.record_leaf(ti)? {
+                    Some(record) => (record.name, record.unique_name, record.forward),
+                    None => continue,
+                },
+            };
+            if forward {
+                continue;
+            }
+            if let Some(unique_name) = unique_name {
+                unique_names.entry(unique_name).or_insert(ti);
+            }
+            names.entry(name).or_insert(ti);
+        }
+        let symbols = read_symbols(&msf)?;
+        Ok(Self {
+            types,
+            names,
+            unique_names,
+            symbols,
+            classes: HashMap::new(),
+            pointer_types: HashMap::new(),
+        })
+    }
+
+    /// Resolves forward references to the definition, types never defined stay as they are.
+    fn definition(&self, ti: u32) -> crate::Result<u32> {
+        let (name, unique_name, forward) = match self.types.kind(ti) {
+            Some(LF_ENUM) => {
+                let (_, _, name, unique_name, forward) = self.types.enum_leaf(ti)?;
+                (name, unique_name, forward)
+            }
+            _ => match self.types.record_leaf(ti)? {
+                Some(record) => (record.name, record.unique_name, record.forward),
+                None => return Ok(ti),
+            },
+        };
+        if !forward {
+            return Ok(ti);
+        }
+        let definition = unique_name
+            .and_then(|unique_name| self.unique_names.get(&unique_name))
+            .or_else(|| self.names.get(&name));
+        Ok(definition.copied().unwrap_or(ti))
+    }
+
+    /// Strips `const` and `volatile` modifiers.
+    fn unmodified(&self, mut ti: u32) -> crate::Result<u32> {
+        while ti >= self.types.begin && self.types.kind(ti) == Some(LF_MODIFIER) {
+            ti = self.types.record(ti)?.1.u32()?;
+        }
+        Ok(ti)
+    }
+
+    /// Returns the python type and size of a type index.
+    fn build(&mut self, memflow: &PyModule, ti: u32) -> crate::Result<(PyObject, usize)> {
+        let py = memflow.py();
+        if ti < self.types.begin {
+            return self.primitive(memflow, ti);
+        }
+        let (kind, mut r) = self.types.record(ti)?;
+        match kind {
+            LF_MODIFIER => {
+                let inner = r.u32()?;
+                self.build(memflow, inner)
+            }
+            LF_POINTER => {
+                let referent = r.u32()?;
+                let attrs = r.u32()?;
+                let size = ((attrs >> 13) & 0x3f) as usize;
+                match (attrs >> 5) & 7 {
+                    // Pointers to members are stored as opaque bytes, their layout depends on the class.
+                    2 | 3 => Ok((
+                        memflow
+                            .getattr("ARRAY")?
+                            .call1((memflow.getattr("c_uint8")?, size))?
+                            .into(),
+                        size,
+                    )),
+                    _ => Ok((self.pointer_to(memflow, referent, size)?, size)),
+                }
+            }
+            LF_ARRAY => {
+                let elem = r.u32()?;
+                let _index = r.u32()?;
+                let size = r.numeric()? as usize;
+                let (obj, elem_size) = self.build(memflow, elem)?;
+                let len = size.checked_div(elem_size).unwrap_or(0);
+                Ok((memflow.getattr("ARRAY")?.call1((obj, len))?.into(), size))
+            }
+            LF_CLASS | LF_STRUCTURE | LF_INTERFACE | LF_UNION => self.record_class(memflow, ti),
+            LF_ENUM => self.enum_class(memflow, ti),
+            LF_PROCEDURE | LF_MFUNCTION => Err(invalid("functions can only be pointed to")),
+            kind => Err(invalid(format!(
+                "unsupported type record {:#x} at {:#x}",
+                kind, ti
+            ))),
+        }
+        .map(|(obj, size)| (obj.clone_ref(py), size))
+    }
+
+    fn primitive(&mut self, memflow: &PyModule, ti: u32) -> crate::Result<(PyObject, usize)> {
+        match ti & 0x0f00 {
+            0 => {}
+            0x0400 => return Ok((self.pointer_to(memflow, ti & 0xff, 4)?, 4)),
+            0x0600 => return Ok((self.pointer_to(memflow, ti & 0xff, 8)?, 8)),
+            mode => return Err(invalid(format!("unsupported pointer mode {:#x}", mode))),
+        }
+        let (name, size) = match ti {
+            0x10 | 0x68 => ("c_int8", 1),
+            0x20 | 0x69 | 0x7c => ("c_uint8", 1),
+            0x70 => ("c_char", 1),
+            // Wide characters are UTF-16 on Windows.
+            0x71 | 0x7a => ("c_wchar", 2),
+            0x7b => ("c_uint32", 4),
+            0x11 | 0x72 => ("c_int16", 2),
+            0x21 | 0x73 => ("c_uint16", 2),
+            0x08 | 0x12 | 0x74 => ("c_int32", 4),
+            0x22 | 0x75 => ("c_uint32", 4),
+            0x13 | 0x76 => ("c_int64", 8),
+            0x23 | 0x77 => ("c_uint64", 8),
+            0x40 => ("c_float", 4),
+            0x41 => ("c_double", 8),
+            0x42 => ("c_float80", 10),
+            0x30 => ("c_bool", 1),
+            0x31 => ("c_uint16", 2),
+            0x32 => ("c_uint32", 4),
+            0x33 => ("c_uint64", 8),
+            0x03 => return Err(invalid("`void` can only be pointed to")),
+            ti => return Err(invalid(format!("unsupported primitive type {:#x}", ti))),
+        };
+        Ok((memflow.getattr(name)?.into(), size))
+    }
+
+    fn pointer_to(
+        &mut self,
+        memflow: &PyModule,
+        referent: u32,
+        size: usize,
+    ) -> crate::Result<PyObject> {
+        let py = memflow.py();
+        let referent = self.unmodified(referent)?;
+        let kind = match referent < self.types.begin {
+            true => None,
+            false => self.types.kind(referent),
+        };
+        match (referent, kind) {
+            // Character pointers are strings, followed on demand like ctypes does.
+            (0x70, _) => self.pointer_type(memflow, "c_char_p", "c_char_p", size),
+            (0x71, _) => self.pointer_type(memflow, "c_wchar_p", "c_wchar_p", size),
+            (0x03, _) | (_, Some(LF_VTSHAPE)) => {
+                self.pointer_type(memflow, "c_void_p", "Pointer", size)
+            }
+            (_, Some(LF_PROCEDURE | LF_MFUNCTION)) => {
+                let (_, mut r) = self.types.record(referent)?;
+                let ret = r.u32()?;
+                let args = match kind {
+                    Some(LF_MFUNCTION) => {
+                        let _class = r.u32()?;
+                        let _this = r.u32()?;
+                        let _call = r.u8()?;
+                        let _attrs = r.u8()?;
+                        let _count = r.u16()?;
+                        r.u32()?
+                    }
+                    _ => {
+                        let _call = r.u8()?;
+                        let _attrs = r.u8()?;
+                        let _count = r.u16()?;
+                        r.u32()?
+                    }
+                };
+                let restype = match self.unmodified(ret)? {
+                    0x03 => py.None(),
+                    ret => self.build(memflow, ret)?.0,
+                };
+                let mut fn_args = vec![restype];
+                let mut arg_types = Vec::new();
+                if let Ok((LF_ARGLIST, mut r)) = self.types.record(args) {
+                    let count = r.u32()?;
+                    for _ in 0..count {
+                        arg_types.push(r.u32()?);
+                    }
+                }
+                // Variadic arguments are marked by a type index of zero.
+                for arg in arg_types.into_iter().filter(|arg| *arg != 0) {
+                    fn_args.push(self.build(memflow, arg)?.0);
+                }
+                let kwargs = PyDict::new(py);
+                kwargs.set_item("byteness", size)?;
+                Ok(memflow
+                    .getattr("CFUNCTYPE")?
+                    .call(PyTuple::new(py, fn_args), Some(kwargs))?
+                    .into())
+            }
+            _ => {
+                let (target, _) = self.build(memflow, referent)?;
+                Ok(memflow.getattr("POINTER")?.call1((target, size))?.into())
+            }
+        }
+    }
+
+    /// Subclass of `base` pinned to the pointer width.
+    fn pointer_type(
+        &mut self,
+        memflow: &PyModule,
+        name: &'static str,
+        base: &str,
+        size: usize,
+    ) -> crate::Result<PyObject> {
+        let py = memflow.py();
+        if let Some(obj) = self.pointer_types.get(&(name, size)) {
+            return Ok(obj.clone_ref(py));
+        }
+        let attrs = PyDict::new(py);
+        attrs.set_item("_byteness_", size)?;
+        let obj = cdecl::new_class(memflow, name, base, attrs)?;
+        self.pointer_types.insert((name, size), obj.clone_ref(py));
+        Ok(obj)
+    }
+
+    fn record_class(&mut self, memflow: &PyModule, ti: u32) -> crate::Result<(PyObject, usize)> {
+        let py = memflow.py();
+        let ti = self.definition(ti)?;
+        let record = self
+            .types
+            .record_leaf(ti)?
+            .ok_or_else(|| invalid(format!("type {:#x} is not a record", ti)))?;
+        if let Some(obj) = self.classes.get(&ti) {
+            return Ok((obj.clone_ref(py), record.size));
+        }
+        // The class is registered before its fields so pointers back to it resolve.
+        let base = if record.union { "Union" } else { "Structure" };
+        let obj = cdecl::new_class(memflow, &record.name, base, PyDict::new(py))?;
+        self.classes.insert(ti, obj.clone_ref(py));
+
+        let fields = match record.forward {
+            // Types never defined are opaque.
+            true => Vec::new(),
+            false => self.types.fields(record.fields)?,
+        };
+        let mut members = Vec::new();
+        for field in fields {
+            members.extend(self.member(memflow, field)?);
+        }
+        let (fields, offsets) = match record.union {
+            true => (
+                cdecl::union_members(memflow, members, record.size)?,
+                Vec::new(),
+            ),
+            false => cdecl::struct_members(memflow, members, record.size)?,
+        };
+        obj.setattr(py, "_fields_", PyList::new(py, fields))?;
+        if !offsets.is_empty() {
+            obj.setattr(py, "_offsets_", PyList::new(py, offsets))?;
+        }
+        Ok((obj, record.size))
+    }
+
+    /// Resolves a member into its name, type and, for bitfields, the bit width and bit offset.
+    fn member(&mut self, memflow: &PyModule, field: Field) -> crate::Result<Option<Member>> {
+        let (name, ty, offset) = match field {
+            Field::Member { ty, offset, name } => (name, ty, offset),
+            Field::Base { ty, offset } => {
+                let name = match self.types.record_leaf(ty)? {
+                    Some(record) => record.name,
+                    None => format!("_base_0x{:x}", offset),
+                };
+                (name, ty, offset)
+            }
+            Field::VfTable { ty } => ("__vfptr".to_owned(), ty, 0),
+            Field::Enumerate { .. } => return Ok(None),
+        };
+        if let Ok((LF_BITFIELD, mut r)) = self.types.record(ty) {
+            let unit = r.u32()?;
+            let bits = r.u8()? as u32;
+            let position = r.u8()? as u32;
+            let (ty, size) = self.build(memflow, unit)?;
+            return Ok(Some(Member {
+                name,
+                offset,
+                ty,
+                size,
+                bits: Some((bits, position)),
+            }));
+        }
+        let (ty, size) = self.build(memflow, ty)?;
+        Ok(Some(Member {
+            name,
+            offset,
+            ty,
+            size,
+            bits: None,
+        }))
+    }
+
+    fn enum_class(&mut self, memflow: &PyModule, ti: u32) -> crate::Result<(PyObject, usize)> {
+        let py = memflow.py();
+        let ti = self.definition(ti)?;
+        let (underlying, fields, name, _, forward) = self.types.enum_leaf(ti)?;
+        let (int_obj, size) = self.build(memflow, underlying)?;
+        if let Some(obj) = self.classes.get(&ti) {
+            return Ok((obj.clone_ref(py), size));
+        }
+        let members: Vec<(String, i128)> = match forward {
+            true => Vec::new(),
+            false => self
+                .types
+                .fields(fields)?
+                .into_iter()
+                .filter_map(|field| match field {
+                    Field::Enumerate { value, name } => Some((name, value)),
+                    _ => None,
+                })
+                .collect(),
+        };
+        let int_enum = py
+            .import("enum")?
+            .getattr("IntEnum")?
+            .call1((name.as_str(), members))?;
+        let obj: PyObject = memflow.getattr("ENUM")?.call1((int_enum, int_obj))?.into();
+        self.classes.insert(ti, obj.clone_ref(py));
+        Ok((obj, size))
+    }
+}
+
+#[pymethods]
+impl PyPdb {
+    /// Loads the PDB file at `path`.
+    #[new]
+    fn new(path: &str) -> PyResult<Self> {
+        let data = std::fs::read(path).map_err(|e| invalid(format!("{}: {}", path, e)))?;
+        Ok(Self::parse(data)?)
+    }
+
+    /// Names of the structures, unions and enums defined in the PDB.
+    fn type_names(&self) -> Vec<String> {
+        self.names.keys().cloned().collect()
+    }
+
+    /// Public and global data symbols mapped to their RVA, add the module base for the address.
+    #[getter]
+    fn symbols<'py>(&self, py: Python<'py>) -> PyResult<&'py PyDict> {
+        let symbols = PyDict::new(py);
+        for (name, rva) in &self.symbols {
+            symbols.set_item(name, rva)?;
+        }
+        Ok(symbols)
+    }
+
+    /// Returns the structure, union or enum type named `name`.
+    fn __getitem__(&mut self, py: Python<'_>, name: &str) -> PyResult<PyObject> {
+        let ti = *self
+            .names
+            .get(name)
+            .ok_or_else(|| PyKeyError::new_err(name.to_owned()))?;
+        let memflow = py.import("memflow")?;
+        Ok(self.build(memflow, ti)?.0)
+    }
+
+    fn __contains__(&self, name: &str) -> bool {
+        self.names.contains_key(name)
+    }
+
+    fn __len__(&self) -> usize {
+        self.names.len()
+    }
+}
@@ -0,0 +1,305 @@
+use std::collections::HashMap;
+
+use memflow::{
+    prelude::{CTup2, ReadData},
+    types::umem,
+};
+use pyo3::{
+    exceptions::{PyException, PyValueError},
+    prelude::*,
+};
+
+use crate::internal::{DTOptions, DataModel, InternalDT, MemorySource};
+
+/// Memory a pointer was read from, it is followed through the same `read` method.
+#[derive(Clone)]
+pub struct PointerSource {
+    /// Bound read method of the `Process`, `Os` or `Connector`, i.e. `process.read` or `connector.phys_read`.
+    read: PyObject,
+    data_model: DataModel,
+}
+
+impl PointerSource {
+    pub fn new(owner: &PyAny, read_method: &str, data_model: DataModel) -> PyResult<Self> {
+        Ok(Self {
+            read: owner.getattr(read_method)?.into(),
+            data_model,
+        })
+    }
+}
+
+/// Pointer to `_type_`, pointer arithmetic and indexing step by the size of the target type.
+///
+/// Pointers without a target type (i.e. `Pointer(addr)`) step by single bytes and can not be dereferenced.
+/// Pointers followed while reading (i.e. `process.read(addr, ty, follow=1)`) hold the target read along with them.
+#[pyclass(name = "Pointer", subclass)]
+pub struct PyPointer {
+    addr: umem,
+    source: Option<PointerSource>,
+    value: Option<PyObject>,
+}
+
+impl PyPointer {
+    pub fn bind(&mut self, source: PointerSource) {
+        self.source = Some(source);
+    }
+
+    fn target(slf: &PyCell<Self>) -> Option<PyObject> {
+        slf.getattr("_type_").ok().map(Into::into)
+    }
+
+    fn target_size(slf: &PyCell<Self>) -> PyResult<usize> {
+        let Some(target) = Self::target(slf) else {
+            return Ok(1);
+        };
+        let data_model = slf
+            .borrow()
+            .source
+            .as_ref()
+            .map(|source| source.data_model)
+            .unwrap_or_default();
+        Ok(InternalDT::from_py(target, data_model.into())?.size())
+    }
+
+    fn target_name(slf: &PyCell<Self>) -> PyResult<String> {
+        match Self::target(slf) {
+            Some(target) => target.getattr(slf.py(), "__name__")?.extract(slf.py()),
+            None => Ok("Pointer".to_owned()),
+        }
+    }
+
+    /// Reads the target type `idx` items past the address.
+    fn read_at(slf: &PyCell<Self>, idx: isize) -> PyResult<PyObject> {
+        let py = slf.py();
+        let target = Self::target(slf).ok_or_else(|| {
+            PyException::new_err("pointer without a target type can not be dereferenced")
+        })?;
+        let addr = Self::step(slf, idx)?;
+        let this = slf.borrow();
+        if addr == 0 {
+            return Err(PyException::new_err("null pointer can not be dereferenced"));
+        }
+        let source = this.source.as_ref().ok_or_else(|| {
+            PyException::new_err(format!(
+                "pointer {:#x} is not bound to the memory it was read from",
+                this.addr
+            ))
+        })?;
+        source.read.call1(py, (addr, target))
+    }
+
+    /// Returns the address `n` items away.
+    fn step(slf: &PyCell<Self>, n: isize) -> PyResult<umem> {
+        let size = Self::target_size(slf)? as i128;
+        let addr = slf.borrow().addr as i128 + n as i128 * size;
+        umem::try_from(addr).map_err(|_| PyValueError::new_err("addr should be a positive number"))
+    }
+}
+
+#[pymethods]
+impl PyPointer {
+    #[new]
+    fn new(addr: &PyAny) -> PyResult<Self> {
+        if addr.lt(0)? {
+            return Err(PyValueError::new_err("addr should be a positive number"));
+        }
+        Ok(Self {
+            addr: addr.extract()?,
+            source: None,
+            value: None,
+        })
+    }
+
+    #[getter]
+    fn addr(&self) -> umem {
+        self.addr
+    }
+
+    /// Object the pointer was read through (i.e. the `Process`), `None` for pointers created by hand.
+    #[getter]
+    fn source(&self, py: Python<'_>) -> PyResult<Option<PyObject>> {
+        self.source
+            .as_ref()
+            .map(|source| source.read.getattr(py, "__self__"))
+            .transpose()
+    }
+
+    fn is_null(&self) -> bool {
+        self.addr == 0
+    }
+
+    /// Reads the target type from the memory the pointer was read from, followed pointers return the target read along.
+    fn deref(slf: &PyCell<Self>) -> PyResult<PyObject> {
+        if let Some(value) = &slf.borrow().value {
+            return Ok(value.clone_ref(slf.py()));
+        }
+        Self::read_at(slf, 0)
+    }
+
+    /// Returns a pointer of the same type `n` items away.
+    fn offset(slf: &PyCell<Self>, n: isize) -> PyResult<PyObject> {
+        let addr = Self::step(slf, n)?;
+        let ptr = slf.get_type().call1((addr,))?.downcast::<PyCell<Self>>()?;
+        ptr.borrow_mut().source = slf.borrow().source.clone();
+        Ok(ptr.into())
+    }
+
+    fn __getitem__(slf: &PyCell<Self>, idx: isize) -> PyResult<PyObject> {
+        Self::read_at(slf, idx)
+    }
+
+    fn __add__(slf: &PyCell<Self>, n: isize) -> PyResult<PyObject> {
+        Self::offset(slf, n)
+    }
+
+    fn __sub__(slf: &PyCell<Self>, n: isize) -> PyResult<PyObject> {
+        Self::offset(slf, -n)
+    }
+
+    fn __bool__(&self) -> bool {
+        !self.is_null()
+    }
+
+    fn __eq__(&self, other: &PyAny) -> PyResult<bool> {
+        match other.extract::<PyRef<Self>>() {
+            Ok(other) => Ok(self.addr == other.addr),
+            Err(_) => Ok(other.extract::<umem>().is_ok_and(|addr| self.addr == addr)),
+        }
+    }
+
+    fn __hash__(&self) -> u64 {
+        self.addr
+    }
+
+    fn __str__(slf: &PyCell<Self>) -> PyResult<String> {
+        Ok(format!(
+            "{} @ {:#x}",
+            Self::target_name(slf)?,
+            slf.borrow().addr
+        ))
+    }
+
+    fn __repr__(slf: &PyCell<Self>) -> PyResult<String> {
+        Ok(format!(
+            "{}({:#x})",
+            Self::target_name(slf)?,
+            slf.borrow().addr
+        ))
+    }
+}
+
+/// Pointer found in a decoded object that is waiting to be followed.
+struct Pending {
+    ptr: Py<PyPointer>,
+    addr: umem,
+    target: PyObject,
+}
+
+/// Target of a pointer, identified by its address and type so cycles resolve to the same object.
+type TargetKey = (umem, usize);
+
+/// Follows the pointers of `root`, decoded from `ty` at `addr`, up to `depth` pointers deep.
+///
+/// The targets of each level are read in one batch. Null pointers and targets that can not be read are left
+/// unfollowed, `deref` reports them when accessed.
+#[allow(clippy::too_many_arguments)]
+pub fn follow(
+    py: Python<'_>,
+    ty: &PyObject,
+    dt: &InternalDT,
+    addr: umem,
+    root: &PyObject,
+    mem: &mut dyn MemorySource,
+    opts: DTOptions,
+    depth: u32,
+) -> crate::Result<()> {
+    let mut visited: HashMap<TargetKey, PyObject> = HashMap::new();
+    visited.insert((addr, ty.as_ptr() as usize), root.clone_ref(py));
+    let mut pending = Vec::new();
+    collect(root.as_ref(py), dt, &mut pending)?;
+
+    for _ in 0..depth {
+        if pending.is_empty() {
+            break;
+        }
+
+        // Every target is read once, even when several pointers share it.
+        let mut reads: Vec<(TargetKey, InternalDT)> = Vec::new();
+        for p in &pending {
+            let key = (p.addr, p.target.as_ptr() as usize);
+            if !visited.contains_key(&key) && !reads.iter().any(|(k, _)| *k == key) {
+                reads.push((key, InternalDT::from_py(p.target.clone_ref(py), opts)?));
+            }
+        }
+
+        let mut bufs: Vec<Vec<u8>> = reads.iter().map(|(_, dt)| vec![0; dt.size()]).collect();
+        let batched = {
+            let mut data: Vec<ReadData> = reads
+                .iter()
+                .zip(bufs.iter_mut())
+                .map(|(((addr, _), _), buf)| CTup2((*addr).into(), buf.as_mut_slice().into()))
+                .collect();
+            mem.read_raw_list(&mut data).is_ok()
+        };
+
+        let mut next = Vec::new();
+        for (((addr, class), dt), buf) in reads.into_iter().zip(bufs) {
+            // Failed batches are retried one by one, so a single bad pointer does not fail the others.
+            let bytes = match batched {
+                true => dt.read_tail(mem, addr.into(), buf),
+                false => dt.read_bytes(mem, addr.into()),
+            };
+            let Ok(bytes) = bytes else {
+                continue;
+            };
+            let value = dt.py_from_bytes(bytes, Some(&mut *mem))?;
+            collect(value.as_ref(py), &dt, &mut next)?;
+            visited.insert((addr, class), value);
+        }
+
+        for p in pending {
+            if let Some(value) = visited.get(&(p.addr, p.target.as_ptr() as usize)) {
+                p.ptr.borrow_mut(py).value = Some(value.clone_ref(py));
+            }
+        }
+        pending = next;
+    }
+    Ok(())
+}
+
+/// Gathers the typed, non null pointers of a decoded object.
+fn collect(obj: &PyAny, dt: &InternalDT, pending: &mut Vec<Pending>) -> crate::Result<()> {
+    match dt {
+        InternalDT::Pointer(_, _) => {
+            let Ok(ptr) = obj.downcast::<PyCell<PyPointer>>() else {
+                return Ok(());
+            };
+            let addr = ptr.borrow().addr;
+            if let (false, Some(target)) = (addr == 0, PyPointer::target(ptr)) {
+                pending.push(Pending {
+                    ptr: ptr.into(),
+                    addr,
+                    target,
+                });
+            }
+        }
+        InternalDT::BigEndian(dt) => collect(obj, dt, pending)?,
+        InternalDT::Structure(_, dts) => {
+            for (name, (_, dt)) in dts.iter().filter(|(_, (_, dt))| dt.has_pointers()) {
+                if let Ok(value) = obj.getattr(name.as_str()) {
+                    collect(value, dt, pending)?;
+                }
+            }
+        }
+        // Only arrays that can hold pointers are walked, the members of unions are left alone as the active one is unknown.
+        InternalDT::Array(_, item_dt, _) | InternalDT::DynArray(_, item_dt, _)
+            if item_dt.has_pointers() =>
+        {
+            for item in obj.iter()? {
+                collect(item?, item_dt, pending)?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
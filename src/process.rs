@@ -1,99 +1,209 @@
-use crate::{internal::InternalDT, MemflowPyError};
+use crate::{
+    dump::{self, PyTypeDump},
+    float80, hexdump,
+    internal::{BoundMemory, DTOptions, DataModel, InternalDT},
+    pointer::{self, PointerSource},
+    strings::{self, DecodeErrors, WideEncoding},
+    typelib,
+    view::{PyView, ViewOwner},
+    MemflowPyError,
+};
 use memflow::{
     prelude::{
-        ArchitectureIdent, IntoProcessInstanceArcBox, MemoryView, ModuleInfo, PartialError,
-        Process, ProcessInfo, ProcessState,
+        ArchitectureIdent, IntoProcessInstanceArcBox, MemoryView, ModuleInfo, Process, ProcessInfo,
+        ProcessState,
     },
-    types::{umem, Address},
+    types::umem,
 };
 use pyo3::{exceptions::PyException, prelude::*};
 
+/// Process instance with the data model override and whether it runs on a Windows target.
 #[derive(Clone)]
 #[pyclass(name = "Process")]
-pub struct PyProcess(IntoProcessInstanceArcBox<'static>);
+pub struct PyProcess(IntoProcessInstanceArcBox<'static>, Option<DataModel>, bool);
 
 impl PyProcess {
     pub fn new(inst: IntoProcessInstanceArcBox<'static>) -> Self {
-        Self(inst)
+        Self(inst, None, false)
+    }
+
+    /// Creates a process of an os, inheriting its data model override and target os.
+    pub fn with_data_model(
+        inst: IntoProcessInstanceArcBox<'static>,
+        data_model: Option<DataModel>,
+        windows: bool,
+    ) -> Self {
+        Self(inst, data_model, windows)
+    }
+
+    /// Returns the explicitly set data model or derives it from the process architecture.
+    pub fn target_data_model(&self) -> DataModel {
+        self.1
+            .unwrap_or_else(|| DataModel::from_arch(self.0.info().proc_arch, self.2))
+    }
+
+    /// Memory of the process, shared with the views created from it.
+    pub fn memory(&mut self) -> &mut IntoProcessInstanceArcBox<'static> {
+        &mut self.0
+    }
+
+    fn dt_options(&self) -> DTOptions {
+        DTOptions {
+            extended: float80::Format::from_arch(self.0.info().proc_arch),
+            ..self.target_data_model().into()
+        }
     }
 }
 
 #[pymethods]
 impl PyProcess {
-    fn read(&mut self, addr: umem, ty: PyObject) -> PyResult<PyObject> {
-        let dt: InternalDT = ty.try_into()?;
-
-        let bytes = self
-            .0
-            .read_raw(addr.into(), dt.size())
-            .map_err(|e| PyException::new_err(format!("failed to read bytes {}", e)))?;
+    #[getter]
+    fn data_model(&self) -> String {
+        self.target_data_model().to_string()
+    }
 
-        Ok(dt.py_from_bytes(bytes)?)
+    /// Overrides the data model used for target dependent types, `None` derives it from the process again.
+    #[setter]
+    fn set_data_model(&mut self, data_model: Option<&str>) -> PyResult<()> {
+        self.1 = data_model.map(DataModel::try_from).transpose()?;
+        Ok(())
     }
 
-    fn read_char_string(&mut self, addr: umem, max_bytes: Option<usize>) -> PyResult<String> {
-        let str = self
-            .0
-            .read_char_string_n(addr.into(), max_bytes.unwrap_or(4096))
-            .map_err(|e| {
-                PyException::new_err(format!("failed to read variable length string {}", e))
-            })?;
-
-        Ok(str)
-    }
-
-    fn read_wchar_string(&mut self, addr: umem, max_bytes: Option<usize>) -> PyResult<Vec<u16>> {
-        let mut read_wchar_string_n =
-            |addr: Address, n: usize| -> Result<Vec<u16>, PartialError<memflow::prelude::Error>> {
-                let mut buf = vec![0; std::cmp::min(32, n)];
-                let mut last_n = 0;
-                loop {
-                    let (_, right) = buf.split_at_mut(last_n);
-                    memflow::prelude::PartialResultExt::data_part(
-                        self.0.read_raw_into(addr + last_n, right),
-                    )?;
-                    if let Some((n, _)) = right.iter().enumerate().find(|(_, c)| **c == 0_u8) {
-                        buf.truncate(last_n + n);
-                        return Ok(buf
-                            .chunks_exact(2)
-                            .into_iter()
-                            .map(|a| u16::from_ne_bytes([a[0], a[1]]))
-                            .collect());
-                    }
-                    if buf.len() >= n {
-                        break;
-                    }
-                    last_n = buf.len();
-                    buf.extend((0..buf.len()).map(|_| 0));
-                }
-                Err(PartialError::Error(memflow::prelude::Error(
-                    memflow::prelude::ErrorOrigin::VirtualMemory,
-                    memflow::prelude::ErrorKind::OutOfBounds,
-                )))
-            };
+    /// Reads `ty` at `addr`, pointers are dereferenced up to `follow` pointers deep.
+    #[pyo3(signature = (addr, ty, follow=None))]
+    fn read(
+        slf: &PyCell<Self>,
+        addr: umem,
+        ty: PyObject,
+        follow: Option<u32>,
+    ) -> PyResult<PyObject> {
+        // Resolved up front so following pointers sees the same type as the read.
+        let ty = typelib::resolve(slf.py(), ty)?;
+        let mut this = slf.try_borrow_mut()?;
+        let opts = this.dt_options();
+        let dt = InternalDT::from_py(ty.clone_ref(slf.py()), opts)?;
+        let source = PointerSource::new(slf, "read", this.target_data_model())?;
+        let mut mem = BoundMemory::new(&mut this.0, source);
+
+        let bytes = dt.read_bytes(&mut mem, addr.into())?;
+
+        let value = dt.py_from_bytes(bytes, Some(&mut mem))?;
+        if let Some(depth) = follow {
+            pointer::follow(slf.py(), &ty, &dt, addr, &value, &mut mem, opts, depth)?;
+        }
+        Ok(value)
+    }
+
+    /// Dumps the fields of the structure `ty` at `addr` like the WinDbg `dt` command.
+    /// Nested structures and structures behind pointers are expanded while `depth` is above one.
+    #[pyo3(signature = (addr, ty, depth=1))]
+    fn dump_type(
+        &mut self,
+        py: Python<'_>,
+        addr: umem,
+        ty: PyObject,
+        depth: u32,
+    ) -> PyResult<PyTypeDump> {
+        let opts = self.dt_options();
+        let dt = InternalDT::from_py(ty, opts)?;
+        Ok(dump::dump(py, &dt, addr.into(), &mut self.0, opts, depth)?)
+    }
+
+    /// Formats `len` bytes at `addr` as a hexdump, bytes that can not be read show as `??`.
+    /// The fields of the structure `ty` label the bytes they cover, `len` defaults to its size.
+    /// `data_model` lays out `ty` for another data model than the one of the process.
+    #[pyo3(signature = (addr, len=None, ty=None, data_model=None))]
+    fn hexdump(
+        &mut self,
+        addr: umem,
+        len: Option<usize>,
+        ty: Option<PyObject>,
+        data_model: Option<&str>,
+    ) -> PyResult<String> {
+        let mut opts = self.dt_options();
+        if let Some(data_model) = data_model {
+            opts.data_model = DataModel::try_from(data_model)?;
+        }
+        let dt = ty.map(|ty| InternalDT::from_py(ty, opts)).transpose()?;
+        let len = hexdump::length(len, dt.as_ref())?;
+        Ok(hexdump::hexdump(&mut self.0, addr, len, dt.as_ref()))
+    }
+
+    /// Returns a lazy view of `ty` at `addr`, fields are read and written on access.
+    fn view(slf: Py<Self>, py: Python<'_>, addr: umem, ty: PyObject) -> PyResult<PyView> {
+        let dt = InternalDT::from_py(ty, slf.borrow(py).dt_options())?;
+        Ok(PyView::new(ViewOwner::Process(slf), addr, dt))
+    }
+
+    fn read_char_string(
+        &mut self,
+        addr: umem,
+        max_bytes: Option<usize>,
+        encoding: Option<&str>,
+        errors: Option<&str>,
+    ) -> PyResult<String> {
+        strings::read_char_string(
+            &mut self.0,
+            addr.into(),
+            max_bytes.unwrap_or(4096),
+            encoding,
+            errors,
+        )
+    }
 
-        let wide_str_buf: Vec<u16> = read_wchar_string_n(addr.into(), max_bytes.unwrap_or(4096))
-            .map_err(|e| {
-                PyException::new_err(format!("failed to read variable length wide string {}", e))
-            })?;
+    fn read_wchar_string(
+        &mut self,
+        addr: umem,
+        max_bytes: Option<usize>,
+        encoding: Option<&str>,
+        errors: Option<&str>,
+    ) -> PyResult<String> {
+        strings::read_wchar_string(
+            &mut self.0,
+            addr.into(),
+            max_bytes.unwrap_or(4096),
+            encoding
+                .map(WideEncoding::try_from)
+                .transpose()?
+                .unwrap_or_default(),
+            errors
+                .map(DecodeErrors::try_from)
+                .transpose()?
+                .unwrap_or_default(),
+        )
+    }
 
-        Ok(wide_str_buf)
+    /// Reads the text of a Windows `UNICODE_STRING` (length, maximum length and buffer pointer).
+    fn read_unicode_string(&mut self, addr: umem, errors: Option<&str>) -> PyResult<String> {
+        let pointer_size = self.target_data_model().pointer_size();
+        strings::read_unicode_string(
+            &mut self.0,
+            addr.into(),
+            pointer_size,
+            errors
+                .map(DecodeErrors::try_from)
+                .transpose()?
+                .unwrap_or_default(),
+        )
     }
 
-    fn read_ptr(&mut self, ptr_inst: PyObject) -> PyResult<PyObject> {
+    fn read_ptr(slf: &PyCell<Self>, ptr_inst: PyObject) -> PyResult<PyObject> {
+        let mut this = slf.try_borrow_mut()?;
         let addr: umem = Python::with_gil(|py| ptr_inst.getattr(py, "addr")?.extract(py))?;
-        let dt: InternalDT = Python::with_gil(|py| ptr_inst.getattr(py, "_type_")?.try_into())?;
+        let dt_options = this.dt_options();
+        let dt = Python::with_gil(|py| {
+            InternalDT::from_py(ptr_inst.getattr(py, "_type_")?, dt_options)
+        })?;
+        let source = PointerSource::new(slf, "read", this.target_data_model())?;
+        let mut mem = BoundMemory::new(&mut this.0, source);
 
-        let bytes = self
-            .0
-            .read_raw(addr.into(), dt.size())
-            .map_err(|e| PyException::new_err(format!("failed to read bytes {}", e)))?;
+        let bytes = dt.read_bytes(&mut mem, addr.into())?;
 
-        Ok(dt.py_from_bytes(bytes)?)
+        Ok(dt.py_from_bytes(bytes, Some(&mut mem))?)
     }
 
     fn write(&mut self, addr: umem, ty: PyObject, value: PyObject) -> PyResult<()> {
-        let dt: InternalDT = ty.try_into()?;
+        let dt = InternalDT::from_py(ty, self.dt_options())?;
 
         self.0
             .write_raw(addr.into(), &dt.py_to_bytes(value)?)
@@ -102,6 +212,47 @@ impl PyProcess {
         Ok(())
     }
 
+    #[pyo3(signature = (addr, value, encoding=None, errors=None, terminate=true, max_bytes=None))]
+    fn write_char_string(
+        &mut self,
+        addr: umem,
+        value: &str,
+        encoding: Option<&str>,
+        errors: Option<&str>,
+        terminate: bool,
+        max_bytes: Option<usize>,
+    ) -> PyResult<()> {
+        let bytes = strings::encode_char_string(value, encoding, errors, terminate, max_bytes)?;
+        self.0
+            .write_raw(addr.into(), &bytes)
+            .map_err(|e| PyException::new_err(format!("failed to write bytes {}", e)))?;
+
+        Ok(())
+    }
+
+    #[pyo3(signature = (addr, value, encoding=None, terminate=true, max_bytes=None))]
+    fn write_wchar_string(
+        &mut self,
+        addr: umem,
+        value: &str,
+        encoding: Option<&str>,
+        terminate: bool,
+        max_bytes: Option<usize>,
+    ) -> PyResult<()> {
+        let encoding = encoding.map(WideEncoding::try_from).transpose()?;
+        let bytes = strings::encode_wchar_string(
+            value,
+            encoding.unwrap_or_default(),
+            terminate,
+            max_bytes,
+        )?;
+        self.0
+            .write_raw(addr.into(), &bytes)
+            .map_err(|e| PyException::new_err(format!("failed to write bytes {}", e)))?;
+
+        Ok(())
+    }
+
     fn set_dtb(&mut self, dtb1: umem, dtb2: umem) -> PyResult<()> {
         self.0
             .set_dtb(dtb1.into(), dtb2.into())
@@ -142,6 +293,7 @@ pub struct PyProcessInfo(ProcessInfo);
 #[pymethods]
 impl PyProcessInfo {
     #[new]
+    #[allow(clippy::too_many_arguments)]
     fn new(
         address: umem,
         pid: u32,
@@ -0,0 +1,332 @@
+use memflow::{
+    prelude::{ErrorKind, ErrorOrigin, PartialError, PartialResultExt},
+    types::Address,
+};
+use pyo3::{
+    exceptions::PyException,
+    prelude::*,
+    types::{PyBytes, PyString},
+};
+
+use crate::{internal::MemorySource, MemflowPyError};
+
+/// Byte order of UTF-16 code units in target memory.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum WideEncoding {
+    #[default]
+    Utf16Le,
+    Utf16Be,
+}
+
+impl WideEncoding {
+    fn decode_unit(&self, unit: [u8; 2]) -> u16 {
+        match self {
+            WideEncoding::Utf16Le => u16::from_le_bytes(unit),
+            WideEncoding::Utf16Be => u16::from_be_bytes(unit),
+        }
+    }
+
+    fn encode_unit(&self, unit: u16) -> [u8; 2] {
+        match self {
+            WideEncoding::Utf16Le => unit.to_le_bytes(),
+            WideEncoding::Utf16Be => unit.to_be_bytes(),
+        }
+    }
+}
+
+impl TryFrom<&str> for WideEncoding {
+    type Error = MemflowPyError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value.to_ascii_lowercase().replace('_', "-").as_str() {
+            "utf-16" | "utf-16-le" | "utf-16le" => Ok(Self::Utf16Le),
+            "utf-16-be" | "utf-16be" => Ok(Self::Utf16Be),
+            _ => Err(MemflowPyError::InvalidEncoding(value.to_owned())),
+        }
+    }
+}
+
+/// Handling of malformed text, named after the python codec error handlers.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DecodeErrors {
+    Strict,
+    /// Malformed sequences become U+FFFD.
+    #[default]
+    Replace,
+    /// Malformed sequences are dropped.
+    Ignore,
+}
+
+impl TryFrom<&str> for DecodeErrors {
+    type Error = MemflowPyError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "strict" => Ok(Self::Strict),
+            "replace" => Ok(Self::Replace),
+            "ignore" => Ok(Self::Ignore),
+            _ => Err(MemflowPyError::InvalidErrors(value.to_owned())),
+        }
+    }
+}
+
+/// Codec used for narrow strings when none is given.
+const DEFAULT_ENCODING: &str = "utf-8";
+
+/// Reads a NUL terminated narrow string of at most `max_bytes` bytes and decodes it with a python codec.
+pub fn read_char_string(
+    mem: &mut (impl MemorySource + ?Sized),
+    addr: Address,
+    max_bytes: usize,
+    encoding: Option<&str>,
+    errors: Option<&str>,
+) -> PyResult<String> {
+    let bytes = read_terminated(mem, addr, max_bytes, 1).map_err(|e| {
+        PyException::new_err(format!("failed to read variable length string {}", e))
+    })?;
+    Python::with_gil(|py| {
+        PyBytes::new(py, &bytes)
+            .call_method1(
+                "decode",
+                (
+                    encoding.unwrap_or(DEFAULT_ENCODING),
+                    errors.unwrap_or("replace"),
+                ),
+            )?
+            .extract()
+    })
+}
+
+/// Encodes a narrow string with a python codec, optionally NUL terminated and limited to `max_bytes` bytes.
+pub fn encode_char_string(
+    value: &str,
+    encoding: Option<&str>,
+    errors: Option<&str>,
+    terminate: bool,
+    max_bytes: Option<usize>,
+) -> PyResult<Vec<u8>> {
+    let bytes: Vec<u8> = Python::with_gil(|py| {
+        PyString::new(py, value)
+            .call_method1(
+                "encode",
+                (
+                    encoding.unwrap_or(DEFAULT_ENCODING),
+                    errors.unwrap_or("strict"),
+                ),
+            )?
+            .extract()
+    })?;
+    Ok(finish_encoded(bytes, 1, terminate, max_bytes)?)
+}
+
+/// Encodes a wide string as UTF-16, optionally NUL terminated and limited to `max_bytes` bytes.
+pub fn encode_wchar_string(
+    value: &str,
+    encoding: WideEncoding,
+    terminate: bool,
+    max_bytes: Option<usize>,
+) -> crate::Result<Vec<u8>> {
+    let bytes = value
+        .encode_utf16()
+        .flat_map(|unit| encoding.encode_unit(unit))
+        .collect();
+    finish_encoded(bytes, 2, terminate, max_bytes)
+}
+
+fn finish_encoded(
+    mut bytes: Vec<u8>,
+    unit_size: usize,
+    terminate: bool,
+    max_bytes: Option<usize>,
+) -> crate::Result<Vec<u8>> {
+    if terminate {
+        bytes.extend(std::iter::repeat_n(0, unit_size));
+    }
+    match max_bytes {
+        Some(max_bytes) if bytes.len() > max_bytes => {
+            Err(MemflowPyError::StringTooLong(bytes.len(), max_bytes))
+        }
+        _ => Ok(bytes),
+    }
+}
+
+/// Reads a wide string of at most `max_bytes` bytes terminated by a NUL code unit.
+pub fn read_wchar_string(
+    mem: &mut (impl MemorySource + ?Sized),
+    addr: Address,
+    max_bytes: usize,
+    encoding: WideEncoding,
+    errors: DecodeErrors,
+) -> PyResult<String> {
+    let bytes = read_terminated(mem, addr, max_bytes, 2).map_err(|e| {
+        PyException::new_err(format!("failed to read variable length wide string {}", e))
+    })?;
+    Ok(decode_utf16(&bytes, encoding, errors)?)
+}
+
+/// Reads `wchar_t` text of `width` bytes per character of at most `max_bytes` bytes terminated by a NUL character.
+pub fn read_wide_chars(
+    mem: &mut (impl MemorySource + ?Sized),
+    addr: Address,
+    max_bytes: usize,
+    width: usize,
+) -> PyResult<String> {
+    let bytes = read_terminated(mem, addr, max_bytes, width).map_err(|e| {
+        PyException::new_err(format!("failed to read variable length wide string {}", e))
+    })?;
+    Ok(decode_wide_chars(&bytes, width, false)?)
+}
+
+/// Reads a wide string with a known length of `len_bytes` bytes, like the buffer of a `UNICODE_STRING`.
+pub fn read_counted_wchar_string(
+    mem: &mut (impl MemorySource + ?Sized),
+    addr: Address,
+    len_bytes: usize,
+    encoding: WideEncoding,
+    errors: DecodeErrors,
+) -> PyResult<String> {
+    let mut bytes = vec![0; len_bytes];
+    mem.read_raw_into(addr, &mut bytes)
+        .data_part()
+        .map_err(|e| PyException::new_err(format!("failed to read counted wide string {}", e)))?;
+    Ok(decode_utf16(&bytes, encoding, errors)?)
+}
+
+/// Reads the text of a Windows `UNICODE_STRING` (length, maximum length and buffer pointer).
+pub fn read_unicode_string(
+    mem: &mut (impl MemorySource + ?Sized),
+    addr: Address,
+    pointer_size: usize,
+    errors: DecodeErrors,
+) -> PyResult<String> {
+    // The buffer pointer is aligned to its own size after the two length fields.
+    let mut header = vec![0; pointer_size * 2];
+    mem.read_raw_into(addr, &mut header)
+        .map_err(|e| PyException::new_err(format!("failed to read bytes {}", e)))?;
+    let length = u16::from_le_bytes([header[0], header[1]]);
+    let mut buffer = [0; 8];
+    buffer[..pointer_size].copy_from_slice(&header[pointer_size..]);
+    let buffer = u64::from_le_bytes(buffer);
+    if buffer == 0 || length == 0 {
+        return Ok(String::new());
+    }
+    read_counted_wchar_string(
+        mem,
+        buffer.into(),
+        length as usize,
+        WideEncoding::Utf16Le,
+        errors,
+    )
+}
+
+/// Decodes UTF-16 text, a trailing odd byte is treated as a truncated code unit.
+pub fn decode_utf16(
+    bytes: &[u8],
+    encoding: WideEncoding,
+    errors: DecodeErrors,
+) -> crate::Result<String> {
+    let units = bytes
+        .chunks_exact(2)
+        .map(|unit| encoding.decode_unit([unit[0], unit[1]]));
+    let mut decoded = String::with_capacity(bytes.len() / 2);
+    for (i, c) in char::decode_utf16(units).enumerate() {
+        match (c, errors) {
+            (Ok(c), _) => decoded.push(c),
+            (Err(_), DecodeErrors::Replace) => decoded.push(char::REPLACEMENT_CHARACTER),
+            (Err(_), DecodeErrors::Ignore) => {}
+            (Err(e), DecodeErrors::Strict) => {
+                return Err(MemflowPyError::InvalidString(format!(
+                    "unpaired surrogate {:#06x} in code unit {}",
+                    e.unpaired_surrogate(),
+                    i
+                )))
+            }
+        }
+    }
+    if !bytes.len().is_multiple_of(2) {
+        match errors {
+            DecodeErrors::Strict => {
+                return Err(MemflowPyError::InvalidString(
+                    "truncated code unit".to_owned(),
+                ))
+            }
+            DecodeErrors::Replace => decoded.push(char::REPLACEMENT_CHARACTER),
+            DecodeErrors::Ignore => {}
+        }
+    }
+    Ok(decoded)
+}
+
+/// Decodes `wchar_t` text of `width` bytes per character, UTF-16 for 2 byte and UTF-32 for 4 byte characters.
+pub fn decode_wide_chars(bytes: &[u8], width: usize, big_endian: bool) -> crate::Result<String> {
+    match width {
+        4 => Ok(bytes
+            .chunks_exact(4)
+            .map(|unit| {
+                let unit = unit.try_into().unwrap();
+                let unit = match big_endian {
+                    true => u32::from_be_bytes(unit),
+                    false => u32::from_le_bytes(unit),
+                };
+                char::from_u32(unit).unwrap_or(char::REPLACEMENT_CHARACTER)
+            })
+            .collect()),
+        _ => {
+            let encoding = match big_endian {
+                true => WideEncoding::Utf16Be,
+                false => WideEncoding::Utf16Le,
+            };
+            decode_utf16(bytes, encoding, DecodeErrors::Replace)
+        }
+    }
+}
+
+/// Encodes `wchar_t` text of `width` bytes per character, the counterpart of `decode_wide_chars`.
+pub fn encode_wide_chars(value: &str, width: usize, big_endian: bool) -> crate::Result<Vec<u8>> {
+    match width {
+        4 => Ok(value
+            .chars()
+            .flat_map(|c| match big_endian {
+                true => (c as u32).to_be_bytes(),
+                false => (c as u32).to_le_bytes(),
+            })
+            .collect()),
+        _ => {
+            let encoding = match big_endian {
+                true => WideEncoding::Utf16Be,
+                false => WideEncoding::Utf16Le,
+            };
+            encode_wchar_string(value, encoding, false, None)
+        }
+    }
+}
+
+/// Reads the bytes in front of the first NUL unit of `unit_size` bytes, within `max_bytes` bytes.
+fn read_terminated(
+    mem: &mut (impl MemorySource + ?Sized),
+    addr: Address,
+    max_bytes: usize,
+    unit_size: usize,
+) -> Result<Vec<u8>, PartialError<memflow::prelude::Error>> {
+    // Only whole units are considered.
+    let max_bytes = max_bytes - max_bytes % unit_size;
+    let mut buf = vec![0; std::cmp::min(32, max_bytes)];
+    let mut last_n = 0;
+    while last_n < buf.len() {
+        let (_, right) = buf.split_at_mut(last_n);
+        mem.read_raw_into(addr + last_n, right).data_part()?;
+        if let Some(n) = right
+            .chunks_exact(unit_size)
+            .position(|unit| unit.iter().all(|b| *b == 0))
+        {
+            buf.truncate(last_n + n * unit_size);
+            return Ok(buf);
+        }
+        last_n = buf.len();
+        buf.resize(std::cmp::min(last_n * 2, max_bytes), 0);
+    }
+    Err(PartialError::Error(memflow::prelude::Error(
+        ErrorOrigin::VirtualMemory,
+        ErrorKind::OutOfBounds,
+    )))
+}
@@ -0,0 +1,990 @@
+use std::collections::{HashMap, HashSet};
+
+use indexmap::IndexMap;
+use pyo3::{
+    exceptions::PyKeyError,
+    prelude::*,
+    sync::GILOnceCell,
+    types::{PyDict, PyList, PyModule, PyString, PyTuple, PyType},
+};
+
+use crate::{cdecl, MemflowPyError};
+
+/// Words of the builtin C types, resolved to the types following the data model of the target.
+const BUILTIN_WORDS: &[&str] = &[
+    "void", "char", "short", "int", "long", "signed", "unsigned", "float", "double", "_Bool",
+    "bool", "wchar_t", "int8_t", "uint8_t", "int16_t", "uint16_t", "int32_t", "uint32_t",
+    "int64_t", "uint64_t",
+];
+
+const QUALIFIERS: &[&str] = &["const", "volatile", "restrict", "__restrict"];
+
+/// Canonical expressions of the builtin types, the first name of a type is used when writing.
+const BUILTIN_NAMES: &[(&str, &str)] = &[
+    ("int8_t", "c_int8"),
+    ("uint8_t", "c_uint8"),
+    ("int16_t", "c_int16"),
+    ("uint16_t", "c_uint16"),
+    ("int32_t", "c_int32"),
+    ("uint32_t", "c_uint32"),
+    ("int64_t", "c_int64"),
+    ("uint64_t", "c_uint64"),
+    ("long", "c_long"),
+    ("unsigned long", "c_ulong"),
+    ("char", "c_char"),
+    ("wchar_t", "c_wchar"),
+    ("bool", "c_bool"),
+    ("float", "c_float"),
+    ("double", "c_double"),
+    ("long double", "c_longdouble"),
+];
+
+/// Library used to resolve type expressions passed in place of a type.
+static DEFAULT_LIBRARY: GILOnceCell<Py<PyTypeLibrary>> = GILOnceCell::new();
+
+/// Pointer types pinned to a width without a `POINTER` helper, shared so resolving is stable.
+static PINNED_POINTERS: GILOnceCell<Py<PyDict>> = GILOnceCell::new();
+
+fn invalid(expr: &str, msg: impl Into<String>) -> MemflowPyError {
+    MemflowPyError::InvalidTypeExpression(expr.to_owned(), msg.into())
+}
+
+/// Resolves `value` through the default library if it is a type expression.
+pub fn resolve(py: Python<'_>, value: PyObject) -> crate::Result<PyObject> {
+    match value.as_ref(py).downcast::<PyString>() {
+        Ok(expr) => default_library(py)?.borrow(py).resolve(py, expr.to_str()?),
+        Err(_) => Ok(value),
+    }
+}
+
+fn default_library(py: Python<'_>) -> crate::Result<&Py<PyTypeLibrary>> {
+    Ok(DEFAULT_LIBRARY.get_or_try_init(py, || Py::new(py, PyTypeLibrary::default()))?)
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Ident(String),
+    Number(usize),
+    Punct(&'static str),
+}
+
+fn tokenize(expr: &str) -> crate::Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = expr.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        if c.is_whitespace() {
+            continue;
+        }
+        if c.is_ascii_alphanumeric() || c == '_' {
+            let mut end = start + c.len_utf8();
+            while let Some((idx, c)) = chars.peek() {
+                if !c.is_ascii_alphanumeric() && *c != '_' {
+                    break;
+                }
+                end = idx + c.len_utf8();
+                chars.next();
+            }
+            let word = &expr[start..end];
+            tokens.push(match c.is_ascii_digit() {
+                true => {
+                    Token::Number(parse_number(word).ok_or_else(|| {
+                        invalid(expr, format!("`{}` is not a valid length", word))
+                    })?)
+                }
+                false => Token::Ident(word.to_owned()),
+            });
+            continue;
+        }
+        if expr[start..].starts_with("...") {
+            chars.nth(1);
+            tokens.push(Token::Punct("..."));
+            continue;
+        }
+        let punct = match c {
+            '*' => "*",
+            '[' => "[",
+            ']' => "]",
+            '(' => "(",
+            ')' => ")",
+            ',' => ",",
+            c => return Err(invalid(expr, format!("unexpected `{}`", c))),
+        };
+        tokens.push(Token::Punct(punct));
+    }
+    Ok(tokens)
+}
+
+fn parse_number(word: &str) -> Option<usize> {
+    match word.strip_prefix("0x").or_else(|| word.strip_prefix("0X")) {
+        Some(hex) => usize::from_str_radix(hex, 16).ok(),
+        None => word.parse().ok(),
+    }
+}
+
+/// Type while applying the declarator of an expression.
+enum ExprType {
+    Void,
+    Obj(PyObject),
+    Function(Box<ExprType>, Vec<PyObject>),
+}
+
+/// Part of an abstract declarator, applied to the base type from the inside out.
+enum Modifier {
+    /// Pointer with an optional width given by `__ptr32` or `__ptr64`.
+    Pointer(Option<usize>),
+    Array(usize),
+    Function(Vec<PyObject>),
+}
+
+/// Parser of type names (C abstract declarators), i.e. `struct _LIST_ENTRY *` or `uint32_t[8]`.
+struct ExprParser<'a, 'py> {
+    py: Python<'py>,
+    memflow: &'py PyModule,
+    types: &'a IndexMap<String, PyObject>,
+    expr: &'a str,
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl<'a, 'py> ExprParser<'a, 'py> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn error(&self, msg: impl Into<String>) -> MemflowPyError {
+        invalid(self.expr, msg)
+    }
+
+    fn describe(&self) -> String {
+        match self.peek() {
+            Some(Token::Ident(ident)) => format!("`{}`", ident),
+            Some(Token::Number(n)) => format!("`{}`", n),
+            Some(Token::Punct(punct)) => format!("`{}`", punct),
+            None => "end of expression".to_owned(),
+        }
+    }
+
+    fn eat(&mut self, punct: &str) -> bool {
+        let found = matches!(self.peek(), Some(Token::Punct(p)) if *p == punct);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn expect(&mut self, punct: &str) -> crate::Result<()> {
+        match self.eat(punct) {
+            true => Ok(()),
+            false => Err(self.error(format!("expected `{}`, found {}", punct, self.describe()))),
+        }
+    }
+
+    fn eat_ident(&mut self) -> Option<String> {
+        match self.peek() {
+            Some(Token::Ident(ident)) => {
+                let ident = ident.clone();
+                self.pos += 1;
+                Some(ident)
+            }
+            _ => None,
+        }
+    }
+
+    fn skip_qualifiers(&mut self) {
+        while matches!(self.peek(), Some(Token::Ident(ident)) if QUALIFIERS.contains(&ident.as_str()))
+        {
+            self.pos += 1;
+        }
+    }
+
+    fn parse(mut self) -> crate::Result<PyObject> {
+        let ty = self.type_name()?;
+        if self.pos < self.tokens.len() {
+            return Err(self.error(format!("unexpected {}", self.describe())));
+        }
+        match ty {
+            ExprType::Obj(obj) => Ok(obj),
+            ExprType::Void => Err(self.error("`void` can only be pointed to")),
+            ExprType::Function(..) => Err(self.error("functions can only be pointed to")),
+        }
+    }
+
+    fn type_name(&mut self) -> crate::Result<ExprType> {
+        let base = self.specifiers()?;
+        let modifiers = self.declarator()?;
+        self.apply(base, modifiers)
+    }
+
+    fn lookup(&self, name: &str) -> Option<PyObject> {
+        self.types.get(name).map(|obj| obj.clone_ref(self.py))
+    }
+
+    fn specifiers(&mut self) -> crate::Result<ExprType> {
+        self.skip_qualifiers();
+        let ident = self
+            .eat_ident()
+            .ok_or_else(|| self.error(format!("expected a type, found {}", self.describe())))?;
+        let base = match ident.as_str() {
+            "struct" | "union" | "enum" => {
+                let name = self
+                    .eat_ident()
+                    .ok_or_else(|| self.error(format!("expected a name after `{}`", ident)))?;
+                let obj = self
+                    .lookup(&format!("{} {}", ident, name))
+                    .or_else(|| self.lookup(&name))
+                    .ok_or_else(|| {
+                        MemflowPyError::UnknownTypeName(format!("{} {}", ident, name))
+                    })?;
+                ExprType::Obj(obj)
+            }
+            _ if self.types.contains_key(&ident) => ExprType::Obj(self.lookup(&ident).unwrap()),
+            _ if BUILTIN_WORDS.contains(&ident.as_str()) => {
+                let mut words = vec![ident];
+                loop {
+                    self.skip_qualifiers();
+                    match self.peek() {
+                        Some(Token::Ident(ident)) if BUILTIN_WORDS.contains(&ident.as_str()) => {
+                            words.push(ident.clone());
+                            self.pos += 1;
+                        }
+                        _ => break,
+                    }
+                }
+                self.builtin(&words)?
+            }
+            // Types of the memflow module by their python name, i.e. `c_uint32_be`.
+            _ => match self.memflow.getattr(ident.as_str()) {
+                Ok(obj) if obj.is_instance_of::<PyType>() => ExprType::Obj(obj.into()),
+                _ => return Err(MemflowPyError::UnknownTypeName(ident)),
+            },
+        };
+        self.skip_qualifiers();
+        Ok(base)
+    }
+
+    fn builtin(&self, words: &[String]) -> crate::Result<ExprType> {
+        let has = |word: &str| words.iter().any(|w| w == word);
+        let longs = words.iter().filter(|w| *w == "long").count();
+        let signed = !has("unsigned");
+        let pick = |signed_name: &str, unsigned_name: &str| match signed {
+            true => signed_name.to_owned(),
+            false => unsigned_name.to_owned(),
+        };
+        let name = if let [word] = words {
+            match word.as_str() {
+                "void" => return Ok(ExprType::Void),
+                word if word.ends_with("_t") && word.contains("int") => {
+                    format!("c_{}", word.trim_end_matches("_t"))
+                }
+                _ => String::new(),
+            }
+        } else {
+            String::new()
+        };
+        let name = match name.is_empty() {
+            false => name,
+            true if has("_Bool") || has("bool") => "c_bool".to_owned(),
+            true if has("wchar_t") => "c_wchar".to_owned(),
+            true if has("float") => "c_float".to_owned(),
+            true if has("double") && longs > 0 => "c_longdouble".to_owned(),
+            true if has("double") => "c_double".to_owned(),
+            true if has("char") && !has("signed") && !has("unsigned") => "c_char".to_owned(),
+            true if has("char") => pick("c_int8", "c_uint8"),
+            true if has("short") => pick("c_short", "c_ushort"),
+            true if longs >= 2 => pick("c_longlong", "c_ulonglong"),
+            true if longs == 1 => pick("c_long", "c_ulong"),
+            true if has("int") || has("signed") || has("unsigned") => pick("c_int", "c_uint"),
+            true => return Err(self.error(format!("unknown type `{}`", words.join(" ")))),
+        };
+        Ok(ExprType::Obj(self.memflow.getattr(name.as_str())?.into()))
+    }
+
+    fn declarator(&mut self) -> crate::Result<Vec<Modifier>> {
+        let mut pointers = Vec::new();
+        while self.eat("*") {
+            let mut byteness = None;
+            while let Some(Token::Ident(ident)) = self.peek() {
+                match ident.as_str() {
+                    "__ptr32" => byteness = Some(4),
+                    "__ptr64" => byteness = Some(8),
+                    ident if QUALIFIERS.contains(&ident) => {}
+                    _ => break,
+                }
+                self.pos += 1;
+            }
+            pointers.push(Modifier::Pointer(byteness));
+        }
+
+        let nested = matches!(self.peek(), Some(Token::Punct("(")))
+            && matches!(self.tokens.get(self.pos + 1), Some(Token::Punct("*")));
+        let inner = match nested {
+            true => {
+                self.pos += 1;
+                let inner = self.declarator()?;
+                self.expect(")")?;
+                inner
+            }
+            false => Vec::new(),
+        };
+
+        let mut suffixes = Vec::new();
+        loop {
+            if self.eat("[") {
+                let len = match self.peek() {
+                    Some(Token::Number(len)) => {
+                        let len = *len;
+                        self.pos += 1;
+                        len
+                    }
+                    _ => 0,
+                };
+                self.expect("]")?;
+                suffixes.push(Modifier::Array(len));
+            } else if self.eat("(") {
+                suffixes.push(Modifier::Function(self.params()?));
+            } else {
+                break;
+            }
+        }
+
+        // Suffixes bind tighter than pointers, the innermost declarator applies last.
+        let mut modifiers = pointers;
+        modifiers.extend(suffixes.into_iter().rev());
+        modifiers.extend(inner);
+        Ok(modifiers)
+    }
+
+    fn params(&mut self) -> crate::Result<Vec<PyObject>> {
+        let mut params = Vec::new();
+        if self.eat(")") {
+            return Ok(params);
+        }
+        loop {
+            if !self.eat("...") {
+                match self.type_name()? {
+                    ExprType::Obj(obj) => params.push(obj),
+                    // `(void)` declares no parameters.
+                    ExprType::Void if params.is_empty() => {}
+                    _ => return Err(self.error("parameters must be complete types")),
+                }
+            }
+            if !self.eat(",") {
+                break;
+            }
+        }
+        self.expect(")")?;
+        Ok(params)
+    }
+
+    fn apply(&mut self, base: ExprType, modifiers: Vec<Modifier>) -> crate::Result<ExprType> {
+        let mut ty = base;
+        for modifier in modifiers {
+            ty = match modifier {
+                Modifier::Pointer(byteness) => ExprType::Obj(self.pointer_to(ty, byteness)?),
+                Modifier::Array(len) => match ty {
+                    ExprType::Obj(obj) => {
+                        ExprType::Obj(self.memflow.getattr("ARRAY")?.call1((obj, len))?.into())
+                    }
+                    _ => return Err(self.error("arrays must be of complete types")),
+                },
+                Modifier::Function(params) => ExprType::Function(Box::new(ty), params),
+            };
+        }
+        Ok(ty)
+    }
+
+    fn pointer_to(&self, target: ExprType, byteness: Option<usize>) -> crate::Result<PyObject> {
+        let py = self.py;
+        match target {
+            ExprType::Void => self.pinned_pointer("c_void_p", "Pointer", byteness),
+            ExprType::Function(ret, params) => {
+                let restype = match *ret {
+                    ExprType::Obj(obj) => obj,
+                    ExprType::Void => py.None(),
+                    ExprType::Function(..) => {
+                        return Err(self.error("functions can not return functions"))
+                    }
+                };
+                let mut args = vec![restype];
+                args.extend(params);
+                let kwargs = PyDict::new(py);
+                kwargs.set_item("byteness", byteness)?;
+                Ok(self
+                    .memflow
+                    .getattr("CFUNCTYPE")?
+                    .call(PyTuple::new(py, args), Some(kwargs))?
+                    .into())
+            }
+            // Character pointers are strings, followed on demand like ctypes does.
+            ExprType::Obj(obj) if obj.is(self.memflow.getattr("c_char")?) => {
+                self.pinned_pointer("c_char_p", "c_char_p", byteness)
+            }
+            ExprType::Obj(obj) if obj.is(self.memflow.getattr("c_wchar")?) => {
+                self.pinned_pointer("c_wchar_p", "c_wchar_p", byteness)
+            }
+            ExprType::Obj(obj) => Ok(self
+                .memflow
+                .getattr("POINTER")?
+                .call1((obj, byteness))?
+                .into()),
+        }
+    }
+
+    /// Returns `base`, or a subclass of it pinned to `byteness`.
+    fn pinned_pointer(
+        &self,
+        name: &str,
+        base: &str,
+        byteness: Option<usize>,
+    ) -> crate::Result<PyObject> {
+        let py = self.py;
+        let Some(byteness) = byteness else {
+            return Ok(self.memflow.getattr(base)?.into());
+        };
+        let cache = PINNED_POINTERS
+            .get_or_init(py, || PyDict::new(py).into())
+            .as_ref(py);
+        let key = (name, byteness);
+        if let Some(obj) = cache.get_item(key)? {
+            return Ok(obj.into());
+        }
+        let attrs = PyDict::new(py);
+        attrs.set_item("_byteness_", byteness)?;
+        let obj = cdecl::new_class(self.memflow, name, base, attrs)?;
+        cache.set_item(key, &obj)?;
+        Ok(obj)
+    }
+}
+
+/// Writes types as type expressions, records without a name in the library are named as they are found.
+struct ExprWriter<'py> {
+    py: Python<'py>,
+    memflow: &'py PyModule,
+    names: HashMap<usize, String>,
+    taken: HashSet<String>,
+    /// Records and enums found while writing that still need a definition.
+    pending: Vec<(String, PyObject)>,
+}
+
+impl<'py> ExprWriter<'py> {
+    fn is_subclass(&self, ty: &PyType, base: &str) -> crate::Result<bool> {
+        Ok(ty.is_subclass(self.memflow.getattr(base)?)?)
+    }
+
+    fn is_record(&self, ty: &PyType) -> crate::Result<bool> {
+        Ok(ty.hasattr("_enum_")?
+            || self.is_subclass(ty, "Structure")?
+            || self.is_subclass(ty, "Union")?)
+    }
+
+    fn name_of(&mut self, ty: &PyType) -> crate::Result<String> {
+        if let Some(name) = self.names.get(&(ty.as_ptr() as usize)) {
+            return Ok(name.clone());
+        }
+        let base_name = match ty.getattr("_enum_") {
+            Ok(enum_obj) => enum_obj.getattr("__name__")?.extract::<String>()?,
+            Err(_) => ty.name()?.to_owned(),
+        };
+        let mut name = base_name.clone();
+        let mut idx = 1;
+        while self.taken.contains(&name) {
+            idx += 1;
+            name = format!("{}_{}", base_name, idx);
+        }
+        self.taken.insert(name.clone());
+        self.names.insert(ty.as_ptr() as usize, name.clone());
+        self.pending.push((name.clone(), ty.into()));
+        Ok(name)
+    }
+
+    fn expr(&mut self, ty: &PyAny) -> crate::Result<String> {
+        self.decl(ty, String::new(), true)
+    }
+
+    /// Writes `ty` around the declarator `inner`, names of the library are used unless `named` is unset.
+    fn decl(&mut self, ty: &PyAny, inner: String, named: bool) -> crate::Result<String> {
+        let join = |name: &str, inner: String| match inner.is_empty() {
+            true => name.to_owned(),
+            false if inner.starts_with('(') => format!("{} {}", name, inner),
+            false => format!("{}{}", name, inner),
+        };
+        let ty: &PyType = ty.downcast().map_err(PyErr::from)?;
+        if named {
+            if let Some(name) = self.names.get(&(ty.as_ptr() as usize)) {
+                return Ok(join(name, inner));
+            }
+        }
+        for (name, attr) in BUILTIN_NAMES {
+            if ty.is(self.memflow.getattr(*attr)?) {
+                return Ok(join(name, inner));
+            }
+        }
+        if self.is_record(ty)? {
+            let name = self.name_of(ty)?;
+            return Ok(join(&name, inner));
+        }
+        let ptr = |inner: String| -> crate::Result<String> {
+            let byteness = match ty.getattr("_byteness_") {
+                Ok(byteness) => Some(byteness.extract::<usize>()?),
+                Err(_) => None,
+            };
+            Ok(match byteness {
+                Some(4) => format!("* __ptr32{}", inner),
+                Some(8) => format!("* __ptr64{}", inner),
+                _ => format!("*{}", inner),
+            })
+        };
+        if self.is_subclass(ty, "FunctionPointer")? {
+            let args = ty
+                .getattr("_argtypes_")?
+                .iter()?
+                .map(|arg| self.expr(arg?))
+                .collect::<crate::Result<Vec<_>>>()?;
+            let args = match args.is_empty() {
+                true => "void".to_owned(),
+                false => args.join(", "),
+            };
+            let inner = format!("({})({})", ptr(inner)?, args);
+            let restype = ty.getattr("_restype_")?;
+            return match restype.is_none() {
+                true => Ok(join("void", inner)),
+                false => self.decl(restype, inner, true),
+            };
+        }
+        if self.is_subclass(ty, "c_char_p")? {
+            if ty.hasattr("_max_bytes_")? {
+                return Err(MemflowPyError::InexpressibleType(ty.name()?.to_owned()));
+            }
+            let target = match self.is_subclass(ty, "c_wchar_p")? {
+                true => "wchar_t",
+                false => "char",
+            };
+            return Ok(join(target, ptr(inner)?));
+        }
+        if self.is_subclass(ty, "Pointer")? {
+            return match ty.getattr("_type_") {
+                Ok(target) => self.decl(target, ptr(inner)?, true),
+                Err(_) => Ok(join("void", ptr(inner)?)),
+            };
+        }
+        if ty.hasattr("_length_")? && ty.hasattr("_type_")? {
+            let inner = match inner.starts_with('*') {
+                true => format!("({})", inner),
+                false => inner,
+            };
+            let len: usize = ty.getattr("_length_")?.extract()?;
+            return self.decl(ty.getattr("_type_")?, format!("{}[{}]", inner, len), true);
+        }
+        // Other types of the memflow module are written by their python name, i.e. `c_uint32_be`.
+        let name = ty.name()?;
+        match self.memflow.getattr(name) {
+            Ok(obj) if obj.is(ty) => Ok(join(name, inner)),
+            _ => Err(MemflowPyError::InexpressibleType(name.to_owned())),
+        }
+    }
+
+    /// Describes the layout of a record or enum.
+    fn definition(&mut self, ty: &PyAny) -> crate::Result<PyObject> {
+        let py = self.py;
+        let ty: &PyType = ty.downcast().map_err(PyErr::from)?;
+        let def = PyDict::new(py);
+        if let Ok(enum_obj) = ty.getattr("_enum_") {
+            let flags = enum_obj
+                .downcast::<PyType>()
+                .map_err(PyErr::from)?
+                .is_subclass(py.import("enum")?.getattr("IntFlag")?)?;
+            def.set_item("kind", if flags { "flags" } else { "enum" })?;
+            def.set_item("type", self.expr(ty.getattr("__base__")?)?)?;
+            let members = PyDict::new(py);
+            for item in enum_obj
+                .getattr("__members__")?
+                .call_method0("items")?
+                .iter()?
+            {
+                let (name, member): (String, &PyAny) = item?.extract()?;
+                members.set_item(name, member.getattr("value")?)?;
+            }
+            def.set_item("members", members)?;
+            return Ok(def.into());
+        }
+        let union = self.is_subclass(ty, "Union")?;
+        def.set_item("kind", if union { "union" } else { "struct" })?;
+        if let Ok(endianness) = ty.getattr("_endianness_") {
+            def.set_item("endianness", endianness)?;
+        }
+        let fields = PyList::empty(py);
+        for field in ty.getattr("_fields_")?.iter()? {
+            let field: Vec<&PyAny> = field?.extract()?;
+            let mut out = vec![field[0].into_py(py), self.expr(field[1])?.into_py(py)];
+            out.extend(field[2..].iter().map(|bits| bits.into_py(py)));
+            fields.append(out)?;
+        }
+        def.set_item("fields", fields)?;
+        if let Ok(offsets_obj) = ty.getattr("_offsets_") {
+            let offsets = PyList::empty(py);
+            for offset in offsets_obj.iter()? {
+                let offset: Vec<&PyAny> = offset?.extract()?;
+                let mut out = vec![
+                    offset[0].into_py(py),
+                    offset[1].into_py(py),
+                    self.expr(offset[2])?.into_py(py),
+                ];
+                out.extend(offset[3..].iter().map(|bits| bits.into_py(py)));
+                offsets.append(out)?;
+            }
+            def.set_item("offsets", offsets)?;
+        }
+        Ok(def.into())
+    }
+}
+
+/// Named types to look up and resolve type expressions against.
+#[pyclass(name = "TypeLibrary")]
+#[derive(Default)]
+pub struct PyTypeLibrary {
+    types: IndexMap<String, PyObject>,
+    version: Option<String>,
+}
+
+impl PyTypeLibrary {
+    /// Resolves a type expression, i.e. `POINT*`, `uint32_t[8]` or `struct _LIST_ENTRY`.
+    pub fn resolve(&self, py: Python<'_>, expr: &str) -> crate::Result<PyObject> {
+        ExprParser {
+            py,
+            memflow: py.import("memflow")?,
+            types: &self.types,
+            expr,
+            tokens: tokenize(expr)?,
+            pos: 0,
+        }
+        .parse()
+    }
+
+    fn insert(
+        &mut self,
+        py: Python<'_>,
+        name: String,
+        ty: PyObject,
+        replace: bool,
+    ) -> crate::Result<()> {
+        match self.types.get(&name) {
+            Some(existing) if !replace && !existing.is(&ty) => {
+                Err(MemflowPyError::TypeConflict(name))
+            }
+            _ => {
+                self.types.insert(name, ty.clone_ref(py));
+                Ok(())
+            }
+        }
+    }
+}
+
+#[pymethods]
+impl PyTypeLibrary {
+    /// Creates a library from a mapping of names to types (i.e. the result of `parse_c`).
+    #[new]
+    #[pyo3(signature = (types=None, version=None))]
+    fn new(py: Python<'_>, types: Option<&PyDict>, version: Option<String>) -> PyResult<Self> {
+        let mut library = Self {
+            types: IndexMap::new(),
+            version,
+        };
+        if let Some(types) = types {
+            for (name, ty) in types {
+                library.insert(py, name.extract()?, ty.into(), true)?;
+            }
+        }
+        Ok(library)
+    }
+
+    /// Library used when a type expression is passed in place of a type, i.e. `sizeof("POINT*")`.
+    #[staticmethod]
+    #[pyo3(name = "default")]
+    fn py_default(py: Python<'_>) -> PyResult<Py<Self>> {
+        Ok(default_library(py)?.clone_ref(py))
+    }
+
+    #[getter]
+    fn version(&self) -> Option<String> {
+        self.version.clone()
+    }
+
+    #[setter]
+    fn set_version(&mut self, version: Option<String>) {
+        self.version = version;
+    }
+
+    /// Registers `ty` under `name`, its class name by default. Returns `ty` so it can be used as a decorator.
+    #[pyo3(signature = (ty, name=None))]
+    fn register(
+        &mut self,
+        py: Python<'_>,
+        ty: &PyType,
+        name: Option<String>,
+    ) -> PyResult<PyObject> {
+        let name = match name {
+            Some(name) => name,
+            None => ty.name()?.to_owned(),
+        };
+        self.insert(py, name, ty.into(), false)?;
+        Ok(ty.into())
+    }
+
+    /// Adds the types of `other` (a `TypeLibrary` or a mapping), names taken by other types are kept unless
+    /// `replace` is set.
+    #[pyo3(signature = (other, replace=false))]
+    fn merge(&mut self, py: Python<'_>, other: &PyAny, replace: bool) -> PyResult<()> {
+        let types: Vec<(String, PyObject)> = match other.extract::<PyRef<Self>>() {
+            Ok(other) => other
+                .types
+                .iter()
+                .map(|(name, ty)| (name.clone(), ty.clone_ref(py)))
+                .collect(),
+            Err(_) => other
+                .downcast::<PyDict>()?
+                .iter()
+                .map(|(name, ty)| Ok((name.extract()?, ty.into())))
+                .collect::<PyResult<_>>()?,
+        };
+        // Conflicts are checked up front so a failed merge leaves the library untouched.
+        if !replace {
+            for (name, ty) in &types {
+                if matches!(self.types.get(name), Some(existing) if !existing.is(ty)) {
+                    return Err(MemflowPyError::TypeConflict(name.clone()).into());
+                }
+            }
+        }
+        for (name, ty) in types {
+            self.insert(py, name, ty, true)?;
+        }
+        Ok(())
+    }
+
+    /// Names of the registered types.
+    fn names(&self) -> Vec<String> {
+        self.types.keys().cloned().collect()
+    }
+
+    /// Resolves a type expression against the library.
+    fn __getitem__(&self, py: Python<'_>, expr: &str) -> PyResult<PyObject> {
+        self.resolve(py, expr).map_err(|err| match err {
+            MemflowPyError::UnknownTypeName(name) => PyKeyError::new_err(name),
+            err => err.into(),
+        })
+    }
+
+    fn __setitem__(&mut self, py: Python<'_>, name: String, ty: &PyType) -> PyResult<()> {
+        Ok(self.insert(py, name, ty.into(), true)?)
+    }
+
+    fn __delitem__(&mut self, name: &str) -> PyResult<()> {
+        self.types
+            .shift_remove(name)
+            .map(|_| ())
+            .ok_or_else(|| PyKeyError::new_err(name.to_owned()))
+    }
+
+    fn __contains__(&self, name: &str) -> bool {
+        self.types.contains_key(name)
+    }
+
+    fn __len__(&self) -> usize {
+        self.types.len()
+    }
+
+    /// Serialises the layouts of the types, records and enums they use are included under their class name.
+    #[pyo3(signature = (indent=None))]
+    fn to_json(&self, py: Python<'_>, indent: Option<usize>) -> PyResult<String> {
+        let mut writer = ExprWriter {
+            py,
+            memflow: py.import("memflow")?,
+            names: HashMap::new(),
+            taken: self.types.keys().cloned().collect(),
+            pending: Vec::new(),
+        };
+        for (name, ty) in &self.types {
+            writer
+                .names
+                .entry(ty.as_ptr() as usize)
+                .or_insert_with(|| name.clone());
+        }
+
+        let types = PyDict::new(py);
+        for (name, ty) in &self.types {
+            let ty = ty.as_ref(py);
+            let first = &writer.names[&(ty.as_ptr() as usize)];
+            let def = if first != name {
+                alias(py, first)?
+            } else if writer.is_record(ty.downcast().map_err(PyErr::from)?)? {
+                writer.definition(ty)?
+            } else {
+                alias(py, &writer.decl(ty, String::new(), false)?)?
+            };
+            types.set_item(name, def)?;
+        }
+        while let Some((name, ty)) = writer.pending.pop() {
+            types.set_item(name, writer.definition(ty.as_ref(py))?)?;
+        }
+
+        let library = PyDict::new(py);
+        library.set_item("version", &self.version)?;
+        library.set_item("types", types)?;
+        let kwargs = PyDict::new(py);
+        kwargs.set_item("indent", indent)?;
+        py.import("json")?
+            .getattr("dumps")?
+            .call((library,), Some(kwargs))?
+            .extract()
+    }
+
+    /// Rebuilds a library written by `to_json`.
+    #[staticmethod]
+    fn from_json(py: Python<'_>, json: &str) -> PyResult<Self> {
+        let memflow = py.import("memflow")?;
+        let library: &PyDict = py
+            .import("json")?
+            .getattr("loads")?
+            .call1((json,))?
+            .downcast()?;
+        let mut this = Self {
+            types: IndexMap::new(),
+            version: match library.get_item("version")? {
+                Some(version) => version.extract()?,
+                None => None,
+            },
+        };
+        let Some(types) = library.get_item("types")? else {
+            return Ok(this);
+        };
+        let types: &PyDict = types.downcast()?;
+
+        // Records are created up front so their members can refer to each other.
+        let mut records = Vec::new();
+        let mut rest = Vec::new();
+        for (name, def) in types {
+            let name: String = name.extract()?;
+            let def: &PyDict = def.downcast()?;
+            let kind: String = get(def, "kind")?.extract()?;
+            match kind.as_str() {
+                "struct" | "union" => {
+                    let attrs = PyDict::new(py);
+                    if let Some(endianness) = def.get_item("endianness")? {
+                        attrs.set_item("_endianness_", endianness)?;
+                    }
+                    let base = if kind == "struct" {
+                        "Structure"
+                    } else {
+                        "Union"
+                    };
+                    let obj = cdecl::new_class(memflow, &name, base, attrs)?;
+                    this.types.insert(name, obj.clone_ref(py));
+                    records.push((obj, def));
+                }
+                "enum" | "flags" | "alias" => rest.push((name, kind, def)),
+                kind => {
+                    return Err(MemflowPyError::InvalidTypeLibrary(format!(
+                        "unknown kind `{}` of `{}`",
+                        kind, name
+                    ))
+                    .into())
+                }
+            }
+        }
+
+        // Aliases and enums may refer to each other in any order, they are resolved until no progress is made.
+        while !rest.is_empty() {
+            let mut unresolved = Vec::new();
+            let count = rest.len();
+            for (name, kind, def) in rest {
+                let expr: String = get(def, "type")?.extract()?;
+                let ty = match this.resolve(py, &expr) {
+                    Ok(ty) => ty,
+                    Err(MemflowPyError::UnknownTypeName(_)) => {
+                        unresolved.push((name, kind, def));
+                        continue;
+                    }
+                    Err(err) => return Err(err.into()),
+                };
+                let ty = match kind.as_str() {
+                    "alias" => ty,
+                    _ => {
+                        let enum_base = if kind == "flags" {
+                            "IntFlag"
+                        } else {
+                            "IntEnum"
+                        };
+                        let members: Vec<(String, i128)> = get(def, "members")?
+                            .downcast::<PyDict>()?
+                            .iter()
+                            .map(|(name, value)| Ok((name.extract()?, value.extract()?)))
+                            .collect::<PyResult<_>>()?;
+                        let enum_obj = py
+                            .import("enum")?
+                            .getattr(enum_base)?
+                            .call1((name.as_str(), members))?;
+                        memflow.getattr("ENUM")?.call1((enum_obj, ty))?.into()
+                    }
+                };
+                this.types.insert(name, ty);
+            }
+            if unresolved.len() == count {
+                let (name, _, def) = &unresolved[0];
+                let expr: String = get(def, "type")?.extract()?;
+                return Err(this
+                    .resolve(py, &expr)
+                    .err()
+                    .unwrap_or_else(|| {
+                        MemflowPyError::InvalidTypeLibrary(format!(
+                            "`{}` can not be resolved",
+                            name
+                        ))
+                    })
+                    .into());
+            }
+            rest = unresolved;
+        }
+
+        for (obj, def) in records {
+            let fields = PyList::empty(py);
+            for field in get(def, "fields")?.iter()? {
+                let field: Vec<&PyAny> = field?.extract()?;
+                let mut out = vec![field[0].into_py(py), this.resolve(py, field[1].extract()?)?];
+                out.extend(field[2..].iter().map(|bits| bits.into_py(py)));
+                fields.append(PyTuple::new(py, out))?;
+            }
+            obj.setattr(py, "_fields_", fields)?;
+            if let Some(offsets_obj) = def.get_item("offsets")? {
+                let offsets = PyList::empty(py);
+                for offset in offsets_obj.iter()? {
+                    let offset: Vec<&PyAny> = offset?.extract()?;
+                    let mut out = vec![
+                        offset[0].into_py(py),
+                        offset[1].into_py(py),
+                        this.resolve(py, offset[2].extract()?)?,
+                    ];
+                    out.extend(offset[3..].iter().map(|bits| bits.into_py(py)));
+                    offsets.append(PyTuple::new(py, out))?;
+                }
+                obj.setattr(py, "_offsets_", offsets)?;
+            }
+        }
+
+        // Entries were created out of order, the library keeps the order of the document.
+        let order: Vec<String> = types.keys().extract()?;
+        this.types
+            .sort_by_cached_key(|name, _| order.iter().position(|n| n == name));
+        Ok(this)
+    }
+}
+
+fn alias(py: Python<'_>, expr: &str) -> PyResult<PyObject> {
+    let def = PyDict::new(py);
+    def.set_item("kind", "alias")?;
+    def.set_item("type", expr)?;
+    Ok(def.into())
+}
+
+fn get<'py>(def: &'py PyDict, key: &str) -> crate::Result<&'py PyAny> {
+    def.get_item(key)?
+        .ok_or_else(|| MemflowPyError::InvalidTypeLibrary(format!("missing `{}`", key)))
+}
@@ -0,0 +1,236 @@
+use memflow::{prelude::MemoryView, types::umem};
+use pyo3::{
+    exceptions::{PyAttributeError, PyException, PyIndexError, PyTypeError},
+    prelude::*,
+    types::PyString,
+};
+
+use crate::{
+    internal::{BoundMemory, InternalDT},
+    os::{memory_view, PyOs},
+    pointer::PointerSource,
+    process::PyProcess,
+};
+
+/// Process or os whose virtual memory a view reads and writes.
+pub enum ViewOwner {
+    Process(Py<PyProcess>),
+    Os(Py<PyOs>),
+}
+
+impl ViewOwner {
+    fn clone_ref(&self, py: Python<'_>) -> Self {
+        match self {
+            Self::Process(process) => Self::Process(process.clone_ref(py)),
+            Self::Os(os) => Self::Os(os.clone_ref(py)),
+        }
+    }
+}
+
+/// Lazy proxy of a datatype in process or os memory, only the accessed fields are read or written.
+#[pyclass(name = "View")]
+pub struct PyView {
+    owner: ViewOwner,
+    addr: umem,
+    dt: InternalDT,
+}
+
+/// Reads and decodes, pointers are bound to the owner of the view like its `read` does.
+fn decode(
+    mem: &mut impl MemoryView,
+    source: PointerSource,
+    addr: umem,
+    dt: &InternalDT,
+) -> PyResult<PyObject> {
+    let mut mem = BoundMemory::new(mem, source);
+    let bytes = dt.read_bytes(&mut mem, addr.into())?;
+    Ok(dt.py_from_bytes(bytes, Some(&mut mem))?)
+}
+
+fn store(mem: &mut impl MemoryView, addr: umem, dt: &InternalDT, bytes: &[u8]) -> PyResult<()> {
+    let mut bytes = bytes.to_vec();
+    if let InternalDT::Bitfield(_, _, _) = dt {
+        // The storage unit is shared with other bitfields, only the bits of this one are replaced.
+        let unit = mem
+            .read_raw(addr.into(), dt.size())
+            .map_err(|e| PyException::new_err(format!("failed to read bytes {}", e)))?;
+        bytes = dt.merge_bitfield(&unit, &bytes);
+    }
+    mem.write_raw(addr.into(), &bytes)
+        .map_err(|e| PyException::new_err(format!("failed to write bytes {}", e)))?;
+    Ok(())
+}
+
+impl PyView {
+    pub fn new(owner: ViewOwner, addr: umem, dt: InternalDT) -> Self {
+        Self { owner, addr, dt }
+    }
+
+    /// Returns a nested view for structures and arrays, any other datatype is read and decoded.
+    fn get(&self, py: Python<'_>, addr: umem, dt: InternalDT) -> PyResult<PyObject> {
+        match &dt {
+            InternalDT::Structure(_, _) | InternalDT::Union(_, _) => {
+                Ok(Self::new(self.owner.clone_ref(py), addr, dt).into_py(py))
+            }
+            // Character arrays are text, they are read as a whole.
+            InternalDT::Array(_, item_dt, _) if item_dt.char_width().is_none() => {
+                Ok(Self::new(self.owner.clone_ref(py), addr, dt).into_py(py))
+            }
+            _ => self.read_dt(py, addr, &dt),
+        }
+    }
+
+    fn read_dt(&self, py: Python<'_>, addr: umem, dt: &InternalDT) -> PyResult<PyObject> {
+        match &self.owner {
+            ViewOwner::Process(process) => {
+                let mut this = process.as_ref(py).try_borrow_mut()?;
+                let source =
+                    PointerSource::new(process.as_ref(py), "read", this.target_data_model())?;
+                decode(this.memory(), source, addr, dt)
+            }
+            ViewOwner::Os(os) => {
+                let this = os.as_ref(py).try_borrow()?;
+                let source = PointerSource::new(os.as_ref(py), "read", this.target_data_model())?;
+                let mut inst = this.memory()?;
+                decode(memory_view(&mut inst)?, source, addr, dt)
+            }
+        }
+    }
+
+    fn set(&self, py: Python<'_>, addr: umem, dt: &InternalDT, value: PyObject) -> PyResult<()> {
+        let bytes = dt.py_to_bytes(value)?;
+        match &self.owner {
+            ViewOwner::Process(process) => {
+                let mut this = process.as_ref(py).try_borrow_mut()?;
+                store(this.memory(), addr, dt, &bytes)
+            }
+            ViewOwner::Os(os) => {
+                let this = os.as_ref(py).try_borrow()?;
+                let mut inst = this.memory()?;
+                store(memory_view(&mut inst)?, addr, dt, &bytes)
+            }
+        }
+    }
+
+    /// Resolves a structure or union field into its address and datatype.
+    fn field(&self, py: Python<'_>, name: &str) -> PyResult<(umem, InternalDT)> {
+        let dts = match &self.dt {
+            InternalDT::Structure(_, dts) | InternalDT::Union(_, dts) => dts,
+            _ => {
+                return Err(PyTypeError::new_err(format!(
+                    "{} has no fields",
+                    self.type_name(py)
+                )))
+            }
+        };
+        let (offset, dt) = dts
+            .get(name)
+            .ok_or_else(|| PyAttributeError::new_err(name.to_owned()))?;
+        let addr = self.addr + *offset as umem;
+        match dt {
+            InternalDT::DynArray(_, _, length_field) => {
+                let (length_addr, length_dt) = self.field(py, length_field)?;
+                let length = self.get(py, length_addr, length_dt)?.extract(py)?;
+                Ok((addr, dt.with_length(length)))
+            }
+            _ => Ok((addr, dt.clone())),
+        }
+    }
+
+    /// Resolves an array item into its address and datatype, negative indices count from the end.
+    fn item(&self, py: Python<'_>, idx: isize) -> PyResult<(umem, InternalDT)> {
+        let InternalDT::Array(_, dt, len) = &self.dt else {
+            return Err(PyTypeError::new_err(format!(
+                "{} is not an array",
+                self.type_name(py)
+            )));
+        };
+        let len = *len as isize;
+        let idx = if idx < 0 { idx + len } else { idx };
+        if !(0..len).contains(&idx) {
+            return Err(PyIndexError::new_err("array index out of range"));
+        }
+        Ok((self.addr + (idx as usize * dt.size()) as umem, *dt.clone()))
+    }
+
+    fn resolve(&self, py: Python<'_>, key: &PyAny) -> PyResult<(umem, InternalDT)> {
+        match key.downcast::<PyString>() {
+            Ok(name) => self.field(py, name.to_str()?),
+            Err(_) => self.item(py, key.extract()?),
+        }
+    }
+
+    fn type_name(&self, py: Python<'_>) -> String {
+        match &self.dt {
+            InternalDT::Structure(class, _)
+            | InternalDT::Union(class, _)
+            | InternalDT::Array(class, _, _) => class
+                .getattr(py, "__name__")
+                .map(|name| name.to_string())
+                .unwrap_or_default(),
+            dt => format!("{:?}", dt),
+        }
+    }
+}
+
+#[pymethods]
+impl PyView {
+    #[getter]
+    fn addr(&self) -> umem {
+        self.addr
+    }
+
+    /// Reads and decodes the whole datatype.
+    fn read(&self, py: Python<'_>) -> PyResult<PyObject> {
+        self.read_dt(py, self.addr, &self.dt)
+    }
+
+    /// Encodes and writes the whole datatype.
+    fn write(&self, py: Python<'_>, value: PyObject) -> PyResult<()> {
+        self.set(py, self.addr, &self.dt, value)
+    }
+
+    fn __getattr__(&self, py: Python<'_>, name: &str) -> PyResult<PyObject> {
+        let (addr, dt) = self.field(py, name)?;
+        self.get(py, addr, dt)
+    }
+
+    fn __setattr__(&self, py: Python<'_>, name: &str, value: PyObject) -> PyResult<()> {
+        let (addr, dt) = self.field(py, name)?;
+        self.set(py, addr, &dt, value)
+    }
+
+    /// Items of arrays by index and fields of structures by name, for fields shadowed by methods.
+    fn __getitem__(&self, py: Python<'_>, key: &PyAny) -> PyResult<PyObject> {
+        let (addr, dt) = self.resolve(py, key)?;
+        self.get(py, addr, dt)
+    }
+
+    fn __setitem__(&self, py: Python<'_>, key: &PyAny, value: PyObject) -> PyResult<()> {
+        let (addr, dt) = self.resolve(py, key)?;
+        self.set(py, addr, &dt, value)
+    }
+
+    fn __len__(&self, py: Python<'_>) -> PyResult<usize> {
+        match &self.dt {
+            InternalDT::Array(_, _, len) => Ok(*len as usize),
+            _ => Err(PyTypeError::new_err(format!(
+                "{} is not an array",
+                self.type_name(py)
+            ))),
+        }
+    }
+
+    fn __dir__(&self) -> Vec<String> {
+        match &self.dt {
+            InternalDT::Structure(_, dts) | InternalDT::Union(_, dts) => {
+                dts.keys().cloned().collect()
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    fn __repr__(&self, py: Python<'_>) -> String {
+        format!("View({} @ {:#x})", self.type_name(py), self.addr)
+    }
+}
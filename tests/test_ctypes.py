@@ -1,5 +1,6 @@
 from memflow import Process, ProcessInfo, ProcessState, Inventory, Os, dummy
 from ctypes import *
+import memflow
 
 
 class POINT(Structure):
@@ -19,3 +20,59 @@ def test_basic():
     # Test reading a structure.
     test_works = proc.read(proc_address, POINT)
     assert test_works.x == 55
+
+
+class FLAGS(Structure):
+    _fields_ = [
+        ("a", c_uint32, 3),
+        ("b", c_uint32, 5),
+        ("c", c_int32, 4),
+        ("d", c_uint16),
+        ("e", c_uint16, 12),
+    ]
+
+
+def test_bitfield():
+    proc = dummy.quick_process(4096, bytes([0x8]))
+    proc_address = proc.info().address
+
+    # Test writing a structure containing bitfields.
+    flags = FLAGS(5, 17, -3, 0xABCD, 0xFFF)
+    proc.write(proc_address, FLAGS, flags)
+
+    # Test bitfield layout matches ctypes.
+    raw = proc.read(proc_address, c_ubyte * sizeof(FLAGS))
+    assert bytes(raw) == bytes(flags)
+
+    # Test reading the bitfields back.
+    test_works = proc.read(proc_address, FLAGS)
+    assert (test_works.a, test_works.b, test_works.c) == (5, 17, -3)
+    assert (test_works.d, test_works.e) == (0xABCD, 0xFFF)
+
+
+class MIXED_FLAGS(Structure):
+    _fields_ = [("a", c_uint8, 4), ("b", c_uint32, 4)]
+
+
+class SPLIT_FLAGS(Structure):
+    _fields_ = [("a", c_uint16, 4), ("b", c_uint16, 4), ("c", c_uint32, 8)]
+
+
+def test_bitfield_mixed_widths():
+    proc = dummy.quick_process(4096, bytes([0x8]))
+    proc_address = proc.info().address
+
+    # Test bitfields of different types share the naturally aligned unit that has room.
+    for ty in (MIXED_FLAGS, SPLIT_FLAGS):
+        assert memflow.sizeof(ty) == sizeof(ty)
+        for name, *_ in ty._fields_:
+            assert memflow.offsetof(ty, name) == getattr(ty, name).offset
+
+    # Test the values land in the same bits ctypes puts them in.
+    for ty, values in ((MIXED_FLAGS, (0xA, 0x5)), (SPLIT_FLAGS, (0xA, 0x5, 0xC3))):
+        flags = ty(*values)
+        proc.write(proc_address, ty, flags)
+        raw = proc.read(proc_address, c_ubyte * sizeof(ty))
+        assert bytes(raw) == bytes(flags)
+        test_works = proc.read(proc_address, ty)
+        assert tuple(getattr(test_works, name) for name, *_ in ty._fields_) == values
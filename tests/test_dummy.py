@@ -1,4 +1,7 @@
 from memflow import *
+from enum import IntEnum, IntFlag
+from fractions import Fraction
+from typing import Annotated
 import pytest
 
 
@@ -102,6 +105,294 @@ def test_string():
     assert test_works == "it works"
 
 
+
+def test_string_encoding():
+    proc = dummy.quick_process(4096, bytes([0x8]))
+    proc_address = proc.info().address
+
+    # Test writing and reading strings with a codec.
+    proc.write_char_string(proc_address, "caf\u00e9", encoding="cp1252")
+    assert list(proc.read(proc_address, c_uint8 * 5)) == [0x63, 0x61, 0x66, 0xE9, 0]
+    assert proc.read_char_string(proc_address, encoding="latin-1") == "caf\u00e9"
+    assert proc.read_char_string(proc_address) == "caf\ufffd"
+    with pytest.raises(Exception):
+        proc.read_char_string(proc_address, errors="strict")
+    proc.write_char_string(proc_address, "\u30e1\u30e2", encoding="shift-jis")
+    assert proc.read_char_string(proc_address, encoding="shift-jis") == "\u30e1\u30e2"
+
+    # Test writing wide strings with and without termination.
+    proc.write_wchar_string(proc_address, "it works")
+    proc.write_wchar_string(proc_address, "IT", terminate=False)
+    assert proc.read_wchar_string(proc_address) == "IT works"
+
+    # Test the maximum length guard.
+    with pytest.raises(Exception):
+        proc.write_char_string(proc_address, "it works", max_bytes=8)
+    proc.write_char_string(proc_address, "it works", terminate=False, max_bytes=8)
+
+
+class PROCESS_ENTRY(Structure):
+    _fields_ = [("pid", c_uint32), ("name", c_char * 16), ("title", c_wchar * 8)]
+
+
+def test_char_array():
+    proc = dummy.quick_process(4096, bytes([0x8]))
+    proc_address = proc.info().address
+
+    # Test writing text that is padded to the array size.
+    proc.write(proc_address, PROCESS_ENTRY, PROCESS_ENTRY(4, b"explorer.exe", "shell"))
+    test_works = proc.read(proc_address, PROCESS_ENTRY)
+    assert test_works.name == b"explorer.exe"
+    assert test_works.name.raw == b"explorer.exe" + bytes(4)
+    assert test_works.title == "shell"
+    assert test_works.title.raw == "shell\0\0\0"
+
+    # Test that the full buffer is written back.
+    proc.write(proc_address + 4, c_char * 16, b"ab\0cd")
+    test_works = proc.read(proc_address + 4, c_char * 16)
+    assert test_works == b"ab"
+    proc.write(proc_address + 0x100, c_char * 16, test_works)
+    assert proc.read(proc_address + 0x100, c_char * 16).raw[:5] == b"ab\0cd"
+
+    # Test text that does not fit.
+    with pytest.raises(Exception):
+        proc.write(proc_address, c_char * 4, b"too long")
+
+
+class CHAR_TYPES(Structure):
+    _fields_ = [("flag", c_bool), ("letter", c_char), ("symbol", c_wchar)]
+
+
+def test_char_types():
+    proc = dummy.quick_process(4096, bytes([0x8]))
+    proc_address = proc.info().address
+
+    # Test the ctypes value types of bool and characters.
+    proc.write(proc_address, CHAR_TYPES, CHAR_TYPES(True, b"a", "\u00e9"))
+    test_works = proc.read(proc_address, CHAR_TYPES)
+    assert test_works.flag is True
+    assert test_works.letter == b"a"
+    assert test_works.symbol == "\u00e9"
+
+    # Test that any non zero byte is true and characters accept their ordinal.
+    proc.write(proc_address, c_uint8, 2)
+    assert proc.read(proc_address, c_bool) is True
+    proc.write(proc_address, c_char, 0xFF)
+    assert proc.read(proc_address, c_char) == b"\xff"
+
+    # Test values out of range.
+    with pytest.raises(Exception):
+        proc.write(proc_address, c_char, 256)
+    with pytest.raises(Exception):
+        proc.write(proc_address, c_char, b"ab")
+    with pytest.raises(Exception):
+        proc.write(proc_address, c_wchar, "ab")
+
+
+class STATE(IntEnum):
+    RUNNING = 1
+    STOPPED = 2
+
+
+class ACCESS(IntFlag):
+    READ = 1
+    WRITE = 2
+
+
+class TASK(BigEndianStructure):
+    _fields_ = [("state", ENUM(STATE, c_uint16)), ("access", ENUM(ACCESS, c_uint8))]
+
+
+def test_enum():
+    proc = dummy.quick_process(4096, bytes([0x8]))
+    proc_address = proc.info().address
+
+    # Test decoding enum members in the byte order of the structure.
+    proc.write(proc_address, TASK, TASK(STATE.STOPPED, ACCESS.READ | ACCESS.WRITE))
+    assert list(proc.read(proc_address, c_uint8 * 3)) == [0, 2, 3]
+    test_works = proc.read(proc_address, TASK)
+    assert test_works.state is STATE.STOPPED
+    assert test_works.access == ACCESS.READ | ACCESS.WRITE
+
+    # Test unknown values staying an int.
+    proc.write(proc_address, TASK, TASK(7, 0))
+    assert type(proc.read(proc_address, TASK).state) is int
+    assert proc.read(proc_address, TASK).state == 7
+
+
+class ANNOTATED(Structure):
+    pid: c_uint32
+    flags: Annotated[c_uint8, Bits(3)]
+    name: Annotated[c_char * 16, Offset(0x20)]
+    next: POINTER(POINT)
+
+
+def test_annotations():
+    proc = dummy.quick_process(4096, bytes([0x8]))
+    proc_address = proc.info().address
+
+    # Test the layout taken from the annotations.
+    assert ANNOTATED._fields_ == [
+        ("pid", c_uint32),
+        ("flags", c_uint8, 3),
+        ("next", POINTER(POINT)),
+    ]
+    assert ANNOTATED._offsets_ == [(0x20, "name", c_char * 16)]
+    assert sizeof(ANNOTATED) == 0x30
+
+    # Test reading and writing annotated fields.
+    proc.write(proc_address, ANNOTATED, ANNOTATED(4, 5, POINTER(POINT)(0x10), name=b"init"))
+    test_works = proc.read(proc_address, ANNOTATED)
+    assert test_works.pid == 4
+    assert test_works.flags == 5
+    assert test_works.next.addr == 0x10
+    assert test_works.name == b"init"
+
+
+class ANNOTATED_CHILD(ANNOTATED):
+    parent: c_uint64
+
+
+def test_annotations_inherited():
+    # Test a subclass appends its annotations to the fields of its base.
+    assert ANNOTATED_CHILD._fields_ == ANNOTATED._fields_ + [("parent", c_uint64)]
+    assert ANNOTATED_CHILD._offsets_ == ANNOTATED._offsets_
+    assert ANNOTATED._fields_[-1][0] == "next"
+    assert sizeof(ANNOTATED_CHILD) == 0x30
+
+
+class POINT_LIST(Structure):
+    _fields_ = [("count", c_uint32), ("points", VARRAY(POINT, "count"))]
+
+
+class BAD_POINT_LIST(Structure):
+    _fields_ = [("points", VARRAY(POINT, "count")), ("count", c_uint32)]
+
+
+def test_dynamic_array():
+    proc = dummy.quick_process(4096, bytes([0x8]))
+    proc_address = proc.info().address
+
+    # Test writing and reading the tail sized by `count`.
+    points = [POINT(i, i / 2) for i in range(3)]
+    proc.write(proc_address, POINT_LIST, POINT_LIST(3, ARRAY(POINT, 3)(*points)))
+    assert sizeof(POINT_LIST) == 4
+    test_works = proc.read(proc_address, POINT_LIST)
+    assert len(test_works.points) == 3
+    assert test_works.points[2].x == 2
+    assert test_works.points[2].y == 1.0
+
+    # Test an empty tail.
+    proc.write(proc_address, c_uint32, 0)
+    assert len(proc.read(proc_address, POINT_LIST).points) == 0
+
+    # Test the dynamic array having to end the structure.
+    with pytest.raises(Exception):
+        sizeof(BAD_POINT_LIST)
+
+
+class VIEWED(Structure):
+    _fields_ = [
+        ("flags", c_uint8, 3),
+        ("mode", c_uint8, 5),
+        ("origin", POINT),
+        ("points", POINT * 4),
+        ("name", c_char * 8),
+        ("count", c_uint32),
+        ("extra", VARRAY(c_uint16, "count")),
+    ]
+
+
+def test_view():
+    proc = dummy.quick_process(4096, bytes([0x8]))
+    proc_address = proc.info().address
+
+    # Test writing single fields, leaving the rest untouched.
+    proc.write(proc_address, c_uint8 * 0x80, [0xAA] * 0x80)
+    view = proc.view(proc_address, VIEWED)
+    view.origin.x = 5
+    view.name = b"view"
+    view.count = 2
+    view.extra[1] = 7
+    assert proc.read(proc_address + 1, c_uint32) == 5
+    assert proc.read(proc_address + 5, c_uint8) == 0xAA
+
+    # Test bitfields keeping the other bits of their storage unit.
+    view.mode = 1
+    assert proc.read(proc_address, c_uint8) == 0x0A
+    assert view.flags == 2
+
+    # Test reading nested views and items.
+    view.points[-1].y = 1.5
+    assert view.points[3].y == 1.5
+    assert len(view.points) == 4
+    assert view["name"] == b"view"
+    assert len(view.extra) == 2
+    assert view.extra[1] == 7
+    assert view.origin.read().x == 5
+    assert view.read().count == 2
+    with pytest.raises(IndexError):
+        view.points[4]
+    with pytest.raises(AttributeError):
+        view.missing
+
+
+class LOOKUP(IntEnum):
+    NONE = 0
+
+    @classmethod
+    def _missing_(cls, value):
+        try:
+            cls.view.value
+        except Exception as e:
+            cls.errors.append(e)
+
+
+class LOOKED_UP(Structure):
+    _fields_ = [("value", ENUM(LOOKUP, c_uint32))]
+
+
+def test_view_reentrant():
+    proc = dummy.quick_process(4096, bytes([0x8]))
+    proc_address = proc.info().address
+
+    # Test views used while they are read raise instead of panicking.
+    proc.write(proc_address, c_uint32, 7)
+    LOOKUP.view = proc.view(proc_address, LOOKED_UP)
+    LOOKUP.errors = []
+    assert LOOKUP.view.value == 7
+    assert len(LOOKUP.errors) == 1
+
+
+def test_wide_string():
+    proc = dummy.quick_process(4096, bytes([0x8]))
+    proc_address = proc.info().address
+
+    # Test reading past zero bytes of ascii range code units.
+    text = "it works \U0001f600"
+    encoded = text.encode("utf-16-le") + bytes(2)
+    proc.write(proc_address, c_uint8 * len(encoded), encoded)
+    assert proc.read_wchar_string(proc_address) == text
+
+    # Test reading big endian code units.
+    encoded = text.encode("utf-16-be") + bytes(2)
+    proc.write(proc_address, c_uint8 * len(encoded), encoded)
+    assert proc.read_wchar_string(proc_address, encoding="utf-16-be") == text
+
+    # Test unpaired surrogates with the error handlers.
+    proc.write(proc_address, c_uint8 * 6, bytes([0x61, 0, 0x00, 0xD8, 0, 0]))
+    assert proc.read_wchar_string(proc_address) == "a\ufffd"
+    assert proc.read_wchar_string(proc_address, errors="ignore") == "a"
+    with pytest.raises(Exception):
+        proc.read_wchar_string(proc_address, errors="strict")
+
+    # Test reading a counted `UNICODE_STRING`.
+    proc.write(proc_address + 0x100, c_char * 10, "works".encode("utf-16-le"))
+    proc.write(proc_address, c_uint16 * 2, [8, 10])
+    proc.write(proc_address + 8, c_uint64, proc_address + 0x100)
+    assert proc.read_unicode_string(proc_address) == "work"
+
+
 def test_struct_array():
     proc = dummy.quick_process(4096, bytes([0x8]))
     proc_address = proc.info().address
@@ -111,3 +402,542 @@ def test_struct_array():
     # Test reading an array of structures.
     test_works = proc.read(proc_address, POINT * 3)
     assert test_works[0] == POINT(1, 2)
+
+
+class BITFIELD_TEST(Structure):
+    _fields_ = [
+        ("kind", c_uint16, 4),
+        ("flags", c_uint16, 12),
+        ("signed", c_int16, 7),
+        ("wide", c_uint32, 30),
+    ]
+
+
+def test_bitfield():
+    mem = dummy.DummyMemory(4096)
+
+    # Test bitfields that do not fit the storage unit start a new unit.
+    assert sizeof(BITFIELD_TEST) == 8
+
+    # Test writing and reading back packed bitfields.
+    mem.write(0, BITFIELD_TEST, BITFIELD_TEST(0xA, 0x5, -64, 0x3FFFFFFF))
+    test_works = mem.read(0, BITFIELD_TEST)
+    assert test_works.kind == 0xA
+    assert test_works.flags == 0x5
+    assert test_works.signed == -64
+    assert test_works.wide == 0x3FFFFFFF
+    assert mem.read(0, c_uint16) == 0x5A
+
+
+class VARIANT(Union):
+    _fields_ = [("as_u8", c_uint8), ("as_u32", c_uint32), ("as_point", POINT)]
+
+
+def test_union():
+    mem = dummy.DummyMemory(4096)
+
+    # Test union size is the size of the largest member.
+    assert sizeof(VARIANT) == 8
+
+    # Test writing only the set member.
+    mem.write(0, POINT, POINT(0x11223344, 1.5))
+    mem.write(0, VARIANT, VARIANT(as_u8=0xFF))
+    assert mem.read(0, c_uint32) == 0xFF
+
+    # Test reading every view of the union.
+    mem.write(0, VARIANT, VARIANT(as_point=POINT(0x11223344, 1.5)))
+    test_works = mem.read(0, VARIANT)
+    assert test_works.as_u8 == 0x44
+    assert test_works.as_u32 == 0x11223344
+    assert test_works.as_point.y == 1.5
+
+    # Test writing back a modified member.
+    test_works.as_u32 = 0x55
+    mem.write(0, VARIANT, test_works)
+    assert mem.read(0, POINT).x == 0x55
+
+
+class NET_HEADER(BigEndianStructure):
+    _fields_ = [
+        ("version", c_uint8, 4),
+        ("length", c_uint8, 4),
+        ("port", c_uint16),
+        ("seq", c_uint32),
+        ("host_order", c_uint32_le),
+        ("inner", POINT),
+    ]
+
+
+def test_big_endian():
+    mem = dummy.DummyMemory(4096)
+
+    # Test writing a big endian structure.
+    mem.write(0, NET_HEADER, NET_HEADER(4, 5, 0x1F90, 0x01020304, 0x01020304, POINT(1, 2)))
+    assert mem.read(0, c_uint8) == 0x45
+    assert mem.read(1, c_uint16_be) == 0x1F90
+    assert mem.read(3, c_uint8 * 4)[0] == 0x01
+    assert mem.read(7, c_uint32) == 0x01020304
+    # Test nested structures inherit the byte order.
+    assert mem.read(11, c_uint32_be) == 1
+    assert mem.read(15, c_float_be) == 2
+
+    # Test reading a big endian structure.
+    test_works = mem.read(0, NET_HEADER)
+    assert (test_works.version, test_works.length) == (4, 5)
+    assert test_works.port == 0x1F90
+    assert test_works.seq == 0x01020304
+    assert test_works.inner == POINT(1, 2)
+
+
+def test_data_model():
+    proc = dummy.quick_process(4096, bytes([0x8]))
+    proc_address = proc.info().address
+
+    # Test data model is derived from the 64-bit unix dummy process.
+    assert proc.data_model == "LP64"
+    proc.write(proc_address, c_uint64, 0x1122334455667788)
+    assert proc.read(proc_address, c_ulong) == 0x1122334455667788
+
+    # Test overriding the data model.
+    proc.data_model = "LLP64"
+    assert proc.read(proc_address, c_ulong) == 0x55667788
+    proc.data_model = None
+    assert proc.data_model == "LP64"
+
+
+def test_os_data_model():
+    dummy_os = dummy.DummyOs(dummy.DummyMemory(0x1000000))
+    pid = dummy_os.alloc_process(4096)
+    os = dummy_os.retrieve_os()
+
+    # Test data model is derived from the 64-bit dummy os instead of the running system.
+    assert os.data_model == "LP64"
+    os.data_model = "ILP32"
+    assert os.data_model == "ILP32"
+    os.data_model = None
+    assert os.data_model == "LP64"
+
+    # Test processes of a unix os are not taken for Windows ones, whatever their path.
+    assert os.process_from_pid(pid).data_model == "LP64"
+    os.data_model = "LLP64"
+    assert os.process_from_pid(pid).data_model == "LLP64"
+
+    # Test types are laid out without holding on to the os, the dummy has no virtual memory.
+    with pytest.raises(Exception, match="MemoryView"):
+        os.write(0x1000, c_long, 1)
+
+    # Test the os reads types like its processes do.
+    for read in [
+        lambda: os.view(0x1000, POINT).x,
+        lambda: os.dump_type(0x1000, POINT),
+        lambda: os.read_unicode_string(0x1000),
+        lambda: os.hexdump(0x1000, ty=POINT, data_model="ILP32"),
+    ]:
+        with pytest.raises(Exception, match="MemoryView"):
+            read()
+
+
+def test_long_double():
+    mem = dummy.DummyMemory(4096)
+
+    # Test writing x87 extended precision values.
+    mem.write(0, c_float80, 1.5)
+    assert bytes(mem.read(0, c_uint8 * 10)) == bytes.fromhex("00000000000000c0ff3f")
+
+    # Test reading x87 extended precision values, pi rounded to 64 significant bits.
+    mem.write(0, c_uint64, 0xC90FDAA22168C235)
+    mem.write(8, c_uint16, 0x4000)
+    assert mem.read(0, c_float80) == 3.141592653589793
+    assert mem.read(0, c_float80_exact) == Fraction(0xC90FDAA22168C235, 2**62)
+
+    # Test lossless round trip of values not representable as a double.
+    mem.write(16, c_float80_exact, Fraction(1, 3))
+    assert mem.read(16, c_float80_exact) == Fraction(0xAAAAAAAAAAAAAAAB, 2**65)
+    assert mem.read(16, c_float80) == 1 / 3
+
+    # Test padded storage follows the data model.
+    assert sizeof(c_longdouble, "LP64") == 16
+    assert sizeof(c_longdouble, "LLP64") == 8
+    assert sizeof(c_longdouble, "ILP32") == 12
+    assert sizeof(c_longdouble, "Win32") == 8
+
+
+def test_float128():
+    mem = dummy.DummyMemory(4096)
+
+    # Test writing quadruple precision values, the integer bit is implicit.
+    mem.write(0, c_float128, 1.5)
+    assert bytes(mem.read(0, c_uint8 * 16)) == bytes(13) + bytes.fromhex("80ff3f")
+    assert mem.read(0, c_float128) == 1.5
+
+    # Test lossless round trip of values not representable as a double.
+    mem.write(16, c_float128_exact, Fraction(1, 3))
+    assert mem.read(16, c_float128_exact) == Fraction((2**114 - 1) // 3, 2**114)
+    assert mem.read(16, c_float128) == 1 / 3
+
+    # Test special values.
+    mem.write(32, c_float128, float("-inf"))
+    assert mem.read(32, c_float128) == float("-inf")
+    mem.write(32, c_float128, 5e-324)
+    assert mem.read(32, c_float128) == 5e-324
+
+
+class NAMED(Structure):
+    _fields_ = [("name", c_char_p), ("eager_name", CHAR_P(64)), ("missing", CHAR_P(64))]
+
+
+def test_string_pointer():
+    proc = dummy.quick_process(4096, bytes([0x8]))
+    proc_address = proc.info().address
+
+    # Test writing a structure with string pointers.
+    proc.write(proc_address + 0x100, c_char * 8, bytes("it works", "utf-8"))
+    string_ptr = c_char_p(proc_address + 0x100)
+    proc.write(proc_address, NAMED, NAMED(string_ptr, string_ptr, None))
+
+    # Test following string pointers on demand and eagerly.
+    test_works = proc.read(proc_address, NAMED)
+    assert test_works.name == string_ptr
+    assert test_works.name.read(proc) == "it works"
+    assert test_works.eager_name == "it works"
+    assert test_works.missing is None
+
+    # Test string pointers hash like the address they compare equal to.
+    assert {test_works.name, string_ptr, c_wchar_p(proc_address)} == {
+        string_ptr,
+        proc_address,
+    }
+
+
+class WIDE_NAMED(Structure):
+    _fields_ = [("name", WCHAR_P(64))]
+
+
+def test_wide_string_pointer():
+    proc = dummy.quick_process(4096, bytes([0x8]))
+    proc_address = proc.info().address
+    proc.write(proc_address, c_uint64, proc_address + 0x100)
+
+    # Test eager wide strings follow the `wchar_t` width of the data model like arrays do.
+    proc.write(proc_address + 0x100, c_char * 20, "wide".encode("utf-32-le"))
+    assert proc.read(proc_address, WIDE_NAMED).name == "wide"
+    assert proc.read(proc_address + 0x100, c_wchar * 4).value == "wide"
+
+    proc.data_model = "LLP64"
+    proc.write(proc_address + 0x100, c_char * 10, "wide".encode("utf-16-le"))
+    assert proc.read(proc_address, WIDE_NAMED).name == "wide"
+    assert proc.read(proc_address + 0x100, c_wchar * 4).value == "wide"
+
+
+def test_pointer_deref():
+    proc = dummy.quick_process(4096, bytes([0x8]))
+    proc_address = proc.info().address
+
+    points_address = proc_address + 0x100
+    test_struct = TEST((1, 2), 2, POINTER64(POINT)(points_address))
+    proc.write(proc_address, TEST, test_struct)
+    proc.write(points_address, POINT, POINT(55, 3.14))
+    proc.write(points_address + 8, POINT, POINT(66, 1.5))
+
+    # Test following pointers read from process memory.
+    test_works = proc.read(proc_address, TEST)
+    assert test_works.ptr.deref().x == 55
+    assert test_works.ptr[1].x == 66
+
+    # Test arithmetic steps by the size of the target type.
+    next_ptr = test_works.ptr.offset(1)
+    assert next_ptr == points_address + 8
+    assert (test_works.ptr + 1) == next_ptr
+    assert next_ptr.deref().y == 1.5
+    assert (next_ptr - 1).deref().x == 55
+
+    # Test pointers read through views are followed as well.
+    assert proc.view(proc_address, TEST).ptr.deref().x == 55
+
+    # Test pointers created by hand are not bound to any memory.
+    with pytest.raises(Exception):
+        POINTER64(POINT)(points_address).deref()
+
+
+class NODE(Structure):
+    pass
+
+
+NODE._fields_ = [("value", c_uint32), ("next", POINTER64(NODE)), ("prev", POINTER64(NODE))]
+
+
+def test_follow():
+    proc = dummy.quick_process(4096, bytes([0x8]))
+    proc_address = proc.info().address
+
+    # Test writing a circular list of three nodes.
+    nodes = [proc_address + i * 0x20 for i in range(3)]
+    for i, addr in enumerate(nodes):
+        next_ptr = POINTER64(NODE)(nodes[(i + 1) % 3])
+        prev_ptr = POINTER64(NODE)(nodes[i - 1] if i > 0 else 0)
+        proc.write(addr, NODE, NODE(i, next_ptr, prev_ptr))
+
+    # Test the pointers are followed up to the given depth.
+    head = proc.read(nodes[0], NODE, follow=2)
+    second = head.next.deref()
+    assert second.value == 1
+    assert second.next.deref().value == 2
+    assert second.prev.deref() is head
+
+    # Test cycles resolve to the objects read already.
+    head = proc.read(nodes[0], NODE, follow=3)
+    assert head.next.deref().next.deref().next.deref() is head
+
+    # Test every structure keeps its own field values.
+    values = [head.value, head.next.deref().value, head.next.deref().next.deref().value]
+    assert values == [0, 1, 2]
+    first, second = NODE(5), NODE(6)
+    assert (first.value, second.value) == (5, 6)
+    assert "value" not in NODE.__dict__
+
+    # Test null pointers are left alone.
+    assert head.prev.is_null()
+    with pytest.raises(Exception):
+        head.prev.deref()
+
+
+class BE_NODE(BigEndianStructure):
+    pass
+
+
+BE_NODE._fields_ = [("value", c_uint32), ("next", POINTER64(BE_NODE))]
+
+
+def test_follow_big_endian():
+    proc = dummy.quick_process(4096, bytes([0x8]))
+    proc_address = proc.info().address
+
+    # Test pointers of big endian structures are followed while reading.
+    second = proc_address + 0x20
+    proc.write(proc_address, BE_NODE, BE_NODE(1, POINTER64(BE_NODE)(second)))
+    proc.write(second, BE_NODE, BE_NODE(2, POINTER64(BE_NODE)(0)))
+    head = proc.read(proc_address, BE_NODE, follow=1)
+    proc.write(second, BE_NODE, BE_NODE(3, POINTER64(BE_NODE)(0)))
+    assert head.next.addr == second
+    assert head.next.deref().value == 2
+
+
+class VTABLE(Structure):
+    _fields_ = [
+        ("destroy", CFUNCTYPE(None, c_uint32)),
+        ("area", CFUNCTYPE(c_float, POINTER64(c_uint8), c_uint32)),
+    ]
+
+
+class SHAPE(Structure):
+    _fields_ = [("vtable", POINTER64(VTABLE)), ("sides", c_uint32)]
+
+
+def test_function_pointer():
+    proc = dummy.quick_process(4096, bytes([0x8]))
+    proc_address = proc.info().address
+
+    vtable_address = proc_address + 0x100
+    proc.write(proc_address, SHAPE, SHAPE(POINTER64(VTABLE)(vtable_address), 4))
+    proc.write(vtable_address, c_uint64 * 2, [0x7FF000001234, 0x10])
+
+    # Test function pointers are read as addresses carrying their signature.
+    shape = proc.read(proc_address, SHAPE, follow=1)
+    vtable = shape.vtable.deref()
+    assert vtable.destroy == 0x7FF000001234
+    assert vtable.area._restype_ is c_float
+    assert vtable.area._argtypes_ == (POINTER64(c_uint8), c_uint32)
+    assert vtable.destroy.source is not None
+
+    # Test symbolic names relative to the module holding the address.
+    arch = ArchitectureIdent("X86_64", None, None)
+    module = ModuleInfo("game.exe", 0, 0x7FF000000000, 0x10000, "C:\\game.exe", proc_address, arch)
+    assert vtable.destroy.symbol([module]) == "game.exe+0x1234"
+    assert vtable.area.symbol([module]) is None
+    assert str(vtable.area) == "0x10"
+
+
+C_DECLS = """
+#define MAX_NAME 16 /* directives other than pragma pack are skipped */
+enum STATE { IDLE, RUNNING = 4, STOPPED };
+
+struct ENTITY {
+    uint8_t kind;
+    double health;
+    char name[MAX_NAME];
+    struct ENTITY *next;
+    union { int32_t i; float f; } extra;
+    struct { int16_t dx, dy; };
+    unsigned int visible : 1, team : 3;
+    enum STATE state;
+    void (*think)(struct ENTITY *self, int);
+    void *user_data;
+};
+
+typedef struct ENTITY ENTITY, *PENTITY;
+
+#pragma pack(push, 1)
+typedef struct { char tag; uint32_t value; } PACKED;
+#pragma pack(pop)
+"""
+
+
+def test_parse_c():
+    proc = dummy.quick_process(4096, bytes([0x8]))
+    proc_address = proc.info().address
+    types = parse_c(C_DECLS, "LP64")
+
+    # Test the layout follows the C rules.
+    ENTITY = types["ENTITY"]
+    assert types["PENTITY"]._type_ is ENTITY
+    assert sizeof(ENTITY, "LP64") == 0x48
+    assert sizeof(types["PACKED"], "LP64") == 5
+    assert [field[0] for field in ENTITY._fields_][:3] == ["kind", "_pad_0x1", "health"]
+
+    # Test reading and writing the parsed structure.
+    proc.write(proc_address, c_uint8 * 0x48, [0] * 0x48)
+    proc.write(proc_address + 0x8, c_double, 0.5)
+    proc.write(proc_address + 0x10, c_char * 6, b"player")
+    proc.write(proc_address + 0x20, c_uint64, proc_address)
+    proc.write(proc_address + 0x2C, c_int16, -3)
+    proc.write(proc_address + 0x30, c_uint8, 0b1011)
+    proc.write(proc_address + 0x34, c_int32, 4)
+    entity = proc.read(proc_address, ENTITY)
+    assert entity.health == 0.5
+    assert entity.name == b"player"
+    assert entity.next.deref().name == b"player"
+    assert entity.dx == -3
+    assert (entity.visible, entity.team) == (1, 5)
+    assert entity.state == types["STATE"]._enum_.RUNNING
+    assert entity.think._argtypes_ == (types["PENTITY"], c_int32)
+    assert entity.user_data.is_null()
+    proc.write(proc_address + 0x100, ENTITY, entity)
+    assert proc.read(proc_address + 0x100, ENTITY).team == 5
+
+    # Test 32-bit Unix only aligns 8 byte scalars to 4 bytes.
+    source = "struct WIDE { int a; double d; long long q; };"
+    assert sizeof(parse_c(source, "ILP32")["WIDE"], "ILP32") == 0x14
+    assert sizeof(parse_c(source, "Win32")["WIDE"], "Win32") == 0x18
+
+    # Test errors point at the offending line.
+    with pytest.raises(Exception, match="line 2"):
+        parse_c("struct A { int a; };\nstruct B { struct C c; };")
+
+
+class SHARED_BITS(Structure):
+    _fields_ = [("a", c_uint32, 4), ("b", c_uint8, 4)]
+
+
+class WIDENED_BITS(Structure):
+    _fields_ = [("a", c_uint8, 4), ("b", c_uint32, 4)]
+
+
+BITS_DECLS = """
+struct SHARED_BITS { unsigned int a : 4; unsigned char b : 4; };
+struct WIDENED_BITS { unsigned char a : 4; unsigned int b : 4; };
+"""
+
+
+def test_parse_c_bitfields():
+    def bits(ty, data_model):
+        return [
+            (row["name"], row["offset"], row.get("bit_offset"))
+            for row in layout(ty, data_model)
+            if row["name"] in ("a", "b")
+        ]
+
+    # Test bitfields share storage units like structures declared by `_fields_`.
+    types = parse_c(BITS_DECLS, "LP64")
+    for declared in [SHARED_BITS, WIDENED_BITS]:
+        parsed = types[declared.__name__]
+        assert sizeof(parsed, "LP64") == sizeof(declared, "LP64") == 4
+        assert bits(parsed, "LP64") == bits(declared, "LP64")
+    assert bits(types["WIDENED_BITS"], "LP64") == [("a", 0, 0), ("b", 0, 4)]
+
+    # Test MSVC starts a new unit whenever the type size changes.
+    types = parse_c(BITS_DECLS, "LLP64")
+    assert sizeof(types["SHARED_BITS"], "LLP64") == 8
+    assert bits(types["SHARED_BITS"], "LLP64") == bits(SHARED_BITS, "LLP64")
+    assert bits(types["WIDENED_BITS"], "LLP64") == [("a", 0, 0), ("b", 4, 0)]
+
+
+def test_dump_type():
+    proc = dummy.quick_process(4096, bytes([0x8]))
+    proc_address = proc.info().address
+    test_struct = TEST((1, 2), -2, POINTER64(POINT)(proc_address + 0x777))
+    proc.write(proc_address, TEST, test_struct)
+    proc.write(proc_address + 0x777, POINT, POINT(55, 0.5))
+
+    # Test each field shows its offset, type and value.
+    lines = str(proc.dump_type(proc_address, TEST)).splitlines()
+    assert lines[0] == f"struct TEST @ {proc_address:#x}"
+    assert lines[1] == "   +0x000 one : uint32_t[2] = [1, 2]"
+    assert lines[2] == "   +0x008 two : int64_t = -2"
+    assert lines[3] == f"   +0x010 ptr : struct POINT* @ {proc_address + 0x777:#x}"
+    assert len(lines) == 4
+
+    # Test following pointers into their target structure.
+    lines = str(proc.dump_type(proc_address, ty=TEST, depth=2)).splitlines()
+    assert lines[4:] == [
+        "   +0x000    x : uint32_t = 55",
+        "   +0x004    y : float = 0.5",
+    ]
+    assert "<td style" in proc.dump_type(proc_address, TEST)._repr_html_()
+
+    # Test dynamic arrays show the items counted by their length field.
+    points = POINT_LIST(2, [POINT(1, 1.5), POINT(2, 2.5)])
+    proc.write(proc_address, POINT_LIST, points)
+    lines = str(proc.dump_type(proc_address, POINT_LIST, depth=2)).splitlines()
+    assert lines[2] == "   +0x004 points  : struct POINT[2]"
+    assert lines[3:5] == [
+        "   +0x000    [0]  : struct POINT",
+        "   +0x000       x : uint32_t = 1",
+    ]
+    assert lines[7:] == [
+        "   +0x000       x : uint32_t = 2",
+        "   +0x004       y : float = 2.5",
+    ]
+
+
+class LONGS(Structure):
+    _fields_ = [("first", c_long), ("second", c_long)]
+
+
+def test_hexdump():
+    proc = dummy.quick_process(4096, bytes([0x8]))
+    proc_address = proc.info().address
+    proc.write(proc_address, c_uint8 * 0x20, [0] * 0x20)
+    proc.write(proc_address, c_uint32, 0x41424344)
+    proc.write(proc_address + 8, c_char * 4, b"test")
+
+    # Test the classic offset, hex and ASCII columns.
+    lines = proc.hexdump(proc_address, 0x14).splitlines()
+    assert lines[0] == (
+        f"{proc_address:016x}  44 43 42 41 00 00 00 00  74 65 73 74 00 00 00 00"
+        "  |DCBA....test....|"
+    )
+    assert lines[1] == f"{proc_address + 0x10:016x}  00 00 00 00" + " " * 39 + "|....|"
+
+    # Test bytes of pages that can not be read are marked instead of failing.
+    end = proc_address + 4096
+    line = proc.hexdump(end - 4, 8)
+    assert line.startswith(f"{end - 4:016x}  00 00 00 00 ?? ?? ?? ??  ")
+    assert line.endswith("|....????|")
+
+    # Test bytes past the end of the address space are unknown as well.
+    line = proc.hexdump(2**64 - 8, 16)
+    unknown = "?? " * 8
+    assert line == f"fffffffffffffff8  {unknown} {unknown} |{'?' * 16}|"
+
+    # Test labelling the bytes with the fields of a structure.
+    lines = proc.hexdump(proc_address, ty=TEST).splitlines()
+    assert lines[0].endswith("|DCBA....        |  one")
+    assert lines[1].endswith("|test....        |  two")
+    assert lines[2].endswith("  ptr")
+    assert len(lines) == 3
+
+    # Test laying out the structure for another data model.
+    lines = proc.hexdump(proc_address, ty=LONGS, data_model="LLP64").splitlines()
+    assert lines[1].startswith(f"{proc_address + 4:016x}  ")
+    assert lines[1].endswith("  second")
+    assert len(proc.hexdump(proc_address, ty=LONGS).splitlines()) == 2
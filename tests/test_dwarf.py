@@ -0,0 +1,361 @@
+from memflow import *
+import os
+import struct
+import tempfile
+
+DW_TAG_ARRAY_TYPE = 0x01
+DW_TAG_ENUMERATION_TYPE = 0x04
+DW_TAG_MEMBER = 0x0D
+DW_TAG_POINTER_TYPE = 0x0F
+DW_TAG_COMPILE_UNIT = 0x11
+DW_TAG_STRUCTURE_TYPE = 0x13
+DW_TAG_TYPEDEF = 0x16
+DW_TAG_UNION_TYPE = 0x17
+DW_TAG_SUBRANGE_TYPE = 0x21
+DW_TAG_BASE_TYPE = 0x24
+DW_TAG_ENUMERATOR = 0x28
+
+DW_AT_NAME = 0x03
+DW_AT_BYTE_SIZE = 0x0B
+DW_AT_BIT_SIZE = 0x0D
+DW_AT_CONST_VALUE = 0x1C
+DW_AT_COUNT = 0x37
+DW_AT_DATA_MEMBER_LOCATION = 0x38
+DW_AT_DECLARATION = 0x3C
+DW_AT_ENCODING = 0x3E
+DW_AT_TYPE = 0x49
+DW_AT_DATA_BIT_OFFSET = 0x6B
+
+DW_FORM_DATA1 = 0x0B
+DW_FORM_STRING = 0x08
+DW_FORM_SDATA = 0x0D
+DW_FORM_REF4 = 0x13
+DW_FORM_FLAG_PRESENT = 0x19
+
+# Abbreviation code to tag, children flag and attributes.
+ABBREVS = {
+    1: (DW_TAG_COMPILE_UNIT, True, [(DW_AT_NAME, DW_FORM_STRING)]),
+    2: (
+        DW_TAG_BASE_TYPE,
+        False,
+        [
+            (DW_AT_NAME, DW_FORM_STRING),
+            (DW_AT_ENCODING, DW_FORM_DATA1),
+            (DW_AT_BYTE_SIZE, DW_FORM_DATA1),
+        ],
+    ),
+    3: (
+        DW_TAG_POINTER_TYPE,
+        False,
+        [(DW_AT_BYTE_SIZE, DW_FORM_DATA1), (DW_AT_TYPE, DW_FORM_REF4)],
+    ),
+    4: (
+        DW_TAG_STRUCTURE_TYPE,
+        True,
+        [(DW_AT_NAME, DW_FORM_STRING), (DW_AT_BYTE_SIZE, DW_FORM_DATA1)],
+    ),
+    5: (
+        DW_TAG_MEMBER,
+        False,
+        [
+            (DW_AT_NAME, DW_FORM_STRING),
+            (DW_AT_TYPE, DW_FORM_REF4),
+            (DW_AT_DATA_MEMBER_LOCATION, DW_FORM_DATA1),
+        ],
+    ),
+    6: (
+        DW_TAG_MEMBER,
+        False,
+        [
+            (DW_AT_NAME, DW_FORM_STRING),
+            (DW_AT_TYPE, DW_FORM_REF4),
+            (DW_AT_BIT_SIZE, DW_FORM_DATA1),
+            (DW_AT_DATA_BIT_OFFSET, DW_FORM_DATA1),
+        ],
+    ),
+    7: (
+        DW_TAG_STRUCTURE_TYPE,
+        False,
+        [(DW_AT_NAME, DW_FORM_STRING), (DW_AT_DECLARATION, DW_FORM_FLAG_PRESENT)],
+    ),
+    8: (
+        DW_TAG_TYPEDEF,
+        False,
+        [(DW_AT_NAME, DW_FORM_STRING), (DW_AT_TYPE, DW_FORM_REF4)],
+    ),
+    9: (
+        DW_TAG_ENUMERATION_TYPE,
+        True,
+        [
+            (DW_AT_NAME, DW_FORM_STRING),
+            (DW_AT_TYPE, DW_FORM_REF4),
+            (DW_AT_BYTE_SIZE, DW_FORM_DATA1),
+        ],
+    ),
+    10: (
+        DW_TAG_ENUMERATOR,
+        False,
+        [(DW_AT_NAME, DW_FORM_STRING), (DW_AT_CONST_VALUE, DW_FORM_SDATA)],
+    ),
+    11: (DW_TAG_ARRAY_TYPE, True, [(DW_AT_TYPE, DW_FORM_REF4)]),
+    12: (DW_TAG_SUBRANGE_TYPE, False, [(DW_AT_COUNT, DW_FORM_DATA1)]),
+    13: (DW_TAG_UNION_TYPE, True, [(DW_AT_BYTE_SIZE, DW_FORM_DATA1)]),
+    14: (
+        DW_TAG_MEMBER,
+        False,
+        [(DW_AT_TYPE, DW_FORM_REF4), (DW_AT_DATA_MEMBER_LOCATION, DW_FORM_DATA1)],
+    ),
+}
+
+# DIEs as (label, abbreviation, values, children), references are given by label.
+DIES = [
+    ("int", 2, ["int", 0x05, 4], None),
+    ("uint", 2, ["unsigned int", 0x07, 4], None),
+    ("char", 2, ["char", 0x06, 1], None),
+    ("ulong", 2, ["long unsigned int", 0x07, 8], None),
+    ("task_decl", 7, ["task"], None),
+    ("ptask", 3, [8, "task_decl"], None),
+    (
+        "list_head",
+        4,
+        ["list_head", 16],
+        [
+            (None, 5, ["next", "plist", 0], None),
+            (None, 5, ["prev", "plist", 8], None),
+        ],
+    ),
+    ("plist", 3, [8, "list_head"], None),
+    (
+        "state",
+        9,
+        ["state", "int", 4],
+        [(None, 10, ["RUNNING", 1], None), (None, 10, ["DEAD", -1], None)],
+    ),
+    ("comm", 11, ["char"], [(None, 12, [16], None)]),
+    (
+        "union",
+        13,
+        [8],
+        [
+            (None, 5, ["value", "ulong", 0], None),
+            (None, 5, ["low", "uint", 0], None),
+        ],
+    ),
+    (
+        "task",
+        4,
+        ["task", 64],
+        [
+            (None, 5, ["pid", "int", 0], None),
+            (None, 6, ["flags", "uint", 3, 32], None),
+            (None, 6, ["kind", "uint", 5, 35], None),
+            (None, 5, ["state", "state", 8], None),
+            (None, 5, ["comm", "comm", 12], None),
+            (None, 5, ["tasks", "list_head", 32], None),
+            (None, 14, ["union", 48], None),
+            (None, 5, ["parent", "ptask", 56], None),
+        ],
+    ),
+    ("task_t", 8, ["task_t", "task"], None),
+]
+
+
+def uleb(value):
+    out = bytearray()
+    while True:
+        byte = value & 0x7F
+        value >>= 7
+        if value:
+            out.append(byte | 0x80)
+        else:
+            out.append(byte)
+            return bytes(out)
+
+
+def sleb(value):
+    out = bytearray()
+    while True:
+        byte = value & 0x7F
+        value >>= 7
+        if (value == 0 and not byte & 0x40) or (value == -1 and byte & 0x40):
+            out.append(byte)
+            return bytes(out)
+        out.append(byte | 0x80)
+
+
+def build_abbrev():
+    out = b""
+    for code, (tag, children, attrs) in ABBREVS.items():
+        out += uleb(code) + uleb(tag) + bytes([children])
+        out += b"".join(uleb(at) + uleb(form) for at, form in attrs) + b"\0\0"
+    return out + b"\0"
+
+
+def build_info(endian="<"):
+    # Offsets are relative to the unit, which starts with an 11 byte header.
+    body = bytearray()
+    labels = {}
+    fixups = []
+
+    def emit(label, code, values, children):
+        if label:
+            labels[label] = 11 + len(body)
+        body.extend(uleb(code))
+        for (_, form), value in zip(ABBREVS[code][2], values):
+            if form == DW_FORM_STRING:
+                body.extend(value.encode() + b"\0")
+            elif form == DW_FORM_DATA1:
+                body.append(value)
+            elif form == DW_FORM_SDATA:
+                body.extend(sleb(value))
+            elif form == DW_FORM_REF4:
+                fixups.append((len(body), value))
+                body.extend(bytes(4))
+        if children is not None:
+            for child in children:
+                emit(*child)
+            body.append(0)
+
+    emit(None, 1, ["test.c"], DIES)
+    for pos, label in fixups:
+        body[pos : pos + 4] = struct.pack(endian + "I", labels[label])
+    return struct.pack(endian + "IHIB", 7 + len(body), 4, 0, 8) + bytes(body)
+
+
+def build_elf(sections, symbols, endian="<"):
+    shstrtab = b"\0"
+    names = []
+    for name, _ in sections + [(".symtab", b""), (".strtab", b""), (".shstrtab", b"")]:
+        names.append(len(shstrtab))
+        shstrtab += name.encode() + b"\0"
+
+    strtab = b"\0"
+    symtab = bytes(24)
+    for name, value in symbols:
+        symtab += struct.pack(endian + "IBBHQQ", len(strtab), 0x11, 0, 1, value, 8)
+        strtab += name.encode() + b"\0"
+
+    contents = [data for _, data in sections] + [symtab, strtab, shstrtab]
+    data = bytearray(64 + 56)
+    offsets = []
+    for content in contents:
+        offsets.append(len(data))
+        data += content
+    data += bytes(-len(data) % 8)
+    shoff = len(data)
+    symtab_idx = len(sections) + 1
+
+    data += bytes(64)
+    for idx, content in enumerate(contents):
+        sh_type, link, entsize = 1, 0, 0
+        if idx == len(sections):
+            sh_type, link, entsize = 2, symtab_idx + 1, 24
+        elif idx > len(sections):
+            sh_type = 3
+        data += struct.pack(
+            endian + "IIQQQQIIQQ",
+            names[idx],
+            sh_type,
+            0,
+            0,
+            offsets[idx],
+            len(content),
+            link,
+            1,
+            1,
+            entsize,
+        )
+
+    # ELF64 executable for x86-64 (or big endian PowerPC) with one loadable segment at 0x400000.
+    data_encoding = b"\x01" if endian == "<" else b"\x02"
+    header = b"\x7fELF\x02" + data_encoding + b"\x01" + bytes(9)
+    header += struct.pack(
+        endian + "HHIQQQIHHHHHH",
+        2,
+        0x3E if endian == "<" else 0x15,
+        1,
+        0,
+        64,
+        shoff,
+        0,
+        64,
+        56,
+        1,
+        64,
+        len(contents) + 1,
+        len(contents),
+    )
+    data[0:64] = header
+    data[64:120] = struct.pack(
+        endian + "IIQQQQQQ", 1, 5, 0, 0x400000, 0x400000, 0, 0x10000, 0x1000
+    )
+    return bytes(data)
+
+
+def load_dwarf(endian="<"):
+    sections = [(".debug_abbrev", build_abbrev()), (".debug_info", build_info(endian))]
+    symbols = [("g_task", 0x404000), ("main", 0x401100)]
+    with tempfile.TemporaryDirectory() as tmp:
+        path = os.path.join(tmp, "test.elf")
+        with open(path, "wb") as f:
+            f.write(build_elf(sections, symbols, endian))
+        return Dwarf(path)
+
+
+def test_dwarf_types():
+    proc = dummy.quick_process(4096, bytes([0x8]))
+    proc_address = proc.info().address
+    types = load_dwarf()
+
+    # Test the layout matches the debug info.
+    assert "list_head" in types and "task_t" in types
+    TASK = types.task
+    assert types["task_t"] is TASK
+    assert sizeof(TASK) == 64
+    assert [field[0] for field in TASK._fields_][4:6] == ["comm", "_pad_0x1c"]
+    assert [offset[1] for offset in TASK._offsets_] == ["value", "low"]
+
+    # Test reading the generated structure, the declared `struct task` resolves to its definition.
+    proc.write(proc_address, c_uint8 * 64, [0] * 64)
+    proc.write(proc_address, c_int32, 42)
+    proc.write(proc_address + 4, c_uint32, 0b11111101)
+    proc.write(proc_address + 8, c_int32, -1)
+    proc.write(proc_address + 12, c_char * 4, b"init")
+    proc.write(proc_address + 32, c_uint64, proc_address + 32)
+    proc.write(proc_address + 48, c_uint64, 0x1122334455667788)
+    proc.write(proc_address + 56, c_uint64, proc_address)
+    task = proc.read(proc_address, TASK)
+    assert (task.pid, task.flags, task.kind) == (42, 0b101, 0b11111)
+    assert task.state == types.state._enum_.DEAD
+    assert task.comm == b"init"
+    assert task.low == 0x55667788
+    assert task.tasks.next.deref().next.addr == proc_address + 32
+    assert task.parent.deref().pid == 42
+
+
+def test_dwarf_symbols():
+    types = load_dwarf()
+    # Symbols are relative to the lowest loaded segment, so the module base can be added.
+    assert types.symbols["g_task"] == 0x4000
+    assert types.symbols["main"] == 0x1100
+
+
+def test_dwarf_big_endian():
+    proc = dummy.quick_process(4096, bytes([0x8]))
+    proc_address = proc.info().address
+    types = load_dwarf(">")
+
+    # Test records of big endian targets keep their byte order.
+    TASK = types.task
+    assert issubclass(TASK, BigEndianStructure)
+    assert sizeof(TASK) == 64
+
+    # Test bitfields are allocated from the most significant bit.
+    proc.write(proc_address, c_uint8 * 64, [0] * 64)
+    proc.write(proc_address, c_uint8 * 4, [0, 0, 0, 42])
+    proc.write(proc_address + 4, c_uint8, 0b10111111)
+    proc.write(proc_address + 48, c_char * 8, bytes.fromhex("1122334455667788"))
+    task = proc.read(proc_address, TASK)
+    assert (task.pid, task.flags, task.kind) == (42, 0b101, 0b11111)
+    assert task.value == 0x1122334455667788
+    assert task.low == 0x11223344
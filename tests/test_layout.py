@@ -0,0 +1,152 @@
+from memflow import *
+import json
+import os
+import shutil
+import subprocess
+import tempfile
+
+
+class LAYOUT_POINT(Structure):
+    _fields_ = [("x", c_int32), ("y", c_int32)]
+
+
+class LAYOUT_NODE(Structure):
+    pass
+
+
+LAYOUT_NODE._fields_ = [
+    ("next", POINTER(LAYOUT_NODE)),
+    ("flags", c_uint32, 3),
+    ("kind", c_uint32, 5),
+    ("pos", LAYOUT_POINT),
+    ("name", c_char * 6),
+    ("cb", CFUNCTYPE(c_int32, c_uint64)),
+]
+LAYOUT_NODE._offsets_ = [(8, "raw", c_uint8)]
+
+
+def test_to_c_header():
+    header = to_c_header(LAYOUT_NODE, "LP64")
+    assert "struct LAYOUT_NODE;\nstruct LAYOUT_POINT;\n" in header
+    assert "#pragma pack(push, 1)" in header
+
+    # Test nested records are defined before they are used by value.
+    assert header.index("struct LAYOUT_POINT {") < header.index("struct LAYOUT_NODE {")
+    assert "/* 0x0000 */ struct LAYOUT_NODE *next;" in header
+    assert "/* 0x0008 */ uint32_t flags : 3;" in header
+    assert "/* 0x0008 */ uint32_t : 24;" in header
+    assert "/* 0x0008 */ uint8_t raw;" in header
+    assert "/* 0x0014 */ char name[6];" in header
+    assert "/* 0x001a */ int32_t (*cb)(uint64_t);" in header
+    assert '_Static_assert(sizeof(struct LAYOUT_NODE) == 0x22, "' in header
+
+    # Test pointers without a width follow the data model.
+    header = to_c_header(LAYOUT_NODE, "ILP32")
+    assert "/* 0x0000 */ struct LAYOUT_NODE *next;" in header
+    assert "/* 0x0016 */ int32_t (*cb)(uint64_t);" in header
+
+
+class MIXED_UNITS(Structure):
+    _fields_ = [("a", c_uint8, 4), ("b", c_uint32, 4), ("c", c_uint16)]
+
+
+def test_to_c_header_mixed_units():
+    header = to_c_header(MIXED_UNITS, "LP64")
+
+    # Test bitfields of overlapping units keep the bits around them in a structure.
+    assert "        struct {\n            /* 0x0000 */ uint32_t : 4;\n" in header
+    assert "/* 0x0000 */ uint32_t b : 4;" in header
+
+    # Test the header compiles and places `b` in the bits after `a`.
+    compiler = shutil.which("cc")
+    if compiler is None:
+        return
+    main = """
+int main(void) {
+    struct MIXED_UNITS value = {0};
+    value.b = 0xF;
+    return ((unsigned char *)&value)[0] != 0xF0;
+}
+"""
+    with tempfile.TemporaryDirectory() as tmp:
+        source = os.path.join(tmp, "layout.c")
+        binary = os.path.join(tmp, "layout")
+        with open(source, "w") as f:
+            f.write(header + main)
+        subprocess.run([compiler, source, "-o", binary], check=True)
+        assert subprocess.run([binary]).returncode == 0
+
+
+def test_to_layout_json():
+    layout = json.loads(to_layout_json(LAYOUT_NODE, "LP64"))
+    assert (layout["name"], layout["size"]) == ("LAYOUT_NODE", 0x22)
+    fields = {field["name"]: field for field in layout["fields"]}
+    assert fields["next"]["type"] == "struct LAYOUT_NODE*"
+    assert fields["kind"] == {
+        "name": "kind",
+        "offset": 8,
+        "size": 4,
+        "type": "uint32_t",
+        "bit_offset": 3,
+        "bit_size": 5,
+    }
+    assert fields["pos"]["offset"] == 12
+    assert [field["offset"] for field in fields["pos"]["fields"]] == [0, 4]
+    assert fields["name"]["type"] == "char[6]"
+
+
+class LAYOUT_OUTER(Structure):
+    _fields_ = [
+        ("tag", c_uint8),
+        ("points", LAYOUT_POINT * 4),
+        ("node", LAYOUT_NODE),
+        ("value", c_double),
+    ]
+
+
+def test_offsetof():
+    assert offsetof(LAYOUT_OUTER, "points") == 1
+    assert offsetof(LAYOUT_OUTER, "points[3].y") == 29
+    assert offsetof(LAYOUT_OUTER, "node.pos.x") == 45
+    assert offsetof(LAYOUT_OUTER, "node.kind") == 41
+    assert offsetof(LAYOUT_POINT * 4, "[2].y") == 20
+
+    # Test invalid paths are reported.
+    for path in ["points[4]", "missing", "tag.x", "points.y"]:
+        try:
+            offsetof(LAYOUT_OUTER, path)
+            assert False
+        except Exception as e:
+            assert "Invalid field path" in str(e)
+
+
+def test_alignof():
+    assert alignof(c_uint16) == 2
+    assert alignof(LAYOUT_POINT) == 4
+    assert alignof(LAYOUT_OUTER, "LP64") == 8
+    assert alignof(c_uint8 * 3) == 1
+    assert alignof(c_longdouble, "ILP32") == 4
+    assert alignof(c_double, "ILP32") == 4
+    assert alignof(c_double, "Win32") == 8
+
+
+def test_layout():
+    class SPARSE(Structure):
+        _fields_ = [("head", c_uint32)]
+        _offsets_ = [(8, "tail", c_uint32), (9, "inner", c_uint8)]
+
+    table = layout(SPARSE)
+    assert [(row["name"], row["offset"], row["size"]) for row in table] == [
+        ("head", 0, 4),
+        (None, 4, 4),
+        ("tail", 8, 4),
+        ("inner", 9, 1),
+    ]
+    assert table[1]["padding"] and not table[0]["padding"]
+    assert table[2]["overlaps"] == ["inner"]
+
+    # Test bitfields only overlap when their bits do.
+    table = {row["name"]: row for row in layout(LAYOUT_NODE, "LP64")}
+    assert table["flags"]["overlaps"] == ["raw"]
+    assert (table["kind"]["bit_offset"], table["kind"]["bit_size"]) == (3, 5)
+    assert table["cb"]["type"] == "int32_t (*)(uint64_t)"
@@ -0,0 +1,174 @@
+from memflow import *
+import os
+import pytest
+import struct
+import tempfile
+
+BLOCK_SIZE = 0x200
+
+LF_POINTER = 0x1002
+LF_FIELDLIST = 0x1203
+LF_BITFIELD = 0x1205
+LF_ENUMERATE = 0x1502
+LF_ARRAY = 0x1503
+LF_STRUCTURE = 0x1505
+LF_ENUM = 0x1507
+LF_MEMBER = 0x150D
+
+
+def record(kind, data):
+    data += bytes(-(len(data) + 2) % 4)
+    return struct.pack("<HH", len(data) + 2, kind) + data
+
+
+def name(s):
+    return s.encode() + b"\0"
+
+
+def pad(data):
+    # Members of a field list are aligned with `LF_PAD` bytes counting down to the next member.
+    n = -len(data) % 4
+    return data + bytes(0xF0 + i for i in range(n, 0, -1))
+
+
+def member(ty, offset, field_name):
+    return pad(struct.pack("<HHIH", LF_MEMBER, 3, ty, offset) + name(field_name))
+
+
+def enumerate_(value, field_name):
+    return pad(struct.pack("<HHH", LF_ENUMERATE, 3, value) + name(field_name))
+
+
+def build_tpi():
+    records = [
+        # 0x1000: forward reference to `_NODE`.
+        struct.pack("<HHIIIH", 0, 0x80, 0, 0, 0, 0) + name("_NODE"),
+        # 0x1001: 64 bit pointer to `_NODE`.
+        struct.pack("<II", 0x1000, 0x0C | (8 << 13)),
+        # 0x1002, 0x1003: `unsigned int` bitfields.
+        struct.pack("<IBB", 0x75, 3, 0),
+        struct.pack("<IBB", 0x75, 5, 4),
+        # 0x1004, 0x1005: `_COLOR` enum.
+        enumerate_(0, "RED") + enumerate_(1, "GREEN") + enumerate_(2, "BLUE"),
+        struct.pack("<HHII", 3, 0, 0x74, 0x1004) + name("_COLOR"),
+        # 0x1006: `char[8]`.
+        struct.pack("<IIH", 0x70, 0x23, 8) + name(""),
+        # 0x1007: `_NODE` members, `Low` overlaps `Value` like an anonymous union.
+        member(0x1001, 0, "Next")
+        + member(0x1002, 8, "Flags")
+        + member(0x1003, 8, "Kind")
+        + member(0x1005, 12, "Color")
+        + member(0x1006, 16, "Name")
+        + member(0x23, 24, "Value")
+        + member(0x75, 24, "Low"),
+        # 0x1008: `_NODE` definition.
+        struct.pack("<HHIIIH", 7, 0, 0x1007, 0, 0, 40) + name("_NODE"),
+    ]
+    kinds = [
+        LF_STRUCTURE,
+        LF_POINTER,
+        LF_BITFIELD,
+        LF_BITFIELD,
+        LF_FIELDLIST,
+        LF_ENUM,
+        LF_ARRAY,
+        LF_FIELDLIST,
+        LF_STRUCTURE,
+    ]
+    data = b"".join(record(kind, r) for kind, r in zip(kinds, records))
+    header = struct.pack("<IIIII", 20040203, 56, 0x1000, 0x1000 + len(kinds), len(data))
+    return header + bytes(56 - len(header)) + data
+
+
+def build_dbi():
+    # Only the symbol record stream (5) and the section header stream (6) are referenced.
+    dbg_header = struct.pack("<11H", *([0xFFFF] * 5 + [6] + [0xFFFF] * 5))
+    header = struct.pack("<iIIHHHHHH", -1, 19990903, 1, 0xFFFF, 0, 0xFFFF, 0, 5, 0)
+    header += struct.pack("<8I", 0, 0, 0, 0, 0, 0, len(dbg_header), 0)
+    header += struct.pack("<HHI", 0, 0x8664, 0)
+    return header + dbg_header
+
+
+def build_symbols():
+    pub = struct.pack("<IIH", 0, 0x10, 1) + name("PsActiveProcessHead")
+    data = struct.pack("<IIH", 0x23, 0x20, 1) + name("KiGlobalValue")
+    return record(0x110E, pub) + record(0x110D, data)
+
+
+def build_sections():
+    return name(".text").ljust(8, b"\0") + struct.pack("<IIII", 0x100, 0x1000, 0x200, 0x400) + bytes(16)
+
+
+def build_msf(streams):
+    # Block 0 holds the superblock, 1 and 2 the free block maps and 3 the block map.
+    blocks = [b"", b"", b"", b""]
+    layout = []
+    for stream in streams:
+        start = len(blocks)
+        for i in range(0, len(stream), BLOCK_SIZE):
+            blocks.append(stream[i : i + BLOCK_SIZE])
+        layout.append(list(range(start, len(blocks))))
+
+    directory = struct.pack("<I", len(streams))
+    directory += b"".join(struct.pack("<I", len(stream)) for stream in streams)
+    directory += b"".join(struct.pack(f"<{len(b)}I", *b) for b in layout)
+    directory_blocks = []
+    for i in range(0, len(directory), BLOCK_SIZE):
+        directory_blocks.append(len(blocks))
+        blocks.append(directory[i : i + BLOCK_SIZE])
+    blocks[3] = struct.pack(f"<{len(directory_blocks)}I", *directory_blocks)
+
+    magic = b"Microsoft C/C++ MSF 7.00\r\n\x1aDS\0\0\0"
+    blocks[0] = magic + struct.pack("<6I", BLOCK_SIZE, 1, len(blocks), len(directory), 0, 3)
+    return b"".join(block.ljust(BLOCK_SIZE, b"\0") for block in blocks)
+
+
+def load_pdb(tpi=None):
+    tpi = tpi if tpi is not None else build_tpi()
+    streams = [b"", b"", tpi, build_dbi(), b"", build_symbols(), build_sections()]
+    with tempfile.TemporaryDirectory() as tmp:
+        path = os.path.join(tmp, "test.pdb")
+        with open(path, "wb") as f:
+            f.write(build_msf(streams))
+        return Pdb(path)
+
+
+def test_pdb_types():
+    proc = dummy.quick_process(4096, bytes([0x8]))
+    proc_address = proc.info().address
+    pdb = load_pdb()
+
+    # Test the layout matches the type records.
+    assert "_NODE" in pdb and "_COLOR" in pdb
+    NODE = pdb["_NODE"]
+    assert pdb["_NODE"] is NODE
+    assert sizeof(NODE) == 40
+    assert NODE._fields_[0][1]._type_ is NODE
+
+    # Test reading the generated structure.
+    proc.write(proc_address, c_uint8 * 40, [0] * 40)
+    proc.write(proc_address, c_uint64, proc_address)
+    proc.write(proc_address + 8, c_uint32, 0b101110101)
+    proc.write(proc_address + 12, c_int32, 2)
+    proc.write(proc_address + 16, c_char * 4, b"root")
+    proc.write(proc_address + 24, c_uint64, 0x1122334455667788)
+    node = proc.read(proc_address, NODE)
+    assert (node.Flags, node.Kind) == (0b101, 0b10111)
+    assert node.Color == pdb["_COLOR"]._enum_.BLUE
+    assert node.Name == b"root"
+    assert node.Low == 0x55667788
+    assert node.Next.deref().Value == 0x1122334455667788
+
+
+def test_pdb_symbols():
+    pdb = load_pdb()
+    assert pdb.symbols["PsActiveProcessHead"] == 0x1010
+    assert pdb.symbols["KiGlobalValue"] == 0x1020
+
+
+def test_pdb_truncated_record():
+    # A record length has to cover at least the record kind.
+    tpi = bytearray(build_tpi() + struct.pack("<HH", 1, LF_POINTER))
+    struct.pack_into("<I", tpi, 16, len(tpi) - 56)
+    with pytest.raises(Exception, match="too short"):
+        load_pdb(bytes(tpi))
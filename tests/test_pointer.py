@@ -18,3 +18,10 @@ def test_ops():
 def test_overflow():
     with pytest.raises(ValueError):
         Pointer(0x3) - 5
+
+
+def test_null():
+    assert Pointer(0x0).is_null()
+    assert not Pointer(0x5).is_null()
+    assert not Pointer(0x0)
+    assert Pointer(0x5)
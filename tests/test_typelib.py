@@ -0,0 +1,99 @@
+from memflow import *
+
+
+class TL_POINT(Structure):
+    _fields_ = [("x", c_int32), ("y", c_int32)]
+
+
+class TL_LIST_ENTRY(Structure):
+    pass
+
+
+TL_LIST_ENTRY._fields_ = [
+    ("Flink", POINTER(TL_LIST_ENTRY)),
+    ("Blink", POINTER(TL_LIST_ENTRY)),
+]
+
+
+def make_library():
+    lib = TypeLibrary(version="1.0")
+    lib.register(TL_POINT)
+    lib.register(TL_LIST_ENTRY, "struct _TL_LIST_ENTRY")
+    return lib
+
+
+def test_type_expressions():
+    lib = make_library()
+    assert lib["TL_POINT"] is TL_POINT
+    assert lib["struct _TL_LIST_ENTRY"] is TL_LIST_ENTRY
+    assert lib["TL_POINT*"]._type_ is TL_POINT
+    assert lib["uint32_t[8]"] is c_uint32 * 8
+    assert lib["unsigned long long"] is c_ulonglong
+    assert sizeof(lib["void * __ptr32"]) == 4
+    assert sizeof(lib["int *[4]"]) == 32
+    assert lib["int (*)[4]"]._type_ is c_int * 4
+    assert lib["const char *"] is c_char_p
+    fn = lib["int32_t (* __ptr64)(uint64_t, void *)"]
+    assert fn._restype_ is c_int32 and fn._argtypes_[0] is c_uint64
+    assert "TL_POINT" in lib and len(lib) == 2
+
+    # Test unknown names and malformed expressions.
+    try:
+        lib["TL_MISSING"]
+        assert False
+    except KeyError:
+        pass
+    try:
+        lib["int ("]
+        assert False
+    except Exception as e:
+        assert "Invalid type expression" in str(e)
+
+
+def test_type_expressions_default():
+    proc = dummy.quick_process(4096, bytes([0x8]))
+    proc_address = proc.info().address
+    TypeLibrary.default().register(TL_POINT)
+
+    # Test strings are accepted anywhere a type is.
+    assert sizeof("TL_POINT*") == 8
+    assert sizeof("TL_POINT[2]") == 16
+    proc.write(proc_address, "TL_POINT", TL_POINT(x=1, y=2))
+    point = proc.read(proc_address, "TL_POINT")
+    assert (point.x, point.y) == (1, 2)
+    proc.write(proc_address + 8, c_uint64, proc_address)
+    ptr = proc.read(proc_address + 8, "TL_POINT*", follow=1)
+    assert ptr.deref().y == 2
+
+
+def test_type_library_merge():
+    lib = make_library()
+    other = TypeLibrary({"TL_POINT": TL_POINT, "point_t": TL_POINT})
+    lib.merge(other)
+    assert lib["point_t"] is TL_POINT
+
+    # Test conflicting names are refused unless replaced.
+    try:
+        lib.merge({"TL_POINT": TL_LIST_ENTRY})
+        assert False
+    except Exception as e:
+        assert "TL_POINT" in str(e)
+    assert lib["TL_POINT"] is TL_POINT
+    lib.merge({"TL_POINT": TL_LIST_ENTRY}, replace=True)
+    assert lib["TL_POINT"] is TL_LIST_ENTRY
+
+
+def test_type_library_json():
+    lib = make_library()
+    lib["point_t"] = TL_POINT
+    loaded = TypeLibrary.from_json(lib.to_json())
+    assert loaded.version == "1.0"
+    assert loaded.names() == ["TL_POINT", "struct _TL_LIST_ENTRY", "point_t"]
+
+    # Test records are rebuilt with the same layout and references.
+    POINT = loaded["TL_POINT"]
+    assert loaded["point_t"] is POINT
+    assert sizeof(POINT) == 8
+    LIST_ENTRY = loaded["struct _TL_LIST_ENTRY"]
+    assert LIST_ENTRY._fields_[0][1]._type_ is LIST_ENTRY
+    assert loaded.to_json() == lib.to_json()
@@ -12,6 +12,10 @@ class TEST(Structure):
 def test_sizeof():
     assert sizeof(TEST) == 0x18
 
+    # Test bare pointers take the width of the data model.
+    assert sizeof(Pointer, "LP64") == 8
+    assert sizeof(Pointer, "ILP32") == 4
+
 
 def test_struct_repr():
     assert repr(POINT(1, 2)) == "POINT(x=1, y=2)"
@@ -21,3 +25,23 @@ def test_struct_repr():
 def test_struct_str():
     assert str(POINT(1, 2)) == "x=1 y=2"
     assert str(TEST([1, 2], 3, 0x0)) == "one=[1, 2] two=3 ptr=0"
+
+
+class VARIANT(Union):
+    _fields_ = [("as_u8", c_uint8), ("as_u32", c_uint32)]
+
+
+def test_union_repr():
+    assert repr(VARIANT(as_u32=5)) == "VARIANT(as_u32=5)"
+    assert str(VARIANT(2, 5)) == "as_u8=2 as_u32=5"
+
+
+class WIN_TYPES(Structure):
+    _fields_ = [("status", c_long), ("name", c_wchar), ("next", POINTER(POINT))]
+
+
+def test_sizeof_data_model():
+    assert sizeof(WIN_TYPES, "LLP64") == 0xE
+    assert sizeof(WIN_TYPES, "LP64") == 0x14
+    assert sizeof(WIN_TYPES, "ILP32") == 0xC
+    assert sizeof(WIN_TYPES, "Win32") == 0xA